@@ -0,0 +1,111 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use proc_macro2::TokenStream;
+use quote::quote;
+use squattr::{ast::Values, attribute::Attribute, derive::Squattr};
+
+mod squattr_route {
+    use super::Squattr;
+
+    #[derive(Squattr)]
+    #[allow(dead_code)]
+    pub struct Route {
+        pub path: String,
+        pub method: Option<String>,
+        pub timeout: u64,
+        pub retries: Option<u8>,
+        pub cached: bool,
+    }
+}
+
+mod darling_route {
+    use darling::FromMeta;
+
+    #[derive(FromMeta)]
+    #[allow(dead_code)]
+    pub struct Route {
+        pub path: String,
+        pub method: Option<String>,
+        pub timeout: u64,
+        pub retries: Option<u8>,
+        #[darling(default)]
+        pub cached: bool,
+    }
+}
+
+mod attribute_derive_route {
+    use attribute_derive::FromAttr;
+
+    #[derive(FromAttr)]
+    #[attribute(ident = route)]
+    #[allow(dead_code)]
+    pub struct Route {
+        pub path: String,
+        pub method: Option<String>,
+        pub timeout: u64,
+        pub retries: Option<u8>,
+        pub cached: bool,
+    }
+}
+
+fn route() -> TokenStream {
+    quote!(
+        path = "/users/{id}",
+        method = "GET",
+        timeout = 30,
+        retries = 3,
+        cached
+    )
+}
+
+fn nested() -> TokenStream {
+    quote! {
+        name = "users",
+        limits(max = 100, min = -1, rate = 0.5),
+        tags("a", "b", "c"),
+        points((1, 2), (3, 4)),
+        !verbose,
+        scale = -inf,
+        cache.ttl = 60,
+    }
+}
+
+fn parse_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("values");
+
+    for (name, tokens) in [("route", route()), ("nested", nested())] {
+        group.bench_function(name, |b| {
+            b.iter(|| syn::parse2::<Values>(black_box(tokens.clone())).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn from_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_values");
+    let tokens = route();
+
+    group.bench_function("squattr", |b| {
+        b.iter(|| squattr_route::Route::from_tokens(black_box(tokens.clone())).unwrap())
+    });
+
+    group.bench_function("darling", |b| {
+        use darling::{FromMeta, ast::NestedMeta};
+
+        b.iter(|| {
+            let items = NestedMeta::parse_meta_list(black_box(tokens.clone())).unwrap();
+            darling_route::Route::from_list(&items).unwrap()
+        })
+    });
+
+    group.bench_function("attribute-derive", |b| {
+        use attribute_derive::FromAttr;
+
+        b.iter(|| attribute_derive_route::Route::from_input(black_box(tokens.clone())).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, parse_values, from_values);
+criterion_main!(benches);
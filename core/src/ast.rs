@@ -1,178 +1,1559 @@
-use std::ops::Index;
+use std::{
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::Index,
+    str::FromStr,
+};
+
+use proc_macro2::{Literal, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    Error, Ident, Lit, LitBool, LitFloat, LitInt, LitStr, Result, Token,
+    meta::ParseNestedMeta,
+    parenthesized,
+    parse::{Parse, ParseStream, discouraged::Speculative},
+    punctuated::{self, Punctuated},
+    spanned::Spanned,
+    token::Paren,
+};
+
+use crate::errors;
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub enum Value {
+    Closure(Closure),
+    Dotted(Dotted),
+    Expr(Expr),
+    Ident(Ident),
+    List(List),
+    Lit(Lit),
+    Neg(Neg),
+    Not(Not),
+    Range(Range),
+    Tuple(Tuple),
+}
+
+impl Value {
+    pub fn identifier(&self) -> Option<String> {
+        match self {
+            Value::Closure(_) => None,
+            Value::Dotted(dotted) => Some(dotted.identifier()),
+            Value::Expr(expr) => Some(expr.identifier()),
+            Value::Ident(ident) => Some(ident.to_string()),
+            Value::List(list) => Some(list.identifier()),
+            Value::Lit(_) => None,
+            Value::Neg(_) => None,
+            Value::Not(not) => Some(not.identifier()),
+            Value::Range(_) => None,
+            Value::Tuple(_) => None,
+        }
+    }
+
+    /// The key of the value, like [identifier](Self::identifier) but borrowed
+    /// rather than written into a new string.
+    ///
+    pub fn ident(&self) -> Option<&Ident> {
+        match self {
+            Value::Closure(_) => None,
+            Value::Dotted(dotted) => Some(&dotted.ident),
+            Value::Expr(expr) => Some(&expr.ident),
+            Value::Ident(ident) => Some(ident),
+            Value::List(list) => Some(&list.ident),
+            Value::Lit(_) => None,
+            Value::Neg(_) => None,
+            Value::Not(not) => Some(&not.ident),
+            Value::Range(_) => None,
+            Value::Tuple(_) => None,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Value::Closure(closure) => closure.span(),
+            Value::Dotted(dotted) => dotted.span(),
+            Value::Expr(expr) => expr.span(),
+            Value::Ident(ident) => ident.span(),
+            Value::List(list) => list.span(),
+            Value::Lit(lit) => lit.span(),
+            Value::Neg(neg) => neg.span(),
+            Value::Not(not) => not.span(),
+            Value::Range(range) => range.span(),
+            Value::Tuple(tuple) => tuple.span(),
+        }
+    }
+
+    /// The span of the value itself, without its key (e.g. `"three"` in
+    /// `count = "three"`, or the parentheses in `key(...)`), for errors about
+    /// the value rather than the key.
+    ///
+    pub fn value_span(&self) -> Span {
+        match self {
+            Value::Dotted(dotted) => dotted.value.value_span(),
+            Value::Expr(expr) => expr.value_span(),
+            Value::List(list) => list.paren_token.span.join(),
+            value => value.span(),
+        }
+    }
+
+    /// The value itself, i.e. the part after the `=` of `key = value`.
+    ///
+    fn inner(&self) -> &Value {
+        match self {
+            Value::Expr(expr) => &expr.value,
+            value => value,
+        }
+    }
+
+    /// The identifier of a bare `ident` or `key = ident`.
+    ///
+    pub fn as_ident(&self) -> Option<&Ident> {
+        match self.inner() {
+            Value::Ident(ident) => Some(ident),
+            _ => None,
+        }
+    }
+
+    /// The literal of a bare `"lit"` or `key = "lit"`.
+    ///
+    pub fn as_lit(&self) -> Option<&Lit> {
+        match self.inner() {
+            Value::Lit(lit) => Some(lit),
+            _ => None,
+        }
+    }
+
+    /// The entries of `key(...)`, `key = (...)` or a bare `(...)`.
+    ///
+    pub fn as_list(&self) -> Option<&Values> {
+        match self.inner() {
+            Value::List(list) => Some(&list.values),
+            Value::Tuple(tuple) => Some(&tuple.values),
+            _ => None,
+        }
+    }
+
+    /// Like [as_ident](Self::as_ident), failing with a type mismatch error
+    /// (e.g. ``expected identifier for `name` ``) for any other value.
+    ///
+    pub fn expect_ident(&self) -> Result<&Ident> {
+        self.as_ident().ok_or_else(|| {
+            errors::Error::type_mismatch(self.identifier(), self, "identifier").into()
+        })
+    }
+
+    /// The value of a string literal, see [as_lit](Self::as_lit).
+    ///
+    pub fn expect_str(&self) -> Result<String> {
+        match self.as_lit() {
+            Some(Lit::Str(lit_str)) => Ok(lit_str.value()),
+            _ => {
+                Err(errors::Error::type_mismatch(self.identifier(), self, "string literal").into())
+            }
+        }
+    }
+
+    /// The value of an integer literal as a `T` (e.g. a `u16`), failing if it
+    /// does not fit.
+    ///
+    pub fn expect_int<T>(&self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.as_lit() {
+            Some(Lit::Int(lit_int)) => lit_int.base10_parse(),
+            _ => Err(errors::Error::type_mismatch(self.identifier(), self, "integer").into()),
+        }
+    }
+
+    /// The value of a boolean literal.
+    ///
+    pub fn expect_bool(&self) -> Result<bool> {
+        match self.as_lit() {
+            Some(Lit::Bool(lit_bool)) => Ok(lit_bool.value()),
+            _ => Err(errors::Error::type_mismatch(self.identifier(), self, "boolean").into()),
+        }
+    }
+
+    /// Like [as_list](Self::as_list), failing for any other value.
+    ///
+    pub fn expect_list(&self) -> Result<&Values> {
+        self.as_list()
+            .ok_or_else(|| errors::Error::type_mismatch(self.identifier(), self, "list").into())
+    }
+
+    /// Parse the entry of a [syn::Attribute::parse_nested_meta] callback (i.e.
+    /// `key`, `key = value` or `key(...)`) into a value.
+    ///
+    pub fn from_nested_meta(meta: &ParseNestedMeta) -> Result<Self> {
+        let ident = meta.path.require_ident()?.clone();
+
+        if meta.input.peek(Token![=]) {
+            let eq_token = meta.input.parse()?;
+            let (value, end_span) = Expr::parse_value(meta.input)?;
+
+            Ok(Self::from_expr(Expr {
+                ident,
+                eq_token,
+                value,
+                end_span,
+            }))
+        } else if meta.input.peek(Paren) {
+            let value_stream;
+
+            let paren_token = parenthesized!(value_stream in meta.input);
+            Ok(Self::List(List::new(
+                ident,
+                paren_token,
+                value_stream.parse()?,
+            )))
+        } else {
+            Ok(Self::Ident(ident))
+        }
+    }
+
+    fn from_expr(expr: Expr) -> Self {
+        // `key = (a, b)` is equivalent to `key(a, b)`
+        match *expr.value {
+            Value::Tuple(Tuple {
+                paren_token,
+                values,
+            }) => Self::List(List::new(expr.ident, paren_token, values)),
+            value => Self::Expr(Expr {
+                value: Box::new(value),
+                ..expr
+            }),
+        }
+    }
+}
+
+/// The kind of value is told apart by peeking at its first two tokens, so that
+/// every value is parsed only once rather than trying each kind in turn.
+///
+impl Parse for Value {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Ident) {
+            if input.peek2(Token![=]) {
+                Ok(Self::from_expr(input.parse()?))
+            } else if input.peek2(Paren) {
+                Ok(Self::List(input.parse()?))
+            } else if input.peek2(Token![.]) {
+                Ok(Self::Dotted(input.parse()?))
+            } else {
+                Ok(Self::Ident(input.parse()?))
+            }
+        } else if let Some(lit) = Arithmetic::parse_folded(input)? {
+            Ok(Self::Lit(Lit::Int(lit)))
+        } else if input.peek(Paren) {
+            Ok(Self::Tuple(input.parse()?))
+        } else if input.peek(Token![!]) {
+            Ok(Self::Not(input.parse()?))
+        } else if input.peek(Token![|]) || input.peek(Token![||]) || input.peek(Token![move]) {
+            Ok(Self::Closure(input.parse()?))
+        } else if input.peek(Token![-]) && input.peek2(Ident) {
+            Ok(Self::Neg(input.parse()?))
+        } else if input.peek(Lit) {
+            let lit = input.parse()?;
+
+            if input.peek(Token![..]) {
+                Ok(Self::Range(Range {
+                    start: lit,
+                    limits: input.parse()?,
+                    end: input.parse()?,
+                }))
+            } else {
+                Ok(Self::Lit(lit))
+            }
+        } else {
+            Err(input.error("type is not supported"))
+        }
+    }
+}
+
+impl ToTokens for Value {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Value::Closure(closure) => closure.to_tokens(tokens),
+            Value::Dotted(dotted) => dotted.to_tokens(tokens),
+            Value::Expr(expr) => expr.to_tokens(tokens),
+            Value::Ident(ident) => ident.to_tokens(tokens),
+            Value::List(list) => list.to_tokens(tokens),
+            Value::Lit(lit) => lit.to_tokens(tokens),
+            Value::Neg(neg) => neg.to_tokens(tokens),
+            Value::Not(not) => not.to_tokens(tokens),
+            Value::Range(range) => range.to_tokens(tokens),
+            Value::Tuple(tuple) => tuple.to_tokens(tokens),
+        }
+    }
+}
+
+/// Values are displayed in their source form (e.g. `key = "v"`), so that error
+/// messages can quote an entry.
+///
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Value::Closure(closure) => closure.fmt(f),
+            Value::Dotted(dotted) => dotted.fmt(f),
+            Value::Expr(expr) => expr.fmt(f),
+            Value::Ident(ident) => ident.fmt(f),
+            Value::List(list) => list.fmt(f),
+            Value::Lit(lit) => lit.to_token_stream().fmt(f),
+            Value::Neg(neg) => neg.fmt(f),
+            Value::Not(not) => not.fmt(f),
+            Value::Range(range) => range.fmt(f),
+            Value::Tuple(tuple) => tuple.fmt(f),
+        }
+    }
+}
+
+/// The comma separated entries of an attribute, kept in source order: the
+/// entries are iterated, indexed and folded in the order they were written,
+/// so that a macro may rely on it (e.g. for a list of middleware).
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Values {
+    span: Span,
+    values: Punctuated<Value, Token![,]>,
+}
+
+impl Values {
+    pub fn new(span: Span) -> Self {
+        Values {
+            span,
+            values: Punctuated::new(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.values.get(index)
+    }
+
+    pub fn iter(&self) -> punctuated::Iter<'_, Value> {
+        self.values.iter()
+    }
+
+    pub fn push(&mut self, value: Value) {
+        self.values.push(value);
+    }
+
+    /// Get the first entry with the key `key` (e.g. `name = "x"`, `name(...)`
+    /// or just `name`).
+    ///
+    pub fn get_by_key(&self, key: &str) -> Option<&Value> {
+        self.position(key).and_then(|index| self.get(index))
+    }
+
+    /// The index of the first entry with the key `key`, e.g. to tell which of
+    /// two keys was written first.
+    ///
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.iter()
+            .position(|value| value.ident().is_some_and(|ident| ident == key))
+    }
+
+    /// Construct values programmatically rather than parsing them from tokens,
+    /// see [ValuesBuilder] and [values!](crate::values!).
+    ///
+    pub fn builder() -> ValuesBuilder {
+        ValuesBuilder::new()
+    }
+
+    /// Parse the values nested in the entry of a
+    /// [syn::Attribute::parse_nested_meta] callback (i.e. the `...` of
+    /// `key(...)`). An entry without a list (i.e. `key`) has no values.
+    ///
+    pub fn from_nested_meta(meta: &ParseNestedMeta) -> Result<Self> {
+        if meta.input.peek(Paren) {
+            let value_stream;
+            parenthesized!(value_stream in meta.input);
+            value_stream.parse()
+        } else if meta.input.is_empty() || meta.input.peek(Token![,]) {
+            Ok(Values::new(meta.path.span()))
+        } else {
+            Err(meta.error("expected a list of values"))
+        }
+    }
+}
+
+impl From<Value> for Values {
+    fn from(value: Value) -> Self {
+        Values {
+            span: value.span(),
+            values: {
+                let mut values = Punctuated::new();
+                values.push(value);
+                values
+            },
+        }
+    }
+}
+
+impl Extend<Value> for Values {
+    fn extend<I: IntoIterator<Item = Value>>(&mut self, iter: I) {
+        self.values.extend(iter);
+    }
+}
+
+impl Index<usize> for Values {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index]
+    }
+}
+
+impl IntoIterator for Values {
+    type Item = Value;
+    type IntoIter = punctuated::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl Parse for Values {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Values {
+            span: input.span(),
+            values: group_dotted(input.parse_terminated(Value::parse, Token![,])?),
+        })
+    }
+}
+
+impl ToTokens for Values {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.values.to_tokens(tokens);
+    }
+}
+
+impl Display for Values {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            value.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Group dotted values by their first segment into a list at the position of
+/// the first occurrence, so that `limits.max = 10, limits.min = 1` is
+/// equivalent to `limits(max = 10, min = 1)`.
+///
+fn group_dotted(values: Punctuated<Value, Token![,]>) -> Punctuated<Value, Token![,]> {
+    let mut grouped = Punctuated::new();
+    let mut groups: Vec<(String, usize)> = Vec::new();
+
+    for value in values {
+        let Dotted { ident, value, .. } = match value {
+            Value::Dotted(dotted) => dotted,
+            value => {
+                grouped.push(value);
+                continue;
+            }
+        };
+
+        let id = ident.to_string();
+
+        match groups.iter().find(|(group, _)| *group == id) {
+            Some(&(_, index)) => {
+                if let Value::List(list) = &mut grouped[index] {
+                    list.values.values.push(*value);
+                }
+            }
+            None => {
+                groups.push((id, grouped.len()));
+                grouped.push(Value::List(List {
+                    paren_token: Paren(ident.span()),
+                    values: Values::from(*value),
+                    ident,
+                }));
+            }
+        }
+    }
+
+    for (_, index) in groups {
+        if let Value::List(list) = &mut grouped[index] {
+            let values = std::mem::take(&mut list.values.values);
+            list.values.values = group_dotted(values);
+        }
+    }
+
+    grouped
+}
+
+/// A dotted key (e.g. `limits.max = 10`), which sets the key after the dot
+/// within the list named before the dot.
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Dotted {
+    pub ident: Ident,
+    pub dot_token: Token![.],
+    pub value: Box<Value>,
+}
+
+impl Dotted {
+    pub fn identifier(&self) -> String {
+        self.ident.to_string()
+    }
+
+    pub fn span(&self) -> Span {
+        self.ident.span()
+    }
+}
+
+impl Parse for Dotted {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self {
+            ident: input.parse()?,
+            dot_token: input.parse()?,
+            value: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Dotted {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+        self.dot_token.to_tokens(tokens);
+        self.value.to_tokens(tokens);
+    }
+}
+
+impl Display for Dotted {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.ident, self.value)
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Expr {
+    pub ident: Ident,
+    pub eq_token: Token![=],
+    pub value: Box<Value>,
+    /// The span of the last token of the value as written (e.g. the closing
+    /// parenthesis in `retry = (1, 2)`), see [Expr::full_error].
+    pub end_span: Span,
+}
+
+impl Expr {
+    pub fn identifier(&self) -> String {
+        self.ident.to_string()
+    }
+
+    /// The span of the key, which errors about the entry as a whole (e.g. a
+    /// duplicate key) point at. See [Expr::full_span] for the entire entry.
+    ///
+    pub fn span(&self) -> Span {
+        self.key_span()
+    }
+
+    /// The span of the key alone (e.g. `count` in `count = 3`).
+    ///
+    pub fn key_span(&self) -> Span {
+        self.ident.span()
+    }
+
+    /// The span of the value alone (e.g. `3` in `count = 3`), for errors about
+    /// what the key was set to.
+    ///
+    pub fn value_span(&self) -> Span {
+        self.value.span()
+    }
+
+    /// The span from the key through the value (e.g. all of `count = 3`), or
+    /// just the key where spans cannot be joined (i.e. in a procedural macro
+    /// on a stable compiler). See [Expr::full_error] to point at the entire
+    /// entry regardless.
+    ///
+    pub fn full_span(&self) -> Span {
+        self.key_span()
+            .join(self.end_span)
+            .unwrap_or(self.key_span())
+    }
+
+    /// An error pointing at the entire entry (e.g. all of `count = 3`). Unlike
+    /// [Expr::full_span], it covers the entry on a stable compiler too, as the
+    /// start and end of an error are spanned separately.
+    ///
+    pub fn full_error<T: Display>(&self, message: T) -> Error {
+        let ident = &self.ident;
+        let end = Token![=](self.end_span);
+
+        Error::new_spanned(quote::quote!(#ident #end), message)
+    }
+
+    /// Parse a value, along with the span of its last token.
+    ///
+    fn parse_value(input: ParseStream) -> Result<(Box<Value>, Span)> {
+        let start = input.cursor();
+        let value = input.parse()?;
+        let end = input.cursor();
+
+        let mut cursor = start;
+        let mut end_span = input.span();
+
+        while cursor != end {
+            let Some((token, next)) = cursor.token_tree() else {
+                break;
+            };
+
+            end_span = match token {
+                TokenTree::Group(group) => group.span_close(),
+                token => token.span(),
+            };
+            cursor = next;
+        }
+
+        Ok((Box::new(value), end_span))
+    }
+}
+
+impl Parse for Expr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ident = input.parse()?;
+        let eq_token = input.parse()?;
+        let (value, end_span) = Self::parse_value(input)?;
+
+        Ok(Self {
+            ident,
+            eq_token,
+            value,
+            end_span,
+        })
+    }
+}
+
+impl ToTokens for Expr {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+        self.eq_token.to_tokens(tokens);
+        self.value.to_tokens(tokens);
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} = {}", self.ident, self.value)
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct List {
+    pub ident: Ident,
+    pub paren_token: Paren,
+    pub values: Values,
+}
+
+impl List {
+    pub fn identifier(&self) -> String {
+        self.ident.to_string()
+    }
+
+    /// The span from the key through the closing parenthesis (e.g. all of
+    /// `opts(a = 1)`), or just the key where spans cannot be joined.
+    ///
+    pub fn span(&self) -> Span {
+        self.ident
+            .span()
+            .join(self.paren_token.span.join())
+            .unwrap_or(self.ident.span())
+    }
+
+    fn new(ident: Ident, paren_token: Paren, values: Values) -> Self {
+        let mut list = Self {
+            ident,
+            paren_token,
+            values,
+        };
+
+        // Errors about the values as a whole, such as a missing key, point at
+        // the entire list rather than at its first value.
+        list.values.span = list.span();
+        list
+    }
+}
+
+impl Parse for List {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let value_stream;
+
+        let ident = input.parse()?;
+        let paren_token = parenthesized!(value_stream in input);
+
+        Ok(Self::new(ident, paren_token, value_stream.parse()?))
+    }
+}
+
+impl ToTokens for List {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+        self.paren_token
+            .surround(tokens, |tokens| self.values.to_tokens(tokens));
+    }
+}
+
+impl Display for List {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}({})", self.ident, self.values)
+    }
+}
+
+impl Index<usize> for List {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index]
+    }
+}
+
+impl IntoIterator for List {
+    type Item = Value;
+    type IntoIter = punctuated::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+/// A list without an identifier (e.g. `(1, 2)` in `points((1, 2), (3, 4))`).
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Tuple {
+    pub paren_token: Paren,
+    pub values: Values,
+}
+
+impl Tuple {
+    pub fn span(&self) -> Span {
+        self.paren_token.span.join()
+    }
+}
+
+impl Parse for Tuple {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let value_stream;
+
+        Ok(Self {
+            paren_token: parenthesized!(value_stream in input),
+            values: value_stream.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Tuple {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.paren_token
+            .surround(tokens, |tokens| self.values.to_tokens(tokens));
+    }
+}
+
+impl Display for Tuple {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "({})", self.values)
+    }
+}
+
+impl IntoIterator for Tuple {
+    type Item = Value;
+    type IntoIter = punctuated::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+/// A negated identifier (e.g. `!verbose`), which explicitly sets a flag to
+/// `false`.
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Not {
+    pub not_token: Token![!],
+    pub ident: Ident,
+}
+
+impl Not {
+    pub fn identifier(&self) -> String {
+        self.ident.to_string()
+    }
+
+    pub fn span(&self) -> Span {
+        self.ident.span()
+    }
+}
+
+impl Parse for Not {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self {
+            not_token: input.parse()?,
+            ident: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Not {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.not_token.to_tokens(tokens);
+        self.ident.to_tokens(tokens);
+    }
+}
+
+impl Display for Not {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "!{}", self.ident)
+    }
+}
+
+/// A negative identifier (e.g. `-inf`), which represents a negative special
+/// value.
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Neg {
+    pub minus_token: Token![-],
+    pub ident: Ident,
+}
+
+impl Neg {
+    pub fn span(&self) -> Span {
+        self.minus_token
+            .span
+            .join(self.ident.span())
+            .unwrap_or(self.ident.span())
+    }
+}
+
+impl Parse for Neg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self {
+            minus_token: input.parse()?,
+            ident: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Neg {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.minus_token.to_tokens(tokens);
+        self.ident.to_tokens(tokens);
+    }
+}
+
+impl Display for Neg {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "-{}", self.ident)
+    }
+}
+
+/// The tokens of a closure (e.g. `|x| x + 1` or `move || run()`), kept as
+/// written so that they can be parsed into a
+/// [syn::ExprClosure](https://docs.rs/syn/latest/syn/struct.ExprClosure.html)
+/// once the `full` feature of syn is enabled.
+///
+/// The body ends at the first comma outside of any delimiters, so a body that
+/// contains one (e.g. turbofish arguments) has to be wrapped in braces.
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Closure {
+    pub tokens: TokenStream,
+}
+
+impl Closure {
+    pub fn span(&self) -> Span {
+        let mut tokens = self.tokens.clone().into_iter();
+        let first = tokens
+            .next()
+            .map_or(Span::call_site(), |token| token.span());
+
+        tokens
+            .last()
+            .and_then(|last| first.join(last.span()))
+            .unwrap_or(first)
+    }
+}
+
+impl Parse for Closure {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut tokens = TokenStream::new();
+
+        if input.peek(Token![move]) {
+            input.parse::<Token![move]>()?.to_tokens(&mut tokens);
+        }
+
+        if input.peek(Token![||]) {
+            input.parse::<Token![||]>()?.to_tokens(&mut tokens);
+        } else {
+            input.parse::<Token![|]>()?.to_tokens(&mut tokens);
+
+            while !input.peek(Token![|]) {
+                if input.is_empty() {
+                    return Err(input.error("expected `|` to close the closure parameters"));
+                }
+                input.parse::<TokenTree>()?.to_tokens(&mut tokens);
+            }
+
+            input.parse::<Token![|]>()?.to_tokens(&mut tokens);
+        }
+
+        if input.is_empty() || input.peek(Token![,]) {
+            return Err(input.error("expected closure body"));
+        }
+
+        while !input.is_empty() && !input.peek(Token![,]) {
+            input.parse::<TokenTree>()?.to_tokens(&mut tokens);
+        }
+
+        Ok(Self { tokens })
+    }
+}
+
+impl ToTokens for Closure {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.tokens.to_tokens(tokens);
+    }
+}
+
+impl Display for Closure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.tokens.fmt(f)
+    }
+}
+
+/// A range between two literals (e.g. `0..8` or `0..=7`).
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone)]
+pub struct Range {
+    pub start: Lit,
+    pub limits: RangeLimits,
+    pub end: Lit,
+}
+
+/// Whether a [Range] includes its end.
+///
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Copy)]
+pub enum RangeLimits {
+    /// `..`, up to but excluding the end.
+    HalfOpen(Token![..]),
+    /// `..=`, up to and including the end.
+    Closed(Token![..=]),
+}
+
+impl Range {
+    pub fn is_inclusive(&self) -> bool {
+        matches!(self.limits, RangeLimits::Closed(_))
+    }
+
+    /// The span from the start through the end (e.g. all of `0..8`), or just
+    /// the start where spans cannot be joined.
+    ///
+    pub fn span(&self) -> Span {
+        self.start
+            .span()
+            .join(self.end.span())
+            .unwrap_or(self.start.span())
+    }
+}
+
+impl Parse for Range {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self {
+            start: input.parse()?,
+            limits: input.parse()?,
+            end: input.parse()?,
+        })
+    }
+}
+
+impl Parse for RangeLimits {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![..=]) {
+            Ok(Self::Closed(input.parse()?))
+        } else {
+            Ok(Self::HalfOpen(input.parse()?))
+        }
+    }
+}
+
+impl ToTokens for Range {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.start.to_tokens(tokens);
+        match &self.limits {
+            RangeLimits::HalfOpen(dot2_token) => dot2_token.to_tokens(tokens),
+            RangeLimits::Closed(dot_dot_eq_token) => dot_dot_eq_token.to_tokens(tokens),
+        }
+        self.end.to_tokens(tokens);
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let limits = if self.is_inclusive() { "..=" } else { ".." };
+
+        write!(
+            f,
+            "{}{}{}",
+            self.start.to_token_stream(),
+            limits,
+            self.end.to_token_stream()
+        )
+    }
+}
+
+/// Integer arithmetic within a value (e.g. `4 * 1024` or `60 * (5 + 1)`), of
+/// literals, `+`, `-`, `*`, `/` and parentheses.
+///
+/// It is folded into the literal of its result while parsing, so that the
+/// value parses as any other integer.
+///
+enum Arithmetic {
+    Int(LitInt),
+    Neg(Token![-], Box<Arithmetic>),
+    Paren(Paren, Box<Arithmetic>),
+    Binary(Box<Arithmetic>, BinOp, Box<Arithmetic>),
+}
+
+#[derive(Clone, Copy)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Arithmetic {
+    /// Parse and evaluate an arithmetic expression, if the input holds one
+    /// as a whole value (i.e. up to the next comma). Anything else, such as a
+    /// lone literal or a tuple, is left to be parsed as usual.
+    ///
+    fn parse_folded(input: ParseStream) -> Result<Option<LitInt>> {
+        if !(input.peek(Lit) || input.peek(Paren) || input.peek(Token![-])) {
+            return Ok(None);
+        }
+
+        let fork = input.fork();
+
+        match Self::parse_sum(&fork) {
+            Ok(expr) if expr.is_arithmetic() && (fork.is_empty() || fork.peek(Token![,])) => {
+                input.advance_to(&fork);
+
+                let span = expr.span();
+                Ok(Some(LitInt::new(&expr.evaluate()?.to_string(), span)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_sum(input: ParseStream) -> Result<Self> {
+        let mut expr = Self::parse_product(input)?;
+
+        loop {
+            let op = if input.peek(Token![+]) {
+                input.parse::<Token![+]>()?;
+                BinOp::Add
+            } else if input.peek(Token![-]) {
+                input.parse::<Token![-]>()?;
+                BinOp::Sub
+            } else {
+                return Ok(expr);
+            };
+
+            expr = Self::Binary(Box::new(expr), op, Box::new(Self::parse_product(input)?));
+        }
+    }
+
+    fn parse_product(input: ParseStream) -> Result<Self> {
+        let mut expr = Self::parse_operand(input)?;
+
+        loop {
+            let op = if input.peek(Token![*]) {
+                input.parse::<Token![*]>()?;
+                BinOp::Mul
+            } else if input.peek(Token![/]) {
+                input.parse::<Token![/]>()?;
+                BinOp::Div
+            } else {
+                return Ok(expr);
+            };
+
+            expr = Self::Binary(Box::new(expr), op, Box::new(Self::parse_operand(input)?));
+        }
+    }
+
+    fn parse_operand(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![-]) {
+            Ok(Self::Neg(
+                input.parse()?,
+                Box::new(Self::parse_operand(input)?),
+            ))
+        } else if input.peek(Paren) {
+            let content;
+            let paren_token = parenthesized!(content in input);
+            let expr = Self::parse_sum(&content)?;
+
+            if !content.is_empty() {
+                return Err(content.error("expected an operator"));
+            }
+
+            Ok(Self::Paren(paren_token, Box::new(expr)))
+        } else {
+            Ok(Self::Int(input.parse()?))
+        }
+    }
+
+    /// Whether the expression is more than a (negated) literal or a tuple of
+    /// one, i.e. whether it has an operator or a negated group (e.g. `-(5)`),
+    /// which nothing else parses.
+    ///
+    fn is_arithmetic(&self) -> bool {
+        match self {
+            Self::Int(_) => false,
+            Self::Neg(_, expr) => matches!(**expr, Self::Paren(..)) || expr.is_arithmetic(),
+            Self::Paren(_, expr) => expr.is_arithmetic(),
+            Self::Binary(..) => true,
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Self::Int(lit) => Some(lit.span()),
+            Self::Neg(minus_token, expr) => minus_token.span.join(expr.span()),
+            Self::Paren(paren_token, _) => Some(paren_token.span.join()),
+            Self::Binary(lhs, _, rhs) => lhs.span().join(rhs.span()),
+        }
+        .unwrap_or_else(|| self.first_span())
+    }
+
+    fn first_span(&self) -> Span {
+        match self {
+            Self::Int(lit) => lit.span(),
+            Self::Neg(minus_token, _) => minus_token.span,
+            Self::Paren(paren_token, _) => paren_token.span.open(),
+            Self::Binary(lhs, _, _) => lhs.first_span(),
+        }
+    }
+
+    /// Evaluate the expression, failing at its span if it overflows or divides
+    /// by zero.
+    ///
+    fn evaluate(&self) -> Result<i128> {
+        let result = match self {
+            Self::Int(lit) => return lit.base10_parse(),
+            Self::Neg(_, expr) => expr.evaluate()?.checked_neg(),
+            Self::Paren(_, expr) => return expr.evaluate(),
+            Self::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate()?, rhs.evaluate()?);
+
+                match op {
+                    BinOp::Add => lhs.checked_add(rhs),
+                    BinOp::Sub => lhs.checked_sub(rhs),
+                    BinOp::Mul => lhs.checked_mul(rhs),
+                    BinOp::Div if rhs == 0 => {
+                        return Err(Error::new(self.span(), "attempt to divide by zero"));
+                    }
+                    BinOp::Div => lhs.checked_div(rhs),
+                }
+            }
+        };
+
+        result.ok_or_else(|| Error::new(self.span(), "arithmetic overflow"))
+    }
+}
+
+/// A builder for [Values], e.g. for tests and code generators:
+///
+/// ```ignore
+/// let values = Values::builder()
+///     .key_str("name", "x")
+///     .flag("verbose")
+///     .list("features", Values::builder().flag("a").flag("b"))
+///     .build();
+/// ```
+///
+/// All tokens are given the call site span. Keys must be valid identifiers.
+///
+#[derive(Clone)]
+pub struct ValuesBuilder {
+    values: Values,
+}
+
+impl ValuesBuilder {
+    pub fn new() -> Self {
+        Self {
+            values: Values::new(Span::call_site()),
+        }
+    }
+
+    /// Add an entry without a key (e.g. `"a"` in `list("a", "b")`).
+    ///
+    pub fn value(mut self, value: impl Into<Value>) -> Self {
+        self.values.push(value.into());
+        self
+    }
+
+    /// Add a key with a value (e.g. `retries = 3`).
+    ///
+    pub fn key(self, key: &str, value: impl Into<Value>) -> Self {
+        let value = value.into();
+
+        self.value(Value::Expr(Expr {
+            ident: Ident::new(key, Span::call_site()),
+            eq_token: Default::default(),
+            end_span: value.span(),
+            value: Box::new(value),
+        }))
+    }
+
+    /// Add a key with a string value (e.g. `name = "x"`).
+    ///
+    pub fn key_str(self, key: &str, value: &str) -> Self {
+        self.key(key, value)
+    }
+
+    /// Add a flag (e.g. `verbose`).
+    ///
+    pub fn flag(self, key: &str) -> Self {
+        self.value(Ident::new(key, Span::call_site()))
+    }
+
+    /// Add a negated flag (e.g. `!verbose`).
+    ///
+    pub fn not(self, key: &str) -> Self {
+        self.value(Value::Not(Not {
+            not_token: Default::default(),
+            ident: Ident::new(key, Span::call_site()),
+        }))
+    }
+
+    /// Add a list (e.g. `features(a, b)`).
+    ///
+    pub fn list(self, key: &str, values: impl Into<Values>) -> Self {
+        self.value(Value::List(List {
+            ident: Ident::new(key, Span::call_site()),
+            paren_token: Default::default(),
+            values: values.into(),
+        }))
+    }
 
-use proc_macro2::Span;
-use syn::{
-    Ident, Lit, Result, Token, parenthesized,
-    parse::{Parse, ParseStream, discouraged::Speculative},
-    punctuated::{self, Punctuated},
-    token::Paren,
-};
+    /// Add a list without a key (e.g. `(1, 2)` in `points((1, 2), (3, 4))`).
+    ///
+    pub fn tuple(self, values: impl Into<Values>) -> Self {
+        self.value(Value::Tuple(Tuple {
+            paren_token: Default::default(),
+            values: values.into(),
+        }))
+    }
 
-#[cfg_attr(feature = "debug", derive(Debug))]
-pub enum Value {
-    Expr(Expr),
-    Ident(Ident),
-    List(List),
-    Lit(Lit),
+    pub fn build(self) -> Values {
+        self.values
+    }
 }
 
-impl Value {
-    pub fn identifier(&self) -> Option<String> {
-        match self {
-            Value::Expr(expr) => Some(expr.identifier()),
-            Value::Ident(ident) => Some(ident.to_string()),
-            Value::List(list) => Some(list.identifier()),
-            Value::Lit(_) => None,
-        }
+impl Default for ValuesBuilder {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    pub fn span(&self) -> Span {
-        match self {
-            Value::Expr(expr) => expr.span(),
-            Value::Ident(ident) => ident.span(),
-            Value::List(list) => list.span(),
-            Value::Lit(lit) => lit.span(),
-        }
+impl From<ValuesBuilder> for Values {
+    fn from(builder: ValuesBuilder) -> Self {
+        builder.build()
     }
 }
 
-impl Parse for Value {
-    fn parse(input: ParseStream) -> Result<Self> {
-        if let Ok(expr) = input.try_parse::<Expr>() {
-            Ok(Self::Expr(expr))
-        } else if let Ok(list) = input.try_parse::<List>() {
-            Ok(Self::List(list))
-        } else if let Ok(lit) = input.try_parse::<Lit>() {
-            Ok(Self::Lit(lit))
-        } else if let Ok(ident) = input.try_parse::<Ident>() {
-            Ok(Self::Ident(ident))
-        } else {
-            Err(input.error("type is not supported"))
-        }
-    }
+/// Construct [Values] from attribute syntax without parsing tokens, e.g.
+/// `values! { name = "x", verbose, features(a, b) }`.
+///
+/// The values of keys are Rust expressions converted through `Into<Value>`
+/// (e.g. `retries = 3` or `name = some_string`). Lists may contain literals,
+/// flags, keys, lists and tuples.
+///
+#[macro_export]
+macro_rules! values {
+    (@entries $builder:expr;) => {
+        $builder.build()
+    };
+    (@entries $builder:expr; $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::values!(@entries $builder.key(::std::stringify!($key), $value); $($($rest)*)?)
+    };
+    (@entries $builder:expr; $key:ident ( $($values:tt)* ) $(, $($rest:tt)*)?) => {
+        $crate::values!(
+            @entries $builder.list(::std::stringify!($key), $crate::values!($($values)*));
+            $($($rest)*)?
+        )
+    };
+    (@entries $builder:expr; ! $key:ident $(, $($rest:tt)*)?) => {
+        $crate::values!(@entries $builder.not(::std::stringify!($key)); $($($rest)*)?)
+    };
+    (@entries $builder:expr; $key:ident $(, $($rest:tt)*)?) => {
+        $crate::values!(@entries $builder.flag(::std::stringify!($key)); $($($rest)*)?)
+    };
+    (@entries $builder:expr; ( $($values:tt)* ) $(, $($rest:tt)*)?) => {
+        $crate::values!(@entries $builder.tuple($crate::values!($($values)*)); $($($rest)*)?)
+    };
+    (@entries $builder:expr; $value:literal $(, $($rest:tt)*)?) => {
+        $crate::values!(@entries $builder.value($value); $($($rest)*)?)
+    };
+    ($($entries:tt)*) => {
+        $crate::values!(@entries $crate::ast::Values::builder(); $($entries)*)
+    };
 }
 
-#[cfg_attr(feature = "debug", derive(Debug))]
-pub struct Values {
-    span: Span,
-    values: Punctuated<Value, Token![,]>,
+impl From<Ident> for Value {
+    fn from(ident: Ident) -> Self {
+        Value::Ident(ident)
+    }
 }
 
-impl Values {
-    pub fn span(&self) -> Span {
-        self.span
+impl From<Lit> for Value {
+    fn from(lit: Lit) -> Self {
+        Value::Lit(lit)
     }
 }
 
-impl From<Value> for Values {
-    fn from(value: Value) -> Self {
-        Values {
-            span: value.span(),
-            values: {
-                let mut values = Punctuated::new();
-                values.push(value);
-                values
-            },
-        }
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Lit(Lit::Str(LitStr::new(value, Span::call_site())))
     }
 }
 
-impl Index<usize> for Values {
-    type Output = Value;
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::from(value.as_str())
+    }
+}
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.values[index]
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Lit(Lit::Bool(LitBool::new(value, Span::call_site())))
     }
 }
 
-impl IntoIterator for Values {
-    type Item = Value;
-    type IntoIter = punctuated::IntoIter<Self::Item>;
+macro_rules! impl_from_integers {
+    ($( ($t:ty, $f:ident) ),*) => {
+        $(impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                Value::Lit(Lit::Int(LitInt::from(Literal::$f(value))))
+            }
+        })*
+    };
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.values.into_iter()
-    }
+impl_from_integers!(
+    (usize, usize_unsuffixed),
+    (u128, u128_unsuffixed),
+    (u64, u64_unsuffixed),
+    (u32, u32_unsuffixed),
+    (u16, u16_unsuffixed),
+    (u8, u8_unsuffixed),
+    (isize, isize_unsuffixed),
+    (i128, i128_unsuffixed),
+    (i64, i64_unsuffixed),
+    (i32, i32_unsuffixed),
+    (i16, i16_unsuffixed),
+    (i8, i8_unsuffixed)
+);
+
+macro_rules! impl_from_floats {
+    ($( ($t:ty, $f:ident) ),*) => {
+        $(impl From<$t> for Value {
+            /// Special values are written as `nan`, `inf` and `-inf`.
+            ///
+            fn from(value: $t) -> Self {
+                if value.is_nan() {
+                    Value::Ident(Ident::new("nan", Span::call_site()))
+                } else if value.is_infinite() && value.is_sign_negative() {
+                    Value::Neg(Neg {
+                        minus_token: Default::default(),
+                        ident: Ident::new("inf", Span::call_site()),
+                    })
+                } else if value.is_infinite() {
+                    Value::Ident(Ident::new("inf", Span::call_site()))
+                } else {
+                    Value::Lit(Lit::Float(LitFloat::from(Literal::$f(value))))
+                }
+            }
+        })*
+    };
 }
 
-impl Parse for Values {
-    fn parse(input: ParseStream) -> Result<Self> {
-        Ok(Values {
-            span: input.span(),
-            values: input.parse_terminated(Value::parse, Token![,])?,
-        })
+impl_from_floats!((f64, f64_unsuffixed), (f32, f32_unsuffixed));
+
+// Values are compared and hashed structurally: identifiers by their string and
+// literals by their value, ignoring spans and punctuation tokens. This makes
+// parsed values usable as cache keys and in test assertions.
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Closure(a), Value::Closure(b)) => a == b,
+            (Value::Dotted(a), Value::Dotted(b)) => a == b,
+            (Value::Expr(a), Value::Expr(b)) => a == b,
+            (Value::Ident(a), Value::Ident(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Lit(a), Value::Lit(b)) => lit_eq(a, b),
+            (Value::Neg(a), Value::Neg(b)) => a == b,
+            (Value::Not(a), Value::Not(b)) => a == b,
+            (Value::Range(a), Value::Range(b)) => a == b,
+            (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            _ => false,
+        }
     }
 }
 
-#[cfg_attr(feature = "debug", derive(Debug))]
-pub struct Expr {
-    pub ident: Ident,
-    pub eq_token: Token![=],
-    pub value: Box<Value>,
-}
+impl Eq for Value {}
 
-impl Expr {
-    pub fn identifier(&self) -> String {
-        self.ident.to_string()
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+
+        match self {
+            Value::Closure(closure) => closure.hash(state),
+            Value::Dotted(dotted) => dotted.hash(state),
+            Value::Expr(expr) => expr.hash(state),
+            Value::Ident(ident) => ident.hash(state),
+            Value::List(list) => list.hash(state),
+            Value::Lit(lit) => lit_hash(lit, state),
+            Value::Neg(neg) => neg.hash(state),
+            Value::Not(not) => not.hash(state),
+            Value::Range(range) => range.hash(state),
+            Value::Tuple(tuple) => tuple.hash(state),
+        }
     }
+}
 
-    pub fn span(&self) -> Span {
-        self.ident.span()
+impl PartialEq for Values {
+    fn eq(&self, other: &Self) -> bool {
+        self.values.len() == other.values.len()
+            && self.iter().zip(other.iter()).all(|(a, b)| a == b)
     }
 }
 
-impl Parse for Expr {
-    fn parse(input: ParseStream) -> Result<Self> {
-        Ok(Self {
-            ident: input.parse()?,
-            eq_token: input.parse()?,
-            value: input.parse()?,
-        })
+impl Eq for Values {}
+
+impl Hash for Values {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.values.len().hash(state);
+        self.iter().for_each(|value| value.hash(state));
     }
 }
 
-#[cfg_attr(feature = "debug", derive(Debug))]
-pub struct List {
-    pub ident: Ident,
-    pub paren_token: Paren,
-    pub values: Values,
+macro_rules! impl_structural_eq {
+    ($( $t:ident { $( $field:ident ),+ } ),*) => {
+        $(impl PartialEq for $t {
+            fn eq(&self, other: &Self) -> bool {
+                $( self.$field == other.$field )&&+
+            }
+        }
+
+        impl Eq for $t {}
+
+        impl Hash for $t {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                $( self.$field.hash(state); )+
+            }
+        })*
+    };
 }
 
-impl List {
-    pub fn identifier(&self) -> String {
-        self.ident.to_string()
+/// Closures are compared by their tokens, regardless of spacing.
+///
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        self.tokens.to_string() == other.tokens.to_string()
     }
+}
 
-    pub fn span(&self) -> Span {
-        self.ident.span()
+impl Eq for Closure {}
+
+impl Hash for Closure {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tokens.to_string().hash(state);
     }
 }
 
-impl Parse for List {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let value_stream;
+impl PartialEq for Range {
+    fn eq(&self, other: &Self) -> bool {
+        lit_eq(&self.start, &other.start)
+            && self.is_inclusive() == other.is_inclusive()
+            && lit_eq(&self.end, &other.end)
+    }
+}
 
-        Ok(Self {
-            ident: input.parse()?,
-            paren_token: parenthesized!(value_stream in input),
-            values: value_stream.parse()?,
-        })
+impl Eq for Range {}
+
+impl Hash for Range {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        lit_hash(&self.start, state);
+        self.is_inclusive().hash(state);
+        lit_hash(&self.end, state);
     }
 }
 
-impl Index<usize> for List {
-    type Output = Value;
+impl_structural_eq!(
+    Dotted { ident, value },
+    Expr { ident, value },
+    List { ident, values },
+    Tuple { values },
+    Not { ident },
+    Neg { ident }
+);
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.values[index]
+fn lit_eq(a: &Lit, b: &Lit) -> bool {
+    match (a, b) {
+        (Lit::Str(a), Lit::Str(b)) => a.value() == b.value() && a.suffix() == b.suffix(),
+        (Lit::ByteStr(a), Lit::ByteStr(b)) => a.value() == b.value() && a.suffix() == b.suffix(),
+        (Lit::CStr(a), Lit::CStr(b)) => a.value() == b.value() && a.suffix() == b.suffix(),
+        (Lit::Byte(a), Lit::Byte(b)) => a.value() == b.value() && a.suffix() == b.suffix(),
+        (Lit::Char(a), Lit::Char(b)) => a.value() == b.value() && a.suffix() == b.suffix(),
+        (Lit::Int(a), Lit::Int(b)) => {
+            a.base10_digits() == b.base10_digits() && a.suffix() == b.suffix()
+        }
+        (Lit::Float(a), Lit::Float(b)) => {
+            a.base10_digits() == b.base10_digits() && a.suffix() == b.suffix()
+        }
+        (Lit::Bool(a), Lit::Bool(b)) => a.value() == b.value(),
+        (a, b) => a.to_token_stream().to_string() == b.to_token_stream().to_string(),
     }
 }
 
-impl IntoIterator for List {
-    type Item = Value;
-    type IntoIter = punctuated::IntoIter<Self::Item>;
+fn lit_hash<H: Hasher>(lit: &Lit, state: &mut H) {
+    std::mem::discriminant(lit).hash(state);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.values.into_iter()
+    match lit {
+        Lit::Str(lit) => (lit.value(), lit.suffix()).hash(state),
+        Lit::ByteStr(lit) => (lit.value(), lit.suffix()).hash(state),
+        Lit::CStr(lit) => (lit.value(), lit.suffix()).hash(state),
+        Lit::Byte(lit) => (lit.value(), lit.suffix()).hash(state),
+        Lit::Char(lit) => (lit.value(), lit.suffix()).hash(state),
+        Lit::Int(lit) => (lit.base10_digits(), lit.suffix()).hash(state),
+        Lit::Float(lit) => (lit.base10_digits(), lit.suffix()).hash(state),
+        Lit::Bool(lit) => lit.value().hash(state),
+        lit => lit.to_token_stream().to_string().hash(state),
     }
 }
 
@@ -201,10 +1582,160 @@ impl TryParseExt for ParseStream<'_> {
 
 #[cfg(test)]
 mod tests {
-    use quote::quote;
+    use quote::{ToTokens, quote};
     use syn::{Lit, parse2};
 
-    use super::{Expr, List, Value};
+    use super::{Expr, List, Not, Tuple, Value, Values};
+
+    #[test]
+    fn fold_arithmetic() {
+        let values: Values = parse2(quote! {
+            len = 4 * 1024, timeout = (60 - 1) * -5 + 10 / 3, sizes(1 + 1, 3), single = (5)
+        })
+        .unwrap();
+
+        let int = |key| match values.get_by_key(key).unwrap().as_lit() {
+            Some(Lit::Int(lit)) => lit.base10_parse::<i64>().unwrap(),
+            _ => panic!("`{}` is not an integer", key),
+        };
+
+        assert_eq!(int("len"), 4096);
+        assert_eq!(int("timeout"), -292);
+        assert_eq!(
+            values.get_by_key("sizes").unwrap().to_string(),
+            "sizes(2, 3)"
+        );
+        assert!(matches!(
+            values.get_by_key("single").unwrap(),
+            Value::List(list) if list.values.len() == 1
+        ));
+
+        let error = |input| match parse2::<Values>(input) {
+            Ok(_) => panic!("the arithmetic is folded"),
+            Err(error) => error.to_string(),
+        };
+
+        assert_eq!(
+            error(quote!(len = 170141183460469231731687303715884105727 * 2)),
+            "arithmetic overflow"
+        );
+        assert_eq!(
+            error(quote!(len = 1 / (2 - 2))),
+            "attempt to divide by zero"
+        );
+    }
+
+    #[test]
+    fn span_entire_entries() {
+        let expr: Expr = syn::parse_str("retry = (1, 2)").unwrap();
+
+        assert_eq!(expr.end_span.source_text().as_deref(), Some(")"));
+        assert_eq!(
+            expr.full_span().source_text().as_deref(),
+            Some("retry = (1, 2)")
+        );
+
+        let tokens = expr
+            .full_error("invalid retry")
+            .to_compile_error()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(tokens.first().unwrap().span().start().column, 0);
+        assert_eq!(tokens.last().unwrap().span().start().column, 13);
+    }
+
+    #[test]
+    fn parse_closures() {
+        let values: Values = parse2(quote! {
+            map = |x| x + 1, visit(|a, b: Vec<u8>| { a.push(b); a }), run = move || go(1, 2), last
+        })
+        .unwrap();
+
+        assert_eq!(values.len(), 4);
+        assert_eq!(
+            values.get_by_key("map").unwrap().to_string(),
+            "map = | x | x + 1"
+        );
+        assert!(matches!(
+            values.get_by_key("visit").unwrap(),
+            Value::List(list) if matches!(list.values.iter().next(), Some(Value::Closure(_)))
+        ));
+        assert_eq!(
+            values.get_by_key("run").unwrap().to_string(),
+            "run = move || go (1 , 2)"
+        );
+        assert!(parse2::<Values>(quote!(map = |x|)).is_err());
+        assert!(parse2::<Values>(quote!(map = |x, y)).is_err());
+    }
+
+    #[test]
+    fn parse_ranges() {
+        let values: Values = parse2(quote!(bits = 0..8, bytes(1..=4, 0x10..0x20))).unwrap();
+
+        assert!(matches!(
+            values.get_by_key("bits").unwrap(),
+            Value::Expr(Expr { value, .. })
+                if matches!(value.as_ref(), Value::Range(range) if !range.is_inclusive())
+        ));
+        assert_eq!(values.to_string(), "bits = 0..8, bytes(1..=4, 0x10..0x20)");
+        assert!(parse2::<Value>(quote!(0..8)).unwrap() != parse2::<Value>(quote!(0..=8)).unwrap());
+        assert!(parse2::<Values>(quote!(bits = 0..)).is_err());
+    }
+
+    #[test]
+    fn keep_source_order() {
+        let values: Values = parse2(quote! {
+            auth, log = "debug", cors(origin = "*"), auth.strict = true, !cache
+        })
+        .unwrap();
+
+        assert_eq!(
+            values
+                .iter()
+                .map(|value| value.identifier().unwrap())
+                .collect::<Vec<_>>(),
+            ["auth", "log", "cors", "auth", "cache"]
+        );
+        assert_eq!(values.position("auth"), Some(0));
+        assert_eq!(values.position("cors"), Some(2));
+        assert_eq!(values.position("cache"), Some(4));
+        assert_eq!(values.position("missing"), None);
+        assert_eq!(
+            values.get_by_key("log").unwrap().to_string(),
+            r#"log = "debug""#
+        );
+    }
+
+    #[test]
+    fn access_values() {
+        let values: Values = parse2(quote! {
+            name = "x", port = 8080, verbose, mode = fast, features(a, b), enabled = 1
+        })
+        .unwrap();
+
+        let get = |key| values.get_by_key(key).unwrap();
+
+        assert_eq!(get("name").expect_str().unwrap(), "x");
+        assert_eq!(get("port").expect_int::<u16>().unwrap(), 8080);
+        assert_eq!(get("verbose").expect_ident().unwrap(), "verbose");
+        assert_eq!(get("mode").expect_ident().unwrap(), "fast");
+        assert_eq!(get("features").expect_list().unwrap().len(), 2);
+        assert!(get("name").as_list().is_none());
+
+        assert_eq!(
+            get("port").expect_int::<u8>().unwrap_err().to_string(),
+            "number too large to fit in target type"
+        );
+        assert_eq!(
+            get("port").expect_str().unwrap_err().to_string(),
+            "squattr(E004): expected string literal for `port`"
+        );
+        assert_eq!(
+            get("enabled").expect_bool().unwrap_err().to_string(),
+            "squattr(E004): expected boolean for `enabled`"
+        );
+    }
 
     #[test]
     fn parse_expr() {
@@ -218,7 +1749,7 @@ mod tests {
 
                 match value.as_ref() {
                     Value::Lit(Lit::Bool(lit_bool)) => {
-                        assert_eq!(lit_bool.value(), true);
+                        assert!(lit_bool.value());
                     }
                     _ => panic!("input is not a boolean"),
                 }
@@ -241,6 +1772,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_dotted() {
+        let input = quote! {
+            limits.max = 10, name = "x", limits.inner.min = 1
+        };
+
+        let values = parse2::<Values>(input).unwrap();
+        let identifiers = |values: &Values| {
+            values
+                .iter()
+                .map(|value| value.identifier().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(identifiers(&values), ["limits", "name"]);
+
+        match &values[0] {
+            Value::List(List { values, .. }) => {
+                assert_eq!(identifiers(values), ["max", "inner"]);
+                assert!(
+                    matches!(&values[1], Value::List(List { values, .. }) if identifiers(values) == ["min"])
+                );
+            }
+            _ => panic!("input is not grouped into a list"),
+        }
+    }
+
+    #[test]
+    fn parse_tuples() {
+        let input = quote! {
+            points((1, 2), (3, 4))
+        };
+
+        match parse2::<Value>(input).unwrap() {
+            Value::List(list) => {
+                for value in list {
+                    match value {
+                        Value::Tuple(Tuple { values, .. }) => assert_eq!(values.iter().count(), 2),
+                        _ => panic!("input is not a tuple"),
+                    }
+                }
+            }
+            _ => panic!("input is not a list"),
+        }
+    }
+
+    #[test]
+    fn parse_parenthesized_expr() {
+        let input = quote! {
+            deps = (serde, tokio)
+        };
+
+        match parse2::<Value>(input).unwrap() {
+            Value::List(List { ident, values, .. }) => {
+                assert_eq!(ident.to_string(), "deps");
+                assert_eq!(values.iter().count(), 2);
+            }
+            _ => panic!("input is not a list"),
+        }
+    }
+
+    #[test]
+    fn parse_not() {
+        let input = quote! {
+            !verbose
+        };
+
+        match parse2::<Value>(input).unwrap() {
+            Value::Not(Not { ident, .. }) => {
+                assert_eq!(ident.to_string(), "verbose")
+            }
+            _ => panic!("input is not a negated ident"),
+        }
+    }
+
     #[test]
     fn parse_list_of_string_literals() {
         let input = quote! {
@@ -338,4 +1944,104 @@ mod tests {
             _ => panic!("parsed value is not a list"),
         }
     }
+
+    #[test]
+    fn write_values() {
+        let input = quote! {
+            key = "value", flag, !negated, list(1, -inf, (a, b)), limits.max = 1.5,
+        };
+
+        let values = parse2::<Values>(input).unwrap();
+
+        assert_eq!(
+            values.to_token_stream().to_string(),
+            quote! {
+                key = "value", flag, !negated, list(1, -inf, (a, b)), limits(max = 1.5)
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn build_values() {
+        let values = Values::builder()
+            .key_str("name", "x")
+            .flag("verbose")
+            .not("quiet")
+            .key("retries", 3)
+            .key("ratio", -0.5)
+            .list("features", Values::builder().flag("a").value("b"))
+            .list(
+                "points",
+                Values::builder().tuple(Values::builder().value(1).value(2)),
+            )
+            .build();
+
+        let expect = quote! {
+            name = "x", verbose, !quiet, retries = 3, ratio = -0.5, features(a, "b"), points((1, 2))
+        };
+
+        assert_eq!(values.to_token_stream().to_string(), expect.to_string());
+
+        let values = crate::values! {
+            name = "x", verbose, !quiet, retries = 3, ratio = -0.5, features(a, "b"), points((1, 2))
+        };
+
+        assert_eq!(values.to_token_stream().to_string(), expect.to_string());
+
+        let name = String::from("x");
+        let values = crate::values!(name = name, features());
+
+        assert_eq!(
+            values.to_token_stream().to_string(),
+            quote!(name = "x", features()).to_string()
+        );
+    }
+
+    #[test]
+    fn compare_values() {
+        use std::collections::HashSet;
+
+        let a = parse2::<Values>(quote!(name = "x", list(0x10, 1.5), !flag)).unwrap();
+        let b = crate::values!(name = "x", list(16, 1.5), !flag);
+        let c = crate::values!(name = "y", list(16, 1.5), !flag);
+
+        assert!(a == b);
+        assert!(a != c);
+        assert!(a.clone() == a);
+
+        let set = [a, b, c].into_iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn display_values() {
+        let values = parse2::<Values>(quote! {
+            key = "v\"", list(a, 1.5), (1, 2), !flag, ratio = -inf, limits.max = 10
+        })
+        .unwrap();
+
+        assert_eq!(
+            values.to_string(),
+            r#"key = "v\"", list(a, 1.5), (1, 2), !flag, ratio = -inf, limits(max = 10)"#
+        );
+        assert_eq!(values[1].to_string(), "list(a, 1.5)");
+    }
+
+    #[test]
+    fn inspect_values() {
+        let values = parse2::<Values>(quote!(name = "x", list(a, b), flag, 1)).unwrap();
+
+        assert_eq!(values.len(), 4);
+        assert!(!values.is_empty());
+        assert!(values.get(3).is_some());
+        assert!(values.get(4).is_none());
+        assert_eq!(values.iter().filter_map(Value::identifier).count(), 3);
+
+        assert_eq!(values.get_by_key("list").unwrap().to_string(), "list(a, b)");
+        assert_eq!(values.get_by_key("flag").unwrap().to_string(), "flag");
+        assert!(values.get_by_key("a").is_none());
+
+        assert!(Values::builder().build().is_empty());
+    }
 }
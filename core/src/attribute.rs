@@ -1,17 +1,68 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use syn::{
-    Error, Meta, MetaList, MetaNameValue, Path, Result, parse::ParseStream, spanned::Spanned,
+    AttrStyle, Error, Meta, MetaList, MetaNameValue, Path, Result, meta::ParseNestedMeta,
+    parse::ParseStream, spanned::Spanned,
 };
 
 use crate::{
-    ast::{List, Value, Values},
-    errors::ErrorsExt,
+    ast::{List, Tuple, Value, Values},
+    errors::{self, Accumulator},
+    runtime::ParseOptions,
+    schema::{Kind, Schema},
     types::{ParseValue, format_error},
 };
 
 pub trait Attribute: Sized {
+    /// The path of the attribute (e.g. `"route"` for `#[route(...)]`), used by
+    /// [Attribute::extract] and [Attribute::extract_one].
+    ///
+    const PATH: Option<&'static str> = None;
+
+    /// A description of the keys of the attribute, see [Schema].
+    ///
+    const SCHEMA: Schema = Schema::Opaque;
+
     fn from_values(values: Values) -> Result<Self>;
 
+    /// Parse like [Attribute::from_values], keeping the kinds of the errors
+    /// (see [errors::Error]), so that they can be placed in the context of the
+    /// key or attribute they were given at without rewriting their messages.
+    ///
+    /// Derived attributes implement [Attribute::from_values] through this.
+    /// Otherwise, every error is a [errors::Error::Custom] one.
+    ///
+    fn parse_values(values: Values) -> errors::Result<Self> {
+        Ok(Self::from_values(values)?)
+    }
+
+    /// Parse like [Attribute::from_values], with `options` applying to this
+    /// and every nested attribute, e.g. to ignore unknown keys:
+    ///
+    /// ```ignore
+    /// let options = ParseOptions {
+    ///     on_unknown: OnUnknown::Ignore,
+    /// };
+    ///
+    /// let route = Route::from_values_with(values, options)?;
+    /// ```
+    ///
+    /// The options are followed by derived attributes with named keys, not by
+    /// flags or attributes implemented by hand.
+    ///
+    fn from_values_with(values: Values, options: ParseOptions) -> Result<Self> {
+        options.scope(|| Self::from_values(values))
+    }
+
+    /// Parse an attribute from an entry of a list of attributes, such as
+    /// `route(path = "/a")` in `routes(route(path = "/a"), route(path = "/b"))`.
+    ///
+    fn from_list_entry(value: Value) -> Result<Self> {
+        Self::from_values(match value {
+            Value::List(List { values, .. }) | Value::Tuple(Tuple { values, .. }) => values,
+            value => return Err(format_error(&value, "list of values").into()),
+        })
+    }
+
     fn from_input(input: ParseStream) -> Result<Self> {
         Self::from_values(input.parse()?)
     }
@@ -20,6 +71,42 @@ pub trait Attribute: Sized {
         syn::parse::Parser::parse2(|input: ParseStream| Self::from_input(input), tokens)
     }
 
+    /// Parse like [Attribute::from_tokens], from the tokens a procedural macro
+    /// receives (e.g. the input of a function-like macro), without converting
+    /// them to a [TokenStream] first.
+    ///
+    fn from_tokens1(tokens: proc_macro::TokenStream) -> Result<Self> {
+        Self::from_tokens(tokens.into())
+    }
+
+    /// Parse the arguments of an attribute macro (i.e. the `args` of a
+    /// `#[proc_macro_attribute]` function).
+    ///
+    fn from_args(args: proc_macro::TokenStream) -> Result<Self> {
+        Self::from_args2(args.into())
+    }
+
+    /// Parse the arguments of an attribute macro, see [Attribute::from_args].
+    ///
+    /// Without any arguments (e.g. `#[my_attr]`), the attribute is parsed from
+    /// an empty list of values, with errors (such as missing keys) reported at
+    /// the attribute itself.
+    ///
+    fn from_args2(args: TokenStream) -> Result<Self> {
+        if args.is_empty() {
+            return Self::from_values(Values::new(Span::call_site()));
+        }
+
+        Self::from_tokens(args)
+    }
+
+    /// Parse an attribute from the entry of a [syn::Attribute::parse_nested_meta]
+    /// callback, such as `route(path = "/a")` in `#[api(route(path = "/a"))]`.
+    ///
+    fn from_nested_meta(meta: &ParseNestedMeta) -> Result<Self> {
+        Self::from_values(Values::from_nested_meta(meta)?)
+    }
+
     fn from_meta(meta: &Meta) -> Result<Self> {
         match meta {
             Meta::List(MetaList { path, tokens, .. }) => match Self::from_tokens(tokens.clone()) {
@@ -41,36 +128,615 @@ pub trait Attribute: Sized {
         }
     }
 
-    fn extract_from_attributes(
-        attributes: &mut Vec<syn::Attribute>,
-        path: &str,
-    ) -> Result<Vec<Self>> {
-        let mut errors = Vec::new();
+    /// Parse a single attribute (e.g. while iterating the attributes of an item),
+    /// placing any errors in the context of its path (e.g.
+    /// ``in `#[route(...)]`: ...``).
+    ///
+    /// With [Attribute::PATH] set, an attribute with another path is an error.
+    ///
+    fn from_attribute(attribute: &syn::Attribute) -> Result<Self> {
+        let mismatch = Self::PATH.filter(|path| !path.matches_attribute(attribute));
 
-        let parsed = attributes
-            .iter()
-            .filter_map(|attr| {
-                if !attr.path().is_ident(path) {
-                    return None;
-                }
+        if let Some(path) = mismatch {
+            return Err(Error::new(
+                attribute.path().span(),
+                format!(
+                    "expected attribute {}, found `{}`",
+                    path.describe(),
+                    path_to_string(attribute.path())
+                ),
+            ));
+        }
+
+        from_attribute_meta(attribute)
+    }
+
+    /// Parse all attributes in `attributes` with the given `path` as if they
+    /// were written in a single attribute (so that duplicate keys are detected
+    /// across attributes). Without any matching attributes, the attribute is
+    /// parsed from an empty list of values.
+    ///
+    fn from_attributes<P>(attributes: &[syn::Attribute], path: &P) -> Result<Self>
+    where
+        P: AttributePath + ?Sized,
+    {
+        let mut values: Option<Values> = None;
+
+        for attribute in attributes {
+            if !path.matches_attribute(attribute) {
+                continue;
+            }
 
-                match Self::from_meta(&attr.meta) {
-                    Ok(attr) => Some(attr),
-                    Err(error) => {
-                        errors.push(error);
-                        None
+            match &attribute.meta {
+                Meta::List(MetaList {
+                    path: list_path,
+                    tokens,
+                    ..
+                }) => {
+                    let parsed = if tokens.is_empty() {
+                        Values::new(list_path.span())
+                    } else {
+                        syn::parse2(tokens.clone()).map_err(|error| {
+                            errors::in_attribute(error.into(), &path_to_string(attribute.path()))
+                        })?
+                    };
+
+                    match &mut values {
+                        Some(values) => values.extend(parsed),
+                        None => values = Some(parsed),
                     }
                 }
+                meta => {
+                    return Err(Error::new(
+                        meta.span(),
+                        format!("expected `#[{}(...)]`", path_to_string(attribute.path())),
+                    ));
+                }
+            }
+        }
+
+        Self::from_values(values.unwrap_or_else(|| Values::new(Span::call_site())))
+    }
+
+    /// Parse all attributes with path [Attribute::PATH] on a field as one (see
+    /// [Attribute::from_attributes]), e.g. the options of a field in a derive
+    /// macro:
+    ///
+    /// ```ignore
+    /// for field in &fields.named {
+    ///     let options = FieldOptions::from_field(field)?;
+    /// }
+    /// ```
+    ///
+    /// Without [Attribute::PATH] set, this is an error at the field.
+    ///
+    fn from_field(field: &syn::Field) -> Result<Self> {
+        Self::from_attributes(&field.attrs, attribute_path::<Self>(field.span())?)
+    }
+
+    /// Parse all attributes with path [Attribute::PATH] on an enum variant as
+    /// one, like [Attribute::from_field].
+    ///
+    fn from_variant(variant: &syn::Variant) -> Result<Self> {
+        Self::from_attributes(&variant.attrs, attribute_path::<Self>(variant.span())?)
+    }
+
+    /// Parse all attributes with the given `path`, leaving the attributes in
+    /// place.
+    ///
+    fn parse_from_attributes<P>(attributes: &[syn::Attribute], path: &P) -> Result<Vec<Self>>
+    where
+        P: AttributePath + ?Sized,
+    {
+        Self::parse_from_attributes_with_paths(attributes, path)
+            .map(|parsed| parsed.into_iter().map(|(_, attr)| attr).collect())
+    }
+
+    /// Parse all attributes with the given `path` (usually a list of candidate
+    /// paths, such as `["serde", "serde_with"]`), leaving the attributes in
+    /// place, along with the path that each attribute was written with.
+    ///
+    fn parse_from_attributes_with_paths<P>(
+        attributes: &[syn::Attribute],
+        path: &P,
+    ) -> Result<Vec<(Path, Self)>>
+    where
+        P: AttributePath + ?Sized,
+    {
+        let mut errors = Accumulator::new();
+
+        let parsed = attributes
+            .iter()
+            .filter(|attr| path.matches_attribute(attr))
+            .filter_map(|attr| {
+                let parsed = errors.handle(from_attribute_meta(attr))?;
+                Some((attr.path().clone(), parsed))
             })
             .collect::<Vec<_>>();
 
-        attributes.retain(|attr| !attr.path().is_ident(path));
+        Ok(errors.finish_with(parsed)?)
+    }
+
+    /// Parse all attributes with the given `path` on `item` (e.g. a field, a
+    /// function or a `Vec<syn::Attribute>`) and remove them from it.
+    ///
+    fn extract_from_attributes<I, P>(item: &mut I, path: &P) -> Result<Vec<Self>>
+    where
+        I: HasAttributes + ?Sized,
+        P: AttributePath + ?Sized,
+    {
+        let attributes = item.attributes_mut();
+        let parsed = Self::parse_from_attributes(attributes, path);
+
+        attributes.retain(|attr| !path.matches_attribute(attr));
+
+        parsed
+    }
+
+    /// Parse all attributes with path [Attribute::PATH] on `item` and remove
+    /// them from it. Without [Attribute::PATH] set, this is an error.
+    ///
+    fn extract<I>(item: &mut I) -> Result<Vec<Self>>
+    where
+        I: HasAttributes + ?Sized,
+    {
+        Self::extract_from_attributes(item, attribute_path::<Self>(Span::call_site())?)
+    }
+
+    /// Parse the attribute with path [Attribute::PATH] on `item`, if any, and
+    /// remove it from it. Repeating the attribute is an error, as is calling
+    /// this without [Attribute::PATH] set.
+    ///
+    fn extract_one<I>(item: &mut I) -> Result<Option<Self>>
+    where
+        I: HasAttributes + ?Sized,
+    {
+        Self::extract_at_most_one(item, attribute_path::<Self>(Span::call_site())?)
+    }
+
+    /// Parse the attribute with the given `path` on `item`, if any, and remove
+    /// it from it. Repeating the attribute is an error.
+    ///
+    fn extract_at_most_one<I, P>(item: &mut I, path: &P) -> Result<Option<Self>>
+    where
+        I: HasAttributes + ?Sized,
+        P: AttributePath + ?Sized,
+    {
+        let mut parsed = Self::extract_from_attributes_with_paths(item, path)?;
 
-        if let Some(error) = errors.combine() {
+        if let [(first, _), (second, _), ..] = parsed.as_slice() {
+            let mut error = Error::new(
+                second.span(),
+                format!(
+                    "attribute `{}` specified multiple times",
+                    path_to_string(second)
+                ),
+            );
+            error.combine(Error::new(
+                first.span(),
+                format!("attribute `{}` first specified here", path_to_string(first)),
+            ));
             return Err(error);
         }
 
-        Ok(parsed)
+        Ok(parsed.pop().map(|(_, attr)| attr))
+    }
+
+    /// Parse the attribute with the given `path` on `item` and remove it from
+    /// it. Omitting or repeating the attribute is an error.
+    ///
+    fn extract_exactly_one<I, P>(item: &mut I, path: &P) -> Result<Self>
+    where
+        I: HasAttributes + ?Sized,
+        P: AttributePath + ?Sized,
+    {
+        match Self::extract_at_most_one(item, path)? {
+            Some(attr) => Ok(attr),
+            None => Err(Error::new(
+                Span::call_site(),
+                format!("attribute {} is required", path.describe()),
+            )),
+        }
+    }
+
+    /// Parse all attributes with the given `path` on `item` and remove them from
+    /// it, along with the removed attributes themselves, so that they can be
+    /// re-emitted or moved elsewhere.
+    ///
+    fn extract_from_attributes_with_originals<I, P>(
+        item: &mut I,
+        path: &P,
+    ) -> Result<Vec<(syn::Attribute, Self)>>
+    where
+        I: HasAttributes + ?Sized,
+        P: AttributePath + ?Sized,
+    {
+        let attributes = item.attributes_mut();
+        let mut errors = Accumulator::new();
+        let mut parsed = Vec::new();
+
+        for attr in std::mem::take(attributes) {
+            if !path.matches_attribute(&attr) {
+                attributes.push(attr);
+                continue;
+            }
+
+            if let Some(value) = errors.handle(from_attribute_meta(&attr)) {
+                parsed.push((attr, value));
+            }
+        }
+
+        Ok(errors.finish_with(parsed)?)
+    }
+
+    /// Parse all attributes with the given `path` on `item` and remove them from
+    /// it, along with the path that each attribute was written with.
+    ///
+    fn extract_from_attributes_with_paths<I, P>(item: &mut I, path: &P) -> Result<Vec<(Path, Self)>>
+    where
+        I: HasAttributes + ?Sized,
+        P: AttributePath + ?Sized,
+    {
+        let attributes = item.attributes_mut();
+        let parsed = Self::parse_from_attributes_with_paths(attributes, path);
+
+        attributes.retain(|attr| !path.matches_attribute(attr));
+
+        parsed
+    }
+}
+
+/// Parse attribute syntax written inline into the attribute `T`, e.g. in unit
+/// tests:
+///
+/// ```ignore
+/// let route = parse_attr!(Route, path = "/a", method = "GET")?;
+/// ```
+///
+/// The tokens are quoted, so variables may be interpolated as in
+/// [quote!](quote::quote) (e.g. `path = #path`).
+///
+#[macro_export]
+macro_rules! parse_attr {
+    ($ty:ty $(, $($tokens:tt)*)?) => {
+        <$ty as $crate::attribute::Attribute>::from_tokens(
+            $crate::__private::quote!($($($tokens)*)?)
+        )
+    };
+}
+
+/// A matcher for the paths of attributes: a single path, such as `"route"` or
+/// `"my_framework::route"`, or a list of candidate paths, such as
+/// `["route", "my_framework::route"]`.
+///
+pub trait AttributePath {
+    fn matches_path(&self, path: &Path) -> bool;
+
+    /// Describe the matched paths for use in error messages (e.g. `` `route` ``).
+    ///
+    fn describe(&self) -> String;
+
+    /// Determine whether an attribute matches, by its path only by default.
+    ///
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        self.matches_path(attribute.path())
+    }
+}
+
+impl AttributePath for str {
+    fn matches_path(&self, path: &Path) -> bool {
+        let mut segments = path.segments.iter();
+
+        self.split("::").all(|name| {
+            segments
+                .next()
+                .is_some_and(|segment| segment.arguments.is_none() && segment.ident == name)
+        }) && segments.next().is_none()
+    }
+
+    fn describe(&self) -> String {
+        format!("`{}`", self)
+    }
+}
+
+impl AttributePath for String {
+    fn matches_path(&self, path: &Path) -> bool {
+        self.as_str().matches_path(path)
+    }
+
+    fn describe(&self) -> String {
+        self.as_str().describe()
+    }
+}
+
+impl<P> AttributePath for &P
+where
+    P: AttributePath + ?Sized,
+{
+    fn matches_path(&self, path: &Path) -> bool {
+        (**self).matches_path(path)
+    }
+
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        (**self).matches_attribute(attribute)
+    }
+}
+
+impl<P> AttributePath for [P]
+where
+    P: AttributePath,
+{
+    fn matches_path(&self, path: &Path) -> bool {
+        self.iter().any(|candidate| candidate.matches_path(path))
+    }
+
+    fn describe(&self) -> String {
+        self.iter()
+            .map(|candidate| candidate.describe())
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        self.iter()
+            .any(|candidate| candidate.matches_attribute(attribute))
+    }
+}
+
+impl<P, const N: usize> AttributePath for [P; N]
+where
+    P: AttributePath,
+{
+    fn matches_path(&self, path: &Path) -> bool {
+        self.as_slice().matches_path(path)
+    }
+
+    fn describe(&self) -> String {
+        self.as_slice().describe()
+    }
+
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        self.as_slice().matches_attribute(attribute)
+    }
+}
+
+impl<P> AttributePath for Vec<P>
+where
+    P: AttributePath,
+{
+    fn matches_path(&self, path: &Path) -> bool {
+        self.as_slice().matches_path(path)
+    }
+
+    fn describe(&self) -> String {
+        self.as_slice().describe()
+    }
+
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        self.as_slice().matches_attribute(attribute)
+    }
+}
+
+/// A path that only matches outer attributes (e.g. `#[route(...)]`).
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Outer<P>(pub P);
+
+impl<P> AttributePath for Outer<P>
+where
+    P: AttributePath,
+{
+    fn matches_path(&self, path: &Path) -> bool {
+        self.0.matches_path(path)
+    }
+
+    fn describe(&self) -> String {
+        self.0.describe()
+    }
+
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        matches!(attribute.style, AttrStyle::Outer) && self.0.matches_attribute(attribute)
+    }
+}
+
+/// A path that only matches inner attributes (e.g. `#![config(...)]`).
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Inner<P>(pub P);
+
+impl<P> AttributePath for Inner<P>
+where
+    P: AttributePath,
+{
+    fn matches_path(&self, path: &Path) -> bool {
+        self.0.matches_path(path)
+    }
+
+    fn describe(&self) -> String {
+        self.0.describe()
+    }
+
+    fn matches_attribute(&self, attribute: &syn::Attribute) -> bool {
+        matches!(attribute.style, AttrStyle::Inner(_)) && self.0.matches_attribute(attribute)
+    }
+}
+
+/// The [Attribute::PATH] of `T`, or an error at `span` if it is not set.
+///
+#[inline]
+fn attribute_path<T: Attribute>(span: Span) -> Result<&'static str> {
+    T::PATH.ok_or_else(|| {
+        Error::new(
+            span,
+            "no attribute path is set, set one with `#[squattr(path = \"...\")]`",
+        )
+    })
+}
+
+/// Parse a single attribute, placing any errors in the context of the
+/// attribute's path (e.g. ``in `#[route(...)]`: ...``).
+///
+#[inline]
+fn from_attribute_meta<T: Attribute>(attribute: &syn::Attribute) -> Result<T> {
+    let parsed = match &attribute.meta {
+        // Without any tokens (e.g. `#[route()]`), errors such as missing keys
+        // are reported at the path of the attribute.
+        Meta::List(MetaList { path, tokens, .. }) if tokens.is_empty() => {
+            T::parse_values(Values::new(path.span()))
+        }
+        Meta::List(MetaList { tokens, .. }) => syn::parse2(tokens.clone())
+            .map_err(errors::Error::from)
+            .and_then(T::parse_values),
+        meta => T::from_meta(meta).map_err(errors::Error::from),
+    };
+
+    parsed.map_err(|error| errors::in_attribute(error, &path_to_string(attribute.path())).into())
+}
+
+fn path_to_string(path: &Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// An item that holds attributes, such as a field, a variant or a function.
+///
+pub trait HasAttributes {
+    fn attributes(&self) -> &[syn::Attribute];
+    fn attributes_mut(&mut self) -> &mut Vec<syn::Attribute>;
+}
+
+impl HasAttributes for Vec<syn::Attribute> {
+    fn attributes(&self) -> &[syn::Attribute] {
+        self
+    }
+
+    fn attributes_mut(&mut self) -> &mut Vec<syn::Attribute> {
+        self
+    }
+}
+
+macro_rules! impl_has_attributes {
+    ($( $t:ty ),* $(,)?) => {
+        $(impl HasAttributes for $t {
+            fn attributes(&self) -> &[syn::Attribute] {
+                &self.attrs
+            }
+
+            fn attributes_mut(&mut self) -> &mut Vec<syn::Attribute> {
+                &mut self.attrs
+            }
+        })*
+    };
+}
+
+impl_has_attributes!(syn::DeriveInput, syn::Field, syn::Variant);
+
+#[cfg(feature = "full")]
+impl_has_attributes!(
+    syn::ItemConst,
+    syn::ItemEnum,
+    syn::ItemFn,
+    syn::ItemImpl,
+    syn::ItemMod,
+    syn::ItemStatic,
+    syn::ItemStruct,
+    syn::ItemTrait,
+    syn::ItemType,
+    syn::ItemUnion,
+    syn::ImplItemConst,
+    syn::ImplItemFn,
+    syn::ImplItemType,
+    syn::TraitItemConst,
+    syn::TraitItemFn,
+    syn::TraitItemType,
+    syn::ForeignItemFn,
+    syn::ForeignItemStatic,
+    syn::PatType,
+);
+
+/// Collect the lines of all doc comments (i.e. `#[doc = "..."]` attributes) in
+/// `attributes`, with the indentation that all lines have in common removed.
+///
+/// Leading and trailing empty lines are omitted, so an item without docs
+/// yields no lines.
+///
+pub fn collect_docs(attributes: &[syn::Attribute]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for attribute in attributes {
+        if !attribute.path().is_ident("doc") {
+            continue;
+        }
+
+        if let Meta::NameValue(MetaNameValue {
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                }),
+            ..
+        }) = &attribute.meta
+        {
+            let value = lit_str.value();
+            lines.extend(value.split('\n').map(|line| line.trim_end().to_string()));
+        }
+    }
+
+    let indentation = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let lines = lines
+        .into_iter()
+        .map(|line| match line.get(indentation..) {
+            Some(line) => line.to_string(),
+            None => String::new(),
+        })
+        .collect::<Vec<_>>();
+
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Boxed attributes parse like the attribute they contain, which allows for
+/// recursive attribute structures (e.g. a field of type `Option<Box<Self>>`).
+///
+/// Note that a generic `ParseValue for Box<T>` cannot coexist with the blanket
+/// implementation below, so other boxed values are implemented one type at a
+/// time (e.g. `Box<String>`), see [ParseValue].
+///
+impl<T> Attribute for Box<T>
+where
+    T: Attribute,
+{
+    const PATH: Option<&'static str> = T::PATH;
+    const SCHEMA: Schema = T::SCHEMA;
+
+    fn from_values(values: Values) -> Result<Self> {
+        T::from_values(values).map(Box::new)
+    }
+
+    fn parse_values(values: Values) -> errors::Result<Self> {
+        T::parse_values(values).map(Box::new)
+    }
+
+    fn from_list_entry(value: Value) -> Result<Self> {
+        T::from_list_entry(value).map(Box::new)
     }
 }
 
@@ -78,21 +744,126 @@ impl<T> ParseValue for T
 where
     T: Attribute,
 {
-    fn parse(value: Value) -> Result<Self> {
-        T::from_values(match value {
-            Value::List(List { values, .. }) => values,
+    const KIND: Kind = Kind::Nested(|| T::SCHEMA);
+
+    /// Errors of a nested attribute are placed in the context of its key, e.g.
+    /// ``in `opts`: unrecognized key `retri` ``.
+    ///
+    fn parse(value: Value) -> errors::Result<Self> {
+        let (key, values) = match value {
+            Value::List(List { ident, values, .. }) => (Some(ident), values),
+            Value::Tuple(Tuple { values, .. }) => (None, values),
             value => return Err(format_error(&value, "list of values")),
+        };
+
+        T::parse_values(values).map_err(|error| match key {
+            Some(key) => errors::in_key(error, &key.to_string()),
+            None => error,
         })
     }
+
+    /// A list of attributes holds one attribute per entry, such as
+    /// `routes(route(path = "/a"), route(path = "/b"))` or
+    /// `routes((path = "/a"), (path = "/b"))`. Attributes given by repeating
+    /// their key are parsed as a [Repeated](crate::types::Repeated) instead.
+    ///
+    fn parse_list(values: Values) -> errors::Result<Vec<Self>> {
+        let mut errors = Accumulator::new();
+        let mut parsed = vec![];
+
+        for value in values {
+            parsed.extend(errors.handle(T::from_list_entry(value)));
+        }
+
+        errors.finish_with(parsed)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{ast::Values, attribute::Attribute, errors::ErrorsExt, types::ValueStorageExt};
+    use crate::{
+        ast::Values,
+        attribute::{Attribute, AttributePath, Inner, Outer, collect_docs},
+        errors::Accumulator,
+        types::{ValueStorageExt, format_error},
+    };
 
     use proc_macro2::Span;
     use quote::quote;
-    use syn::{Ident, Lit, LitInt};
+    use syn::{Ident, Lit, LitInt, Path, parse_quote};
+
+    #[test]
+    fn collect_doc_comments() {
+        let item: syn::DeriveInput = parse_quote! {
+            /// The first line.
+            ///
+            ///     An indented line.
+            #[derive(Debug)]
+            #[doc = " The last line."]
+            struct Foo;
+        };
+
+        assert_eq!(
+            collect_docs(&item.attrs),
+            [
+                "The first line.",
+                "",
+                "    An indented line.",
+                "The last line."
+            ]
+        );
+
+        let item: syn::DeriveInput = parse_quote! {
+            #[doc = "\n    A block comment.\n      An indented line.\n"]
+            struct Foo;
+        };
+
+        assert_eq!(
+            collect_docs(&item.attrs),
+            ["A block comment.", "  An indented line."]
+        );
+
+        let item: syn::DeriveInput = parse_quote! {
+            #[derive(Debug)]
+            struct Foo;
+        };
+
+        assert!(collect_docs(&item.attrs).is_empty());
+    }
+
+    #[test]
+    fn match_attribute_paths() {
+        let route: Path = parse_quote!(route);
+        let qualified: Path = parse_quote!(my_framework::route);
+        let generic: Path = parse_quote!(my_framework::route<T>);
+
+        assert!("route".matches_path(&route));
+        assert!(!"route".matches_path(&qualified));
+        assert!("my_framework::route".matches_path(&qualified));
+        assert!(!"my_framework::route".matches_path(&route));
+        assert!(!"my_framework::route".matches_path(&generic));
+        assert!(!"my_framework".matches_path(&qualified));
+
+        let candidates = ["route", "my_framework::route"];
+        assert!(candidates.matches_path(&route));
+        assert!(candidates.matches_path(&qualified));
+        assert!(!candidates[..1].matches_path(&qualified));
+    }
+
+    #[test]
+    fn match_attribute_styles() {
+        let outer: syn::Attribute = parse_quote!(#[config(debug)]);
+        let inner: syn::Attribute = parse_quote!(#![config(debug)]);
+
+        assert!("config".matches_attribute(&outer));
+        assert!("config".matches_attribute(&inner));
+        assert!(Outer("config").matches_attribute(&outer));
+        assert!(!Outer("config").matches_attribute(&inner));
+        assert!(Inner("config").matches_attribute(&inner));
+        assert!(!Inner("config").matches_attribute(&outer));
+        assert!(!Inner("other").matches_attribute(&inner));
+        assert!([Inner("config"), Inner("other")].matches_attribute(&inner));
+    }
 
     #[test]
     fn parse_attributes() {
@@ -110,7 +881,7 @@ mod tests {
         impl Attribute for SomeAttribute {
             fn from_values(values: Values) -> syn::Result<Self> {
                 let span = values.span();
-                let mut errors = Vec::new();
+                let mut errors = Accumulator::new();
 
                 let mut some_list: Option<Vec<String>> = None;
                 let mut some_ident_list: Option<Vec<Ident>> = None;
@@ -124,10 +895,7 @@ mod tests {
                     let id = match value.identifier() {
                         Some(id) => id,
                         None => {
-                            errors.push(syn::Error::new(
-                                value.span(),
-                                format!("expected an identifier"),
-                            ));
+                            errors.push(format_error(&value, "identifier"));
                             continue;
                         }
                     };
@@ -172,9 +940,7 @@ mod tests {
                     ));
                 }
 
-                if let Some(error) = errors.combine() {
-                    return Err(error);
-                }
+                errors.finish()?;
 
                 Ok(Self {
                     some_list: some_list.expect("values existance has already been confirmed"),
@@ -197,7 +963,7 @@ mod tests {
         impl Attribute for SubAttribute {
             fn from_values(values: Values) -> syn::Result<Self> {
                 let _span = values.span();
-                let mut errors = Vec::new();
+                let mut errors = Accumulator::new();
 
                 let mut some_sub_bool: Option<bool> = None;
 
@@ -219,9 +985,7 @@ mod tests {
                     }
                 }
 
-                if let Some(error) = errors.combine() {
-                    return Err(error);
-                }
+                errors.finish()?;
 
                 Ok(Self {
                     some_sub_bool: some_sub_bool.unwrap_or_default(),
@@ -0,0 +1,136 @@
+use ::darling::FromMeta;
+use quote::ToTokens;
+use syn::{Error, Meta};
+
+use crate::{ast::Value, attribute::Attribute, errors::Result, types::ParseValue};
+
+/// Use an [Attribute] where darling expects a [FromMeta], e.g. as the type of
+/// a field of a `#[derive(FromDeriveInput)]` struct:
+///
+/// ```ignore
+/// #[derive(FromDeriveInput)]
+/// #[darling(attributes(my_attr))]
+/// struct MyInput {
+///     route: Darling<Route>,
+/// }
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Darling<T>(pub T);
+
+impl<T> Darling<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Darling<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Darling<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Attribute> FromMeta for Darling<T> {
+    fn from_meta(item: &Meta) -> ::darling::Result<Self> {
+        T::from_meta(item)
+            .map(Darling)
+            .map_err(::darling::Error::from)
+    }
+}
+
+/// Use a darling [FromMeta] type as the type of a field of a derived
+/// [Attribute], e.g. `#[derive(Attribute)] struct Route { method: FromDarling<Method> }`.
+///
+/// The value is turned back into a [Meta] (i.e. `key`, `key = value` or
+/// `key(...)`) and handed to [FromMeta::from_meta].
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FromDarling<T>(pub T);
+
+impl<T> FromDarling<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for FromDarling<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for FromDarling<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: FromMeta> ParseValue for FromDarling<T> {
+    fn parse(value: Value) -> Result<Self> {
+        let span = value.span();
+        let meta: Meta = syn::parse2(value.to_token_stream())
+            .map_err(|_| Error::new(span, "expected `key`, `key = value` or `key(...)`"))?;
+
+        Ok(T::from_meta(&meta).map(FromDarling).map_err(Error::from)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::darling::FromMeta;
+    use syn::{Meta, parse_quote};
+
+    use super::{Darling, FromDarling};
+    use crate::{ast::Value, options::ContainerOptions, types::ParseValue};
+
+    #[test]
+    fn attribute_from_darling() {
+        let meta: Meta = parse_quote!(squattr(flags, path = "route"));
+        let options = Darling::<ContainerOptions>::from_meta(&meta).unwrap();
+
+        assert!(options.flags);
+        assert_eq!(options.path.as_deref(), Some("route"));
+
+        let meta: Meta = parse_quote!(squattr(flag));
+        let error = Darling::<ContainerOptions>::from_meta(&meta).err().unwrap();
+
+        assert_eq!(
+            syn::Error::from(error).to_string(),
+            "unrecognized option `flag`, did you mean `flags`?"
+        );
+    }
+
+    #[derive(FromMeta)]
+    struct Limits {
+        min: u32,
+        max: u32,
+    }
+
+    #[test]
+    fn darling_from_value() {
+        let value: Value = syn::parse_str("limits(min = 1, max = 10)").unwrap();
+        let limits = FromDarling::<Limits>::parse(value).unwrap();
+
+        assert_eq!((limits.min, limits.max), (1, 10));
+
+        let value: Value = syn::parse_str("name = \"x\"").unwrap();
+        let name = FromDarling::<String>::parse(value).unwrap();
+
+        assert_eq!(*name, "x");
+
+        let value: Value = syn::parse_str("limits(min = 1)").unwrap();
+        let error = FromDarling::<Limits>::parse(value).err().unwrap();
+
+        assert_eq!(error.to_string(), "Missing field `max`");
+    }
+}
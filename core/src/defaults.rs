@@ -0,0 +1,362 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use syn::{Error, Result};
+
+use crate::ast::Values;
+
+/// Fill the keys omitted from `values` with the defaults set in `file`, as an
+/// attribute derived with `#[squattr(defaults_file = "squattr.toml")]` does
+/// before parsing its values.
+///
+/// The file is looked up in the directory of the crate being compiled (i.e.
+/// `CARGO_MANIFEST_DIR`) and then in its ancestors up to the root of its
+/// workspace, so that a single file at that root applies to all of its crates.
+/// Without such a file, the values are left as they are. Each file is read and
+/// parsed once per build, however many attributes use it.
+///
+/// The defaults of an attribute with a `table` name (i.e. its path, such as
+/// `route`) are read from the table by that name, and those of any other
+/// attribute from the top level of the file:
+///
+/// ```toml
+/// [route]
+/// method = "GET"
+/// middleware = ["auth", "log"]
+/// retry = { count = 3 }
+/// ```
+///
+/// Reading a `.toml` file requires the `toml` feature, and a `.json` file the
+/// `json` feature. Note that the compiler does not know about the file, so a
+/// change to it only takes effect once the crate is rebuilt for another reason.
+///
+pub fn fill(mut values: Values, file: &str, table: Option<&str>) -> Result<Values> {
+    let Some(path) = find(file) else {
+        return Ok(values);
+    };
+
+    let defaults = read(&path)
+        .and_then(|document| document.values(table))
+        .map_err(|error| {
+            Error::new(
+                values.span(),
+                format!("invalid defaults file `{}`: {}", path.display(), error),
+            )
+        })?;
+
+    for default in defaults {
+        let omitted = default
+            .ident()
+            .is_some_and(|ident| values.position(&ident.to_string()).is_none());
+
+        if omitted {
+            values.push(default);
+        }
+    }
+
+    Ok(values)
+}
+
+/// Find `file` in the directory of the crate being compiled, or in one of its
+/// ancestors up to the root of its workspace.
+///
+fn find(file: &str) -> Option<PathBuf> {
+    let dir = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+
+    let root = dir
+        .ancestors()
+        .find(|dir| is_workspace_root(dir))
+        .unwrap_or(&dir);
+
+    for dir in dir.ancestors() {
+        let path = dir.join(file);
+
+        if path.is_file() {
+            return Some(path);
+        }
+
+        if dir == root {
+            break;
+        }
+    }
+
+    None
+}
+
+/// Whether the manifest in `dir` (if any) declares a workspace.
+///
+fn is_workspace_root(dir: &Path) -> bool {
+    fs::read_to_string(dir.join("Cargo.toml")).is_ok_and(|manifest| {
+        manifest
+            .lines()
+            .map(str::trim)
+            .any(|line| line == "[workspace]" || line.starts_with("[workspace."))
+    })
+}
+
+/// A parsed defaults file, which is kept apart from its values because those
+/// hold spans that may not outlive the macro invocation reading the file.
+///
+enum Document {
+    Toml(toml_defaults::Document),
+    Json(json_defaults::Document),
+}
+
+impl Document {
+    /// Parse `source` by the extension of the file at `path`.
+    ///
+    fn parse(path: &Path, source: &str) -> std::result::Result<Self, String> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml_defaults::parse(source).map(Self::Toml),
+            Some("json") => json_defaults::parse(source).map(Self::Json),
+            _ => Err("expected a `.toml` or `.json` file".into()),
+        }
+    }
+
+    /// The defaults of the `table` (if any) in the document.
+    ///
+    fn values(&self, table: Option<&str>) -> std::result::Result<Values, String> {
+        match self {
+            Self::Toml(document) => toml_defaults::values(document, table),
+            Self::Json(document) => json_defaults::values(document, table),
+        }
+    }
+}
+
+type Documents = HashMap<PathBuf, std::result::Result<Arc<Document>, String>>;
+
+/// The defaults files read so far, by path.
+///
+static DOCUMENTS: OnceLock<Mutex<Documents>> = OnceLock::new();
+
+/// Read and parse the file at `path`, or take it from the files read before.
+///
+fn read(path: &Path) -> std::result::Result<Arc<Document>, String> {
+    let mut documents = DOCUMENTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    documents
+        .entry(path.to_path_buf())
+        .or_insert_with(|| {
+            fs::read_to_string(path)
+                .map_err(|error| error.to_string())
+                .and_then(|source| Document::parse(path, &source))
+                .map(Arc::new)
+        })
+        .clone()
+}
+
+/// The conversion of the tables of either file format into values.
+///
+#[cfg(any(feature = "toml", feature = "json"))]
+mod tables {
+    use syn::Ident;
+
+    use crate::ast::{Tuple, Value, Values, ValuesBuilder};
+
+    /// Write the entries of a table as keys (e.g. `count = 3`), and those
+    /// holding an array or another table as lists (e.g.
+    /// `middleware("auth", "log")`).
+    ///
+    pub(super) fn entries<'a, I>(entries: I) -> Result<Values, String>
+    where
+        I: IntoIterator<Item = (&'a str, Result<Value, String>)>,
+    {
+        entries
+            .into_iter()
+            .try_fold(ValuesBuilder::new(), |builder, (key, value)| {
+                if syn::parse_str::<Ident>(key).is_err() {
+                    return Err(format!("`{}` is not a valid key", key));
+                }
+
+                Ok(match value? {
+                    Value::Tuple(tuple) => builder.list(key, tuple.values),
+                    value => builder.key(key, value),
+                })
+            })
+            .map(ValuesBuilder::build)
+    }
+
+    /// Write an array, or the entries of a nested table, between parentheses.
+    ///
+    pub(super) fn tuple(values: Result<Values, String>) -> Result<Value, String> {
+        Ok(Value::Tuple(Tuple {
+            paren_token: Default::default(),
+            values: values?,
+        }))
+    }
+
+    pub(super) fn list<I>(values: I) -> Result<Value, String>
+    where
+        I: IntoIterator<Item = Result<Value, String>>,
+    {
+        tuple(
+            values
+                .into_iter()
+                .try_fold(ValuesBuilder::new(), |builder, value| {
+                    Ok(builder.value(value?))
+                })
+                .map(ValuesBuilder::build),
+        )
+    }
+}
+
+#[cfg(feature = "toml")]
+mod toml_defaults {
+    use toml::{Table, Value as TomlValue};
+
+    use proc_macro2::Span;
+
+    use super::tables::{entries, list, tuple};
+    use crate::ast::{Value, Values};
+
+    pub(super) type Document = Table;
+
+    pub(super) fn parse(source: &str) -> Result<Table, String> {
+        source
+            .parse()
+            .map_err(|error: toml::de::Error| error.to_string())
+    }
+
+    pub(super) fn values(root: &Table, table: Option<&str>) -> Result<Values, String> {
+        match table.map(|name| (name, root.get(name))) {
+            None => self::table(root),
+            Some((_, Some(TomlValue::Table(table)))) => self::table(table),
+            Some((name, Some(_))) => Err(format!("`{}` is not a table", name)),
+            Some((_, None)) => Ok(Values::new(Span::call_site())),
+        }
+    }
+
+    fn table(table: &Table) -> Result<Values, String> {
+        entries(
+            table
+                .iter()
+                .map(|(key, value)| (key.as_str(), self::value(value))),
+        )
+    }
+
+    fn value(value: &TomlValue) -> Result<Value, String> {
+        Ok(match value {
+            TomlValue::String(string) => Value::from(string.as_str()),
+            TomlValue::Integer(integer) => Value::from(*integer),
+            TomlValue::Float(float) => Value::from(*float),
+            TomlValue::Boolean(boolean) => Value::from(*boolean),
+            TomlValue::Datetime(datetime) => Value::from(datetime.to_string()),
+            TomlValue::Array(values) => list(values.iter().map(self::value))?,
+            TomlValue::Table(table) => tuple(self::table(table))?,
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+mod json_defaults {
+    use serde_json::{Map, Value as JsonValue};
+
+    use proc_macro2::Span;
+
+    use super::tables::{entries, list, tuple};
+    use crate::ast::{Value, Values};
+
+    pub(super) type Document = Map<String, JsonValue>;
+
+    pub(super) fn parse(source: &str) -> Result<Document, String> {
+        serde_json::from_str(source).map_err(|error| error.to_string())
+    }
+
+    pub(super) fn values(root: &Document, table: Option<&str>) -> Result<Values, String> {
+        match table.map(|name| (name, root.get(name))) {
+            None => object(root),
+            Some((_, Some(JsonValue::Object(object)))) => self::object(object),
+            Some((name, Some(_))) => Err(format!("`{}` is not an object", name)),
+            Some((_, None)) => Ok(Values::new(Span::call_site())),
+        }
+    }
+
+    fn object(object: &Map<String, JsonValue>) -> Result<Values, String> {
+        entries(
+            object
+                .iter()
+                .map(|(key, value)| (key.as_str(), self::value(value))),
+        )
+    }
+
+    fn value(value: &JsonValue) -> Result<Value, String> {
+        Ok(match value {
+            JsonValue::Null => return Err("`null` is not a value".into()),
+            JsonValue::Bool(boolean) => Value::from(*boolean),
+            JsonValue::Number(number) => match (number.as_u64(), number.as_i64()) {
+                (Some(unsigned), _) => Value::from(unsigned),
+                (None, Some(signed)) => Value::from(signed),
+                (None, None) => Value::from(number.as_f64().unwrap_or_default()),
+            },
+            JsonValue::String(string) => Value::from(string.as_str()),
+            JsonValue::Array(values) => list(values.iter().map(self::value))?,
+            JsonValue::Object(object) => tuple(self::object(object))?,
+        })
+    }
+}
+
+#[cfg(not(feature = "toml"))]
+mod toml_defaults {
+    use crate::ast::Values;
+
+    pub(super) type Document = std::convert::Infallible;
+
+    pub(super) fn parse(_: &str) -> Result<Document, String> {
+        Err("reading TOML requires the `toml` feature".into())
+    }
+
+    pub(super) fn values(document: &Document, _: Option<&str>) -> Result<Values, String> {
+        match *document {}
+    }
+}
+
+#[cfg(not(feature = "json"))]
+mod json_defaults {
+    use crate::ast::Values;
+
+    pub(super) type Document = std::convert::Infallible;
+
+    pub(super) fn parse(_: &str) -> Result<Document, String> {
+        Err("reading JSON requires the `json` feature".into())
+    }
+
+    pub(super) fn values(document: &Document, _: Option<&str>) -> Result<Values, String> {
+        match *document {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::is_workspace_root;
+
+    #[test]
+    fn stop_at_workspace_root() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert!(!is_workspace_root(dir));
+        assert!(is_workspace_root(dir.parent().unwrap()));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn read_each_file_once() {
+        use std::sync::Arc;
+
+        use super::read;
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/defaults/squattr.toml");
+
+        assert!(Arc::ptr_eq(&read(&path).unwrap(), &read(&path).unwrap()));
+    }
+}
@@ -0,0 +1,172 @@
+use std::{cell::RefCell, fmt::Display};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote_spanned;
+
+thread_local! {
+    /// The warnings collected by the innermost [collect] in progress, if any.
+    static COLLECTED: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+}
+
+/// A problem with an attribute that does not prevent it from being parsed,
+/// e.g. a deprecated key.
+///
+/// Built with `--cfg squattr_nightly` on a nightly compiler, an emitted
+/// warning is reported right away through `proc_macro::Diagnostic`. Stable
+/// compilers have no such API, so there the warnings emitted while parsing are
+/// returned by [collect], for the macro to write them as tokens that make the
+/// compiler warn (see [Warning::to_tokens]) or as notes on a failed expansion
+/// (see [with_notes]):
+///
+/// ```ignore
+/// let (attribute, warnings) =
+///     diagnostic::collect(|| MyAttribute::from_attributes(&input.attrs));
+///
+/// let output = match attribute {
+///     Ok(attribute) => expand(attribute),
+///     Err(error) => return diagnostic::with_notes(error, warnings).to_compile_error(),
+/// };
+///
+/// let warnings = warnings.iter().map(Warning::to_tokens);
+/// quote!(#output #(#warnings)*)
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct Warning {
+    span: Span,
+    message: String,
+}
+
+impl Warning {
+    pub fn new<T: Display>(span: Span, message: T) -> Self {
+        Self {
+            span,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Report the warning, or hand it to the [collect] in progress when it
+    /// cannot be reported directly (i.e. on stable, or outside of a procedural
+    /// macro). Without one, the warning is dropped.
+    ///
+    pub fn emit(self) {
+        #[cfg(squattr_nightly)]
+        if proc_macro::is_available() {
+            proc_macro::Diagnostic::spanned(
+                self.span.unwrap(),
+                proc_macro::Level::Warning,
+                self.message,
+            )
+            .emit();
+            return;
+        }
+
+        COLLECTED.with_borrow_mut(|collected| {
+            if let Some(collected) = collected {
+                collected.push(self);
+            }
+        });
+    }
+
+    /// Write the warning as a use of a deprecated constant at its span, which
+    /// the compiler reports along with the message.
+    ///
+    pub fn to_tokens(&self) -> TokenStream {
+        let message = &self.message;
+
+        quote_spanned! {self.span=>
+            const _: () = {
+                #[deprecated(note = #message)]
+                #[allow(non_upper_case_globals)]
+                const squattr_warning: () = ();
+                squattr_warning
+            };
+        }
+    }
+}
+
+/// Emit a warning at `span`, see [Warning::emit].
+///
+pub fn warn<T: Display>(span: Span, message: T) {
+    Warning::new(span, message).emit();
+}
+
+/// Run `f` (e.g. parsing the attributes of an item), and return the warnings
+/// emitted meanwhile that were not reported directly, see [Warning::emit].
+///
+/// Every call starts without any warnings, so none are left over from an
+/// earlier parse (e.g. one of another macro invocation on the same thread).
+///
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Vec<Warning>) {
+    struct Restore(Option<Vec<Warning>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            COLLECTED.set(self.0.take());
+        }
+    }
+
+    let _restore = Restore(COLLECTED.replace(Some(Vec::new())));
+    let value = f();
+
+    (value, COLLECTED.take().unwrap_or_default())
+}
+
+/// Append `warnings` to `error` as notes, since the tokens of
+/// [Warning::to_tokens] are of no use to a failed expansion.
+///
+pub fn with_notes(mut error: syn::Error, warnings: Vec<Warning>) -> syn::Error {
+    for warning in warnings {
+        error.combine(syn::Error::new(
+            warning.span,
+            format!("note: {}", warning.message),
+        ));
+    }
+
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+
+    use super::{collect, warn, with_notes};
+
+    #[test]
+    fn collect_warnings_on_stable() {
+        let ((), warnings) = collect(|| warn(Span::call_site(), "key `old` is deprecated"));
+
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0]
+                .to_tokens()
+                .to_string()
+                .contains(r#"deprecated (note = "key `old` is deprecated")"#)
+        );
+
+        // Warnings outside of `collect` are not kept for a later one.
+        warn(Span::call_site(), "key `older` is deprecated");
+
+        let ((), warnings) = collect(|| ());
+        assert!(warnings.is_empty());
+
+        let (error, warnings) = collect(|| {
+            warn(Span::call_site(), "key `old` is deprecated");
+            syn::Error::new(Span::call_site(), "required key `new`")
+        });
+        let error = with_notes(error, warnings);
+
+        assert_eq!(
+            error.into_iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            ["required key `new`", "note: key `old` is deprecated"]
+        );
+    }
+}
@@ -1,34 +1,130 @@
-use strsim::jaro_winkler;
+use strsim::{jaro_winkler, normalized_levenshtein};
+use syn::Lit;
 
-pub fn did_you_mean(options: &[&'static str], given: &str) -> Option<String> {
-    let mut current_best_match = options
-        .get(0)
-        .map(|&option| (option, jaro_winkler(option, given)));
-
-    if let Some(more) = options.get(1..) {
-        for option in more {
-            let score = jaro_winkler(option, given);
-
-            if current_best_match
-                .is_some_and(|(_, current_highest_score)| score > current_highest_score)
-            {
-                current_best_match = Some((option, score))
-            }
+use crate::ast::{Neg, Value};
+
+/// The measure of similarity between a given name and a known option, from
+/// `0.0` (nothing in common) to `1.0` (equal).
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scorer {
+    /// Favors names that share a prefix, which suits short identifiers.
+    JaroWinkler,
+    /// The edit distance relative to the length of the longer name.
+    Levenshtein,
+}
+
+impl Scorer {
+    pub fn score(&self, option: &str, given: &str) -> f64 {
+        match self {
+            Self::JaroWinkler => jaro_winkler(option, given),
+            Self::Levenshtein => normalized_levenshtein(option, given),
         }
     }
+}
 
-    if let Some((option, score)) = current_best_match {
-        if score > 0.8 {
-            return Some(option.into());
-        }
+/// How options similar to a mistyped name are suggested, e.g. in
+/// ``unrecognized key `pth`, did you mean `path`?``.
+///
+/// ```ignore
+/// let suggestions = Suggestions {
+///     threshold: 0.5,
+///     max: 2,
+///     scorer: Scorer::Levenshtein,
+/// };
+///
+/// Error::unknown_key_with(key, span, &["path", "port"], &suggestions)
+/// ```
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Suggestions {
+    /// Only options scoring above the threshold are suggested.
+    pub threshold: f64,
+    /// The maximum number of options to suggest.
+    pub max: usize,
+    pub scorer: Scorer,
+}
+
+impl Suggestions {
+    /// Suggest only the single most similar option, scoring above `0.8` by
+    /// [Scorer::JaroWinkler].
+    ///
+    pub const DEFAULT: Self = Self {
+        threshold: 0.8,
+        max: 1,
+        scorer: Scorer::JaroWinkler,
+    };
+
+    /// Find the options most similar to `given`, the most similar first.
+    ///
+    pub fn suggest(&self, options: &[&'static str], given: &str) -> Vec<String> {
+        let mut scored = options
+            .iter()
+            .map(|&option| (option, self.scorer.score(option, given)))
+            .filter(|&(_, score)| score > self.threshold)
+            .collect::<Vec<_>>();
+
+        // A stable sort keeps equally similar options in their given order.
+        scored.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        scored
+            .into_iter()
+            .take(self.max)
+            .map(|(option, _)| option.to_string())
+            .collect()
     }
 
-    None
+    /// Find the options most similar to a value written as an identifier (e.g.
+    /// `ture`), a negated identifier (e.g. `-inff`) or a string literal (e.g.
+    /// `"fsat"`). Other values have nothing to compare.
+    ///
+    pub fn suggest_value(&self, options: &[&'static str], given: &Value) -> Vec<String> {
+        let given = match given {
+            Value::Ident(ident) => ident.to_string(),
+            Value::Neg(Neg { ident, .. }) => format!("-{}", ident),
+            Value::Lit(Lit::Str(lit_str)) => lit_str.value(),
+            _ => return Vec::new(),
+        };
+
+        self.suggest(options, &given)
+    }
+}
+
+impl Default for Suggestions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub fn did_you_mean(options: &[&'static str], given: &str) -> Option<String> {
+    Suggestions::DEFAULT.suggest(options, given).pop()
+}
+
+/// Find the option most similar to a value, see [Suggestions::suggest_value].
+///
+pub fn did_you_mean_value(options: &[&'static str], given: &Value) -> Option<String> {
+    Suggestions::DEFAULT.suggest_value(options, given).pop()
+}
+
+/// Write `suggestions` as the end of a message, e.g.
+/// ``, did you mean `a`, `b` or `c`?``, or nothing if there are none.
+///
+pub fn format_suggestions(suggestions: &[String]) -> String {
+    let quoted = suggestions
+        .iter()
+        .map(|suggestion| format!("`{}`", suggestion))
+        .collect::<Vec<_>>();
+
+    match quoted.split_last() {
+        None => String::new(),
+        Some((last, [])) => format!(", did you mean {}?", last),
+        Some((last, rest)) => format!(", did you mean {} or {}?", rest.join(", "), last),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::dym::did_you_mean;
+    use crate::dym::{Scorer, Suggestions, did_you_mean, format_suggestions};
 
     #[test]
     fn found_match() {
@@ -48,4 +144,28 @@ mod tests {
 
         assert_eq!(None, did_you_mean(&options, input));
     }
+
+    #[test]
+    fn configure_suggestions() {
+        let options = ["path", "port", "paths", "host"];
+
+        let suggestions = Suggestions {
+            threshold: 0.5,
+            max: 2,
+            scorer: Scorer::Levenshtein,
+        };
+
+        assert_eq!(suggestions.suggest(&options, "pat"), ["path", "paths"]);
+        assert_eq!(Suggestions::DEFAULT.suggest(&options, "pat"), ["path"]);
+
+        assert_eq!(
+            format_suggestions(&suggestions.suggest(&options, "pat")),
+            ", did you mean `path` or `paths`?"
+        );
+        assert_eq!(
+            format_suggestions(&["a".into(), "b".into(), "c".into()]),
+            ", did you mean `a`, `b` or `c`?"
+        );
+        assert_eq!(format_suggestions(&[]), "");
+    }
 }
@@ -1,12 +1,19 @@
-use syn::Error;
+use std::fmt::{self, Display, Formatter};
+
+use proc_macro2::Span;
+
+use crate::{
+    ast::Value,
+    dym::{Suggestions, format_suggestions},
+};
 
 pub trait ErrorsExt: Sized {
-    fn combine(self) -> Option<Error>;
+    fn combine(self) -> Option<syn::Error>;
 }
 
-impl ErrorsExt for Vec<Error> {
-    fn combine(self) -> Option<Error> {
-        let first = match self.get(0) {
+impl ErrorsExt for Vec<syn::Error> {
+    fn combine(self) -> Option<syn::Error> {
+        let first = match self.first() {
             Some(first) => first.clone(),
             None => return None,
         };
@@ -22,3 +29,682 @@ impl ErrorsExt for Vec<Error> {
         }))
     }
 }
+
+/// The result of parsing a value, failing with an [Error] that keeps its kind
+/// until it is reported.
+///
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error raised while parsing an attribute. Its kind can be inspected (e.g.
+/// to report unknown keys as warnings instead), and it converts into a
+/// [syn::Error] to be reported.
+///
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// A key that the attribute does not accept, e.g. `fooo` in `attr(fooo)`.
+    UnknownKey {
+        key: String,
+        span: Span,
+        /// The most similar keys the attribute does accept, if any.
+        suggestions: Vec<String>,
+        /// All keys the attribute accepts, listed when none of them is
+        /// similar enough to suggest.
+        known: Vec<String>,
+    },
+    /// A key that was given more than once, at its repeated occurrence.
+    DuplicateKey {
+        key: String,
+        span: Span,
+        /// Where the key was first given, if known, which is pointed at by a
+        /// note.
+        first: Option<Span>,
+    },
+    /// A required key that was not given, at the span of the attribute.
+    MissingKey { key: String, span: Span },
+    /// A value that is not of the expected type, e.g. `"three"` for an
+    /// integer.
+    TypeMismatch {
+        key: Option<String>,
+        /// The declared Rust type of the key, if known (e.g. `u16`).
+        ty: Option<&'static str>,
+        expected: String,
+        /// The number of values given, for a list of another length.
+        found: Option<usize>,
+        span: Span,
+        /// The most similar values of the expected type, if any (e.g. `true`
+        /// for `ture`).
+        suggestions: Vec<String>,
+    },
+    /// Any other error.
+    Custom(syn::Error),
+    /// Several errors at once, e.g. those of the entries of a list. These are
+    /// collected one by one by an [Accumulator].
+    Multiple(Vec<Error>),
+    /// An error placed in the context of the nested attribute it was raised
+    /// in, and of the attribute holding it, e.g.
+    /// ``in `opts.retry` of `#[route(...)]`: ...``. See [in_key] and
+    /// [in_attribute].
+    Context {
+        /// The keys leading to the nested attribute, outermost first (e.g.
+        /// `opts` and `retry`).
+        keys: Vec<String>,
+        /// The path of the attribute, if known (e.g. `route`).
+        attribute: Option<String>,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    /// An unknown `key`, suggesting the most similar of the `known` keys.
+    ///
+    pub fn unknown_key(key: &str, span: Span, known: &[&'static str]) -> Self {
+        Self::unknown_key_with(key, span, known, &Suggestions::DEFAULT)
+    }
+
+    /// An unknown `key`, suggesting the `known` keys found by `suggestions`.
+    ///
+    pub fn unknown_key_with(
+        key: &str,
+        span: Span,
+        known: &[&'static str],
+        suggestions: &Suggestions,
+    ) -> Self {
+        Self::UnknownKey {
+            key: key.to_string(),
+            span,
+            suggestions: suggestions.suggest(known, key),
+            known: known.iter().map(|known| known.to_string()).collect(),
+        }
+    }
+
+    /// A `value` that is not of the `expected` type (e.g. `"string"`), set for
+    /// `key` if it is the value of one (e.g. `port` in `port = "string"`).
+    ///
+    pub fn type_mismatch(key: Option<String>, value: &Value, expected: &str) -> Self {
+        Self::TypeMismatch {
+            key,
+            ty: None,
+            expected: expected.to_string(),
+            found: None,
+            span: value.value_span(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// A `value` that is not of the `expected` type, suggesting the most
+    /// similar of the `known` values (e.g. `true` for `ture`).
+    ///
+    pub fn type_mismatch_suggesting(
+        key: Option<String>,
+        value: &Value,
+        expected: &str,
+        known: &[&'static str],
+    ) -> Self {
+        Self::TypeMismatch {
+            key,
+            ty: None,
+            expected: expected.to_string(),
+            found: None,
+            span: value.value_span(),
+            suggestions: Suggestions::DEFAULT.suggest_value(known, value),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Self::UnknownKey { span, .. }
+            | Self::DuplicateKey { span, .. }
+            | Self::MissingKey { span, .. }
+            | Self::TypeMismatch { span, .. } => *span,
+            Self::Custom(error) => error.span(),
+            Self::Multiple(errors) => errors.first().map_or_else(Span::call_site, Error::span),
+            Self::Context { error, .. } => error.span(),
+        }
+    }
+
+    /// The stable code of the kind of error, which prefixes its message (e.g.
+    /// ``squattr(E001): unrecognized key `fooo` ``), or `None` for custom (and
+    /// multiple) errors:
+    ///
+    /// | Code | Kind |
+    /// | --- | --- |
+    /// | `E001` | [Error::UnknownKey] |
+    /// | `E002` | [Error::DuplicateKey] |
+    /// | `E003` | [Error::MissingKey] |
+    /// | `E004` | [Error::TypeMismatch] |
+    ///
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            Self::UnknownKey { .. } => Some("E001"),
+            Self::DuplicateKey { .. } => Some("E002"),
+            Self::MissingKey { .. } => Some("E003"),
+            Self::TypeMismatch { .. } => Some("E004"),
+            Self::Custom(_) | Self::Multiple(_) => None,
+            Self::Context { error, .. } => error.code(),
+        }
+    }
+
+    /// The key the error is about, if known.
+    ///
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::UnknownKey { key, .. }
+            | Self::DuplicateKey { key, .. }
+            | Self::MissingKey { key, .. } => Some(key),
+            Self::TypeMismatch { key, .. } => key.as_deref(),
+            Self::Custom(_) | Self::Multiple(_) => None,
+            Self::Context { error, .. } => error.key(),
+        }
+    }
+
+    /// The error without the context it was placed in, see [Error::Context].
+    ///
+    pub fn inner(&self) -> &Error {
+        match self {
+            Self::Context { error, .. } => error.inner(),
+            error => error,
+        }
+    }
+
+    /// The other places involved in the error, each with a note.
+    ///
+    fn notes(&self) -> Vec<(Span, String)> {
+        match self {
+            Self::DuplicateKey {
+                key,
+                first: Some(first),
+                ..
+            } => vec![(*first, format!("`{}` is first set here", key))],
+            Self::Context { error, .. } => error.notes(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Write the message of the error, without its code.
+    ///
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey {
+                key,
+                suggestions,
+                known,
+                ..
+            } => {
+                write!(f, "unrecognized key `{}`", key)?;
+                match known.as_slice() {
+                    _ if !suggestions.is_empty() => {
+                        write!(f, "{}", format_suggestions(suggestions))
+                    }
+                    [] => Ok(()),
+                    [known] => write!(f, ", expected `{}`", known),
+                    known => write!(f, ", expected one of {}", format_keys(known)),
+                }
+            }
+            Self::DuplicateKey { key, .. } => write!(f, "duplicate key `{}`", key),
+            Self::MissingKey { key, .. } => write!(f, "required key `{}` not found", key),
+            Self::TypeMismatch {
+                key,
+                ty,
+                expected,
+                found,
+                suggestions,
+                ..
+            } => {
+                write!(f, "expected {}", expected)?;
+                if let Some(ty) = ty {
+                    write!(f, " of type `{}`", ty)?;
+                }
+                if let Some(key) = key {
+                    write!(f, " for `{}`", key)?;
+                }
+                match found {
+                    Some(1) => write!(f, ", found 1 value")?,
+                    Some(found) => write!(f, ", found {} values", found)?,
+                    None => {}
+                }
+                write!(f, "{}", format_suggestions(suggestions))
+            }
+            Self::Custom(error) => write!(f, "{}", error),
+            Self::Multiple(errors) => match errors.first() {
+                Some(error) => error.fmt_message(f),
+                None => Ok(()),
+            },
+            Self::Context {
+                keys,
+                attribute,
+                error,
+            } => {
+                match (keys.as_slice(), attribute) {
+                    ([], None) => {}
+                    ([], Some(attribute)) => write!(f, "in `#[{}(...)]`: ", attribute)?,
+                    (keys, None) => write!(f, "in `{}`: ", keys.join("."))?,
+                    (keys, Some(attribute)) => {
+                        write!(f, "in `{}` of `#[{}(...)]`: ", keys.join("."), attribute)?
+                    }
+                }
+                error.fmt_message(f)
+            }
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            // Like a combined `syn::Error`, only the first message is shown.
+            Self::Multiple(errors) => match errors.first() {
+                Some(error) => write!(f, "{}", error),
+                None => Ok(()),
+            },
+            // The code comes first, also for an error placed in a context.
+            error => {
+                if let Some(code) = error.code() {
+                    write!(f, "squattr({}): ", code)?;
+                }
+                error.fmt_message(f)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[inline]
+fn format_keys(keys: &[String]) -> String {
+    keys.iter()
+        .map(|key| format!("`{}`", key))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl From<Error> for syn::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Custom(error) => error,
+            Error::Multiple(errors) => errors
+                .into_iter()
+                .map(syn::Error::from)
+                .collect::<Vec<_>>()
+                .combine()
+                .unwrap_or_else(|| syn::Error::new(Span::call_site(), "unknown error")),
+            error => with_notes(error.span(), &error, error.notes()),
+        }
+    }
+}
+
+impl From<syn::Error> for Error {
+    fn from(error: syn::Error) -> Self {
+        Self::Custom(error)
+    }
+}
+
+/// An error at `span` that also points at the other places involved, each with
+/// a note (the way rustc shows related spans), e.g. every key of a conflict:
+///
+/// ```text
+/// error: key `url` conflicts with `path`
+///  --> src/lib.rs:3:21
+/// error: note: `path` is set here
+///  --> src/lib.rs:3:9
+/// ```
+///
+pub fn with_notes<M, I>(span: Span, message: M, notes: I) -> syn::Error
+where
+    M: Display,
+    I: IntoIterator<Item = (Span, String)>,
+{
+    let mut error = syn::Error::new(span, message);
+
+    for (span, note) in notes {
+        error.combine(syn::Error::new(span, format!("note: {}", note)));
+    }
+
+    error
+}
+
+/// Place `error` in the context of the nested attribute at `key`, e.g.
+/// ``in `opts`: ...``. Nested contexts form a key path, such as
+/// ``in `opts.retry`: ...``.
+///
+pub fn in_key(error: Error, key: &str) -> Error {
+    in_context(error, &|keys, _| keys.insert(0, key.to_string()))
+}
+
+/// Place `error` in the context of the attribute with the given `path`, e.g.
+/// ``in `#[route(...)]`: ...``, or ``in `opts.retry` of `#[route(...)]`: ...``
+/// when it is already in the context of a key (see [in_key]).
+///
+pub fn in_attribute(error: Error, path: &str) -> Error {
+    in_context(error, &|_, attribute| {
+        attribute.get_or_insert_with(|| path.to_string());
+    })
+}
+
+/// Update the context of every error held by `error` through `f`, giving a
+/// context to those without one.
+///
+fn in_context(error: Error, f: &impl Fn(&mut Vec<String>, &mut Option<String>)) -> Error {
+    let (mut keys, mut attribute, error) = match error {
+        Error::Multiple(errors) => {
+            return Error::Multiple(
+                errors
+                    .into_iter()
+                    .map(|error| in_context(error, f))
+                    .collect(),
+            );
+        }
+        Error::Context {
+            keys,
+            attribute,
+            error,
+        } => (keys, attribute, error),
+        // Every message of a combined `syn::Error` is placed in the context
+        // on its own.
+        Error::Custom(error) => {
+            let mut errors = error.into_iter().map(Error::Custom).collect::<Vec<_>>();
+            match errors.len() {
+                1 => (Vec::new(), None, Box::new(errors.remove(0))),
+                _ => return in_context(Error::Multiple(errors), f),
+            }
+        }
+        error => (Vec::new(), None, Box::new(error)),
+    };
+
+    f(&mut keys, &mut attribute);
+
+    Error::Context {
+        keys,
+        attribute,
+        error,
+    }
+}
+
+/// Collects errors, so that as many of them as possible are reported at once
+/// instead of only the first:
+///
+/// ```ignore
+/// let mut errors = Accumulator::new();
+///
+/// let path = errors.handle(String::parse_value(path));
+/// let port = errors.handle(u16::parse_value(port));
+///
+/// errors.finish()?;
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct Accumulator {
+    errors: Vec<Error>,
+}
+
+impl Accumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect an error, or each of [Error::Multiple] errors.
+    ///
+    pub fn push(&mut self, error: impl Into<Error>) {
+        match error.into() {
+            Error::Multiple(errors) => errors.into_iter().for_each(|error| self.push(error)),
+            error => self.errors.push(error),
+        }
+    }
+
+    /// Unwrap a result, or collect its error and return `None`.
+    ///
+    pub fn handle<T, E: Into<Error>>(&mut self, result: std::result::Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+
+    /// The collected errors, in the order they were collected.
+    ///
+    pub fn iter(&self) -> std::slice::Iter<'_, Error> {
+        self.errors.iter()
+    }
+
+    /// Keep only the errors for which `f` returns true, e.g. to take out the
+    /// errors that are reported differently.
+    ///
+    pub fn retain(&mut self, f: impl FnMut(&Error) -> bool) {
+        self.errors.retain(f);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Combine the collected errors into a single error, if any.
+    ///
+    pub fn combine(self) -> Option<syn::Error> {
+        self.errors
+            .into_iter()
+            .map(syn::Error::from)
+            .collect::<Vec<_>>()
+            .combine()
+    }
+
+    /// Fail with the collected errors (as [Error::Multiple] if there are
+    /// several), if any were collected.
+    ///
+    pub fn finish(mut self) -> Result<()> {
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.remove(0)),
+            _ => Err(Error::Multiple(self.errors)),
+        }
+    }
+
+    /// Return `value` if no errors were collected, or fail with the collected
+    /// errors otherwise.
+    ///
+    pub fn finish_with<T>(self, value: T) -> Result<T> {
+        self.finish().map(|()| value)
+    }
+}
+
+impl<E: Into<Error>> Extend<E> for Accumulator {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|error| self.push(error));
+    }
+}
+
+impl From<Accumulator> for Vec<Error> {
+    fn from(accumulator: Accumulator) -> Self {
+        accumulator.errors
+    }
+}
+
+impl IntoIterator for Accumulator {
+    type Item = Error;
+    type IntoIter = std::vec::IntoIter<Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+    use syn::Error;
+
+    use super::Accumulator;
+
+    #[test]
+    fn accumulate_errors() {
+        let mut errors = Accumulator::new();
+
+        assert_eq!(errors.handle(Ok::<_, Error>(1)), Some(1));
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            errors.handle(Err::<u8, _>(Error::new(Span::call_site(), "first"))),
+            None
+        );
+        errors.push(Error::new(Span::call_site(), "second"));
+        assert_eq!(errors.len(), 2);
+
+        let error = syn::Error::from(errors.finish_with(()).unwrap_err());
+        assert_eq!(
+            error
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            ["first", "second"]
+        );
+
+        assert!(
+            Accumulator::new()
+                .finish_with(3)
+                .is_ok_and(|value| value == 3)
+        );
+    }
+
+    #[test]
+    fn inspect_error_kinds() {
+        let mut errors = Accumulator::new();
+
+        errors.push(super::Error::unknown_key(
+            "verbos",
+            Span::call_site(),
+            &["verbose", "path"],
+        ));
+        errors.push(super::Error::MissingKey {
+            key: "path".to_string(),
+            span: Span::call_site(),
+        });
+
+        assert_eq!(
+            errors.iter().map(|error| error.key()).collect::<Vec<_>>(),
+            [Some("verbos"), Some("path")]
+        );
+
+        let mut warnings = vec![];
+        errors.retain(|error| match error {
+            super::Error::UnknownKey { .. } => {
+                warnings.push(error.to_string());
+                false
+            }
+            _ => true,
+        });
+
+        assert_eq!(
+            warnings,
+            ["squattr(E001): unrecognized key `verbos`, did you mean `verbose`?"]
+        );
+        assert_eq!(
+            errors.finish().unwrap_err().to_string(),
+            "squattr(E003): required key `path` not found"
+        );
+
+        let error = super::Error::DuplicateKey {
+            key: "path".to_string(),
+            span: Span::call_site(),
+            first: Some(Span::call_site()),
+        };
+        assert_eq!(error.code(), Some("E002"));
+        assert_eq!(
+            Error::from(error)
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "squattr(E002): duplicate key `path`",
+                "note: `path` is first set here"
+            ]
+        );
+        assert_eq!(
+            super::Error::from(Error::new(Span::call_site(), "custom")).code(),
+            None
+        );
+    }
+
+    #[test]
+    fn list_known_keys() {
+        let error = super::Error::unknown_key("qux", Span::call_site(), &["bar", "baz", "ban"]);
+
+        assert_eq!(
+            error.to_string(),
+            "squattr(E001): unrecognized key `qux`, expected one of `bar`, `baz`, `ban`"
+        );
+    }
+
+    #[test]
+    fn name_declared_type() {
+        let error = super::Error::TypeMismatch {
+            key: Some("port".to_string()),
+            ty: Some("u16"),
+            expected: "integer".to_string(),
+            found: None,
+            span: Span::call_site(),
+            suggestions: vec![],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "squattr(E004): expected integer of type `u16` for `port`"
+        );
+    }
+
+    #[test]
+    fn place_errors_in_context() {
+        let mut errors = Accumulator::new();
+
+        errors.push(super::Error::MissingKey {
+            key: "count".to_string(),
+            span: Span::call_site(),
+        });
+        errors.push(super::Error::DuplicateKey {
+            key: "delay".to_string(),
+            span: Span::call_site(),
+            first: Some(Span::call_site()),
+        });
+
+        let mut custom = Error::new(Span::call_site(), "first");
+        custom.combine(Error::new(Span::call_site(), "second"));
+        errors.push(custom);
+
+        let error = super::in_key(errors.finish().unwrap_err(), "retry");
+        let error = super::in_attribute(super::in_key(error, "opts"), "route");
+
+        assert_eq!(error.code(), None);
+        assert_eq!(
+            Error::from(error)
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "squattr(E003): in `opts.retry` of `#[route(...)]`: required key `count` not found",
+                "squattr(E002): in `opts.retry` of `#[route(...)]`: duplicate key `delay`",
+                "note: `delay` is first set here",
+                "in `opts.retry` of `#[route(...)]`: first",
+                "in `opts.retry` of `#[route(...)]`: second",
+            ]
+        );
+
+        let error = super::in_attribute(
+            super::Error::MissingKey {
+                key: "path".to_string(),
+                span: Span::call_site(),
+            },
+            "route",
+        );
+        assert_eq!(error.code(), Some("E003"));
+        assert_eq!(error.key(), Some("path"));
+        assert!(matches!(error.inner(), super::Error::MissingKey { .. }));
+        assert_eq!(
+            error.to_string(),
+            "squattr(E003): in `#[route(...)]`: required key `path` not found"
+        );
+    }
+}
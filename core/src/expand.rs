@@ -1,150 +1,1717 @@
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::{
-    Data, DataStruct, DeriveInput, Error, Field, Fields, FieldsNamed, Ident, PathArguments,
-    PathSegment, Result, Type, TypePath, parse2, punctuated, spanned::Spanned,
+    Data, DataEnum, DataStruct, DeriveInput, Error, ExprLit, Field, Fields, FieldsNamed,
+    FieldsUnnamed, GenericArgument, Ident, Lit, Meta, MetaNameValue, PathArguments, PathSegment,
+    Result, Token, Type, TypePath, Variant, Visibility,
+    ext::IdentExt,
+    parse2,
+    punctuated::{self, Punctuated},
+    spanned::Spanned,
+};
+
+use crate::{
+    dym::{Scorer, Suggestions},
+    errors::Accumulator,
+    options::{ContainerOptions, FieldOptions, MergeStrategy, RenameRule, parse_options},
 };
 
 pub fn expand(input: TokenStream) -> Result<TokenStream> {
     let input = parse2::<DeriveInput>(input)?;
+    let options = parse_options::<ContainerOptions>(&input.attrs)?;
+    let doc = doc_string(&input.attrs);
     let ident = input.ident;
     match input.data {
-        Data::Struct(DataStruct { fields, .. }) => expand_struct(ident.clone(), fields),
-        Data::Enum(_) => Err(Error::new(Span::call_site(), "enums are not supported")),
+        Data::Struct(DataStruct { fields, .. }) => {
+            expand_struct(ident.clone(), input.vis, fields, options, doc)
+        }
+        Data::Enum(DataEnum { variants, .. }) => expand_enum(ident, variants, options, doc),
         Data::Union(_) => Err(Error::new(Span::call_site(), "unions are not supported")),
     }
 }
 
-fn expand_struct(ident: Ident, fields: Fields) -> Result<TokenStream> {
+fn expand_enum(
+    ident: Ident,
+    variants: Punctuated<Variant, Token![,]>,
+    options: ContainerOptions,
+    doc: String,
+) -> Result<TokenStream> {
+    if options.flags {
+        return Err(Error::new(
+            Span::call_site(),
+            "`flags` is only supported on structs",
+        ));
+    }
+
+    if options.to_tokens {
+        return Err(Error::new(
+            Span::call_site(),
+            "`to_tokens` is only supported on structs",
+        ));
+    }
+
+    if options.partial {
+        return Err(Error::new(
+            Span::call_site(),
+            "`partial` is only supported on structs",
+        ));
+    }
+
+    if options.merge {
+        return Err(Error::new(
+            Span::call_site(),
+            "`merge` is only supported on structs",
+        ));
+    }
+
+    if options.defaults_file.is_some() {
+        return Err(Error::new(
+            Span::call_site(),
+            "`defaults_file` is only supported on structs",
+        ));
+    }
+
+    if options.from_derive_input {
+        return Err(Error::new(
+            Span::call_site(),
+            "`from_derive_input` is only supported on structs",
+        ));
+    }
+
+    if variants
+        .iter()
+        .all(|variant| matches!(variant.fields, Fields::Unit))
+    {
+        expand_unit_enum(ident, variants.iter(), options)
+    } else if variants.iter().all(|variant| is_newtype(&variant.fields)) {
+        expand_untagged_enum(ident, variants.iter(), options)
+    } else if variants
+        .iter()
+        .all(|variant| matches!(variant.fields, Fields::Named(_) | Fields::Unit))
+    {
+        expand_tagged_enum(ident, variants.iter(), options, doc)
+    } else {
+        Err(Error::new(
+            Span::call_site(),
+            "only enums with either unit variants, newtype variants or struct variants are supported",
+        ))
+    }
+}
+
+fn expand_tagged_enum(
+    ident: Ident,
+    variants: punctuated::Iter<Variant>,
+    options: ContainerOptions,
+    doc: String,
+) -> Result<TokenStream> {
+    let rename_rule = options.rename_all.unwrap_or(RenameRule::Snake);
+
+    let mut variant_strs = TokenStream::new();
+    let mut schema_variants = TokenStream::new();
+    let mut variant_names = Vec::new();
+    let mut match_arms = TokenStream::new();
+    let mut to_tokens_arms = TokenStream::new();
+    let mut errors = Accumulator::new();
+
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        let variant_str = rename_rule.apply(&variant_ident.to_string());
+        let variant_doc = doc_string(&variant.attrs);
+        to_tokens_arms.extend(expand_tagged_to_tokens(variant, &variant_str));
+        variant_names.push(variant_str.clone());
+        let schema_keys = expand_schema_keys(variant.fields.iter());
+
+        let body = expand_named_fields(
+            &ident,
+            quote!(Self::#variant_ident),
+            variant.fields.iter(),
+            &options,
+            false,
+        );
+        let Some(body) = errors.handle(body) else {
+            continue;
+        };
+
+        variant_strs.extend(quote! {
+            #variant_str,
+        });
+
+        schema_variants.extend(quote! {
+            ::squattr::schema::Variant {
+                name: #variant_str,
+                doc: #variant_doc,
+                keys: #schema_keys,
+            },
+        });
+
+        match_arms.extend(quote! {
+            (#variant_str, values) => {
+                #body
+            }
+        });
+    }
+
+    errors.finish()?;
+
+    let path = expand_path(&options);
+    let parse = expand_parse(&ident, &options);
+    let ident_str = ident.to_string();
+    let help = expand_help(&variant_names, quote!(&values[0]));
+    let parse_tagged = expand_suggesting(
+        &options,
+        quote!(::squattr::types),
+        "parse_tagged",
+        quote!(values, &[#variant_strs]),
+    );
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::squattr::attribute::Attribute for #ident {
+            #path
+
+            const SCHEMA: ::squattr::schema::Schema = ::squattr::schema::Schema::Tagged {
+                name: #ident_str,
+                doc: #doc,
+                variants: &[#schema_variants],
+            };
+
+            fn from_values(values: ::squattr::ast::Values) -> ::syn::Result<Self> {
+                ::std::result::Result::Ok(Self::parse_values(values)?)
+            }
+
+            fn parse_values(values: ::squattr::ast::Values) -> ::squattr::errors::Result<Self> {
+                if values.len() == 1 {
+                    #help
+                }
+
+                match #parse_tagged? {
+                    #match_arms
+                    _ => ::std::unreachable!("variant names are matched by `parse_tagged`"),
+                }
+            }
+
+            fn from_list_entry(value: ::squattr::ast::Value) -> ::syn::Result<Self> {
+                Self::from_values(<::squattr::ast::Values as ::std::convert::From<_>>::from(value))
+            }
+        }
+
+        /// A variant is written as its name, followed by its keys (if any) in
+        /// parentheses, e.g. `file(path = "x")`.
+        #[automatically_derived]
+        impl ::squattr::tokens::ToValueTokens for #ident {
+            fn to_value_tokens(&self) -> ::proc_macro2::TokenStream {
+                match self {
+                    #to_tokens_arms
+                }
+            }
+
+            fn to_key_tokens(
+                &self,
+                id: &::syn::Ident,
+            ) -> ::std::option::Option<::proc_macro2::TokenStream> {
+                ::std::option::Option::Some(::squattr::tokens::list_tokens(
+                    id,
+                    Self::to_value_tokens(self),
+                ))
+            }
+        }
+
+        #parse
+    })
+}
+
+/// Generate the match arm of `ToValueTokens::to_value_tokens` writing a
+/// `variant` of a tagged enum, by the name `variant_str`.
+///
+fn expand_tagged_to_tokens(variant: &Variant, variant_str: &str) -> TokenStream {
+    let variant_ident = &variant.ident;
+    let name = quote!(&::syn::Ident::new(#variant_str, ::proc_macro2::Span::call_site()));
+
+    if let Fields::Unit = variant.fields {
+        return quote! {
+            Self::#variant_ident => ::quote::ToTokens::to_token_stream(#name),
+        };
+    }
+
+    let bindings = variant.fields.iter().filter_map(|field| {
+        let options = parse_options::<FieldOptions>(&field.attrs).ok()?;
+        (!options.spans).then_some(field.ident.as_ref()?)
+    });
+    let entries = expand_entries(variant.fields.iter(), |ident| quote!((*#ident)));
+
+    quote! {
+        Self::#variant_ident { #(#bindings,)* .. } => {
+            ::squattr::tokens::list_tokens(#name, #entries)
+        }
+    }
+}
+
+fn expand_untagged_enum(
+    ident: Ident,
+    variants: punctuated::Iter<Variant>,
+    options: ContainerOptions,
+) -> Result<TokenStream> {
+    if options.rename_all.is_some() {
+        return Err(Error::new(
+            Span::call_site(),
+            "`rename_all` is only supported on enums with unit variants",
+        ));
+    }
+
+    if options.path.is_some() || options.parse {
+        return Err(Error::new(
+            Span::call_site(),
+            "`path` and `parse` are not supported on enums with newtype variants",
+        ));
+    }
+
+    if options.suggest.is_some() {
+        return Err(Error::new(
+            Span::call_site(),
+            "`suggest` is not supported on enums with newtype variants",
+        ));
+    }
+
+    let mut parsers = TokenStream::new();
+    let mut kinds = TokenStream::new();
+    let mut variant_idents = Vec::new();
+
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        let ty = &variant.fields.iter().next().unwrap().ty;
+        variant_idents.push(variant_ident);
+
+        parsers.extend(quote_spanned! {
+            ty.span()=>
+            |value| <#ty as ::squattr::types::ParseValue>::parse(value).map(Self::#variant_ident),
+        });
+
+        kinds.extend(quote_spanned! {
+            ty.span()=>
+            <#ty as ::squattr::types::ParseValue>::KIND,
+        });
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::squattr::types::ParseValue for #ident {
+            const KIND: ::squattr::schema::Kind = ::squattr::schema::Kind::Either(&[#kinds]);
+
+            fn parse(value: ::squattr::ast::Value) -> ::squattr::errors::Result<Self> {
+                ::squattr::types::parse_untagged(value, &[#parsers])
+            }
+        }
+
+        /// A variant is written as its value.
+        #[automatically_derived]
+        impl ::squattr::tokens::ToValueTokens for #ident {
+            fn to_value_tokens(&self) -> ::proc_macro2::TokenStream {
+                match self {
+                    #(Self::#variant_idents(value) => {
+                        ::squattr::tokens::ToValueTokens::to_value_tokens(value)
+                    })*
+                }
+            }
+
+            fn to_key_tokens(
+                &self,
+                id: &::syn::Ident,
+            ) -> ::std::option::Option<::proc_macro2::TokenStream> {
+                match self {
+                    #(Self::#variant_idents(value) => {
+                        ::squattr::tokens::ToValueTokens::to_key_tokens(value, id)
+                    })*
+                }
+            }
+        }
+    })
+}
+
+fn expand_unit_enum(
+    ident: Ident,
+    variants: punctuated::Iter<Variant>,
+    options: ContainerOptions,
+) -> Result<TokenStream> {
+    if options.path.is_some() || options.parse {
+        return Err(Error::new(
+            Span::call_site(),
+            "`path` and `parse` are not supported on enums with unit variants",
+        ));
+    }
+
+    let rename_rule = options.rename_all.unwrap_or(RenameRule::Snake);
+
+    let mut variant_strs = TokenStream::new();
+    let mut match_arms = TokenStream::new();
+    let mut name_arms = TokenStream::new();
+
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        let variant_str = rename_rule.apply(&variant_ident.to_string());
+
+        variant_strs.extend(quote! {
+            #variant_str,
+        });
+
+        match_arms.extend(quote! {
+            #variant_str => ::std::result::Result::Ok(Self::#variant_ident),
+        });
+
+        name_arms.extend(quote! {
+            Self::#variant_ident => #variant_str,
+        });
+    }
+
+    let parse_variant = expand_suggesting(
+        &options,
+        quote!(::squattr::types),
+        "parse_variant",
+        quote!(value, &[#variant_strs]),
+    );
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::squattr::types::ParseValue for #ident {
+            const KIND: ::squattr::schema::Kind = ::squattr::schema::Kind::OneOf(&[#variant_strs]);
+
+            fn parse(value: ::squattr::ast::Value) -> ::squattr::errors::Result<Self> {
+                match #parse_variant? {
+                    #match_arms
+                    _ => ::std::unreachable!("variant names are matched by `parse_variant`"),
+                }
+            }
+        }
+
+        /// A variant is written as its name in a string literal, as a renamed
+        /// variant (e.g. `very-slow`) is not an identifier.
+        #[automatically_derived]
+        impl ::squattr::tokens::ToValueTokens for #ident {
+            fn to_value_tokens(&self) -> ::proc_macro2::TokenStream {
+                let name = match self {
+                    #name_arms
+                };
+
+                ::quote::ToTokens::into_token_stream(::syn::LitStr::new(
+                    name,
+                    ::proc_macro2::Span::call_site(),
+                ))
+            }
+        }
+    })
+}
+
+fn expand_struct(
+    ident: Ident,
+    vis: Visibility,
+    fields: Fields,
+    options: ContainerOptions,
+    doc: String,
+) -> Result<TokenStream> {
+    if options.rename_all.is_some() {
+        return Err(Error::new(
+            Span::call_site(),
+            "`rename_all` is only supported on enums",
+        ));
+    }
+
+    if options.partial && (options.flags || !matches!(fields, Fields::Named(_))) {
+        return Err(Error::new(
+            Span::call_site(),
+            "`partial` is only supported on structs with named fields",
+        ));
+    }
+
+    if options.merge && (options.flags || !matches!(fields, Fields::Named(_))) {
+        return Err(Error::new(
+            Span::call_site(),
+            "`merge` is only supported on structs with named fields",
+        ));
+    }
+
+    if options.defaults_file.is_some() && (options.flags || !matches!(fields, Fields::Named(_))) {
+        return Err(Error::new(
+            Span::call_site(),
+            "`defaults_file` is only supported on structs with named fields",
+        ));
+    }
+
+    if options.from_derive_input && (options.flags || !matches!(fields, Fields::Named(_))) {
+        return Err(Error::new(
+            Span::call_site(),
+            "`from_derive_input` is only supported on structs with named fields",
+        ));
+    }
+
+    if options.from_derive_input
+        && (options.parse || options.to_tokens || options.partial || options.merge)
+    {
+        return Err(Error::new(
+            Span::call_site(),
+            "`from_derive_input` cannot be combined with `parse`, `to_tokens`, `partial` or \
+             `merge`",
+        ));
+    }
+
+    if let Some(file) = &options.defaults_file {
+        check_defaults_file(file)?;
+    }
+
     match fields {
-        Fields::Named(FieldsNamed { named, .. }) => expand_named_struct(ident, named.iter()),
-        Fields::Unnamed(_) => {
+        Fields::Named(FieldsNamed { named, .. }) if options.flags => {
+            expand_flags_struct(ident, named.iter(), options, doc)
+        }
+        Fields::Named(FieldsNamed { named, .. }) if options.from_derive_input => {
+            expand_input_struct(ident, vis, named, options, doc)
+        }
+        Fields::Named(FieldsNamed { named, .. }) => {
+            expand_named_struct(ident, vis, named.iter(), options, doc)
+        }
+        Fields::Unnamed(_) => Err(Error::new(
+            Span::call_site(),
+            "unnamed structs are not supported",
+        )),
+        Fields::Unit => Err(Error::new(
+            Span::call_site(),
+            "unit structs are not supported",
+        )),
+    }
+}
+
+/// Reject a `defaults_file` the attribute could never read, as the file
+/// itself is only looked up once the attribute is parsed.
+///
+fn check_defaults_file(file: &str) -> Result<()> {
+    let error = match file.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("toml") if cfg!(feature = "toml") => return Ok(()),
+        Some("json") if cfg!(feature = "json") => return Ok(()),
+        Some("toml") => "reading a TOML `defaults_file` requires the `toml` feature",
+        Some("json") => "reading a JSON `defaults_file` requires the `json` feature",
+        _ => "expected a `.toml` or `.json` file for `defaults_file`",
+    };
+
+    Err(Error::new(Span::call_site(), error))
+}
+
+fn expand_named_struct(
+    ident: Ident,
+    vis: Visibility,
+    fields: punctuated::Iter<Field>,
+    options: ContainerOptions,
+    doc: String,
+) -> Result<TokenStream> {
+    let to_tokens = expand_to_tokens(&ident, fields.clone(), &options);
+    let schema_keys = expand_schema_keys(fields.clone());
+    let body = expand_named_fields(&ident, quote!(Self), fields.clone(), &options, false)?;
+    let merge = expand_merge(&ident, &vis, fields.clone(), &options)?;
+    let partial = expand_partial(&ident, &vis, fields, &options)?;
+    let path = expand_path(&options);
+    let parse = expand_parse(&ident, &options);
+    let ident_str = ident.to_string();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::squattr::attribute::Attribute for #ident {
+            #path
+
+            const SCHEMA: ::squattr::schema::Schema = ::squattr::schema::Schema::Struct {
+                name: #ident_str,
+                doc: #doc,
+                keys: #schema_keys,
+            };
+
+            fn from_values(values: ::squattr::ast::Values) -> ::syn::Result<Self> {
+                ::std::result::Result::Ok(Self::parse_values(values)?)
+            }
+
+            fn parse_values(values: ::squattr::ast::Values) -> ::squattr::errors::Result<Self> {
+                #body
+            }
+        }
+
+        #parse
+
+        #to_tokens
+
+        #partial
+
+        #merge
+    })
+}
+
+/// The fields of a struct deriving `FromDeriveInput` that are taken from the
+/// input rather than parsed as keys.
+///
+const INPUT_FIELDS: &[&str] = &["ident", "vis", "generics", "attrs", "data"];
+
+/// Generate an implementation of `FromDeriveInput`, if requested through
+/// `#[squattr(from_derive_input)]`.
+///
+/// The fields named after a part of the input (see [INPUT_FIELDS]) are cloned
+/// from it, except for `data`, which is parsed through `FromDeriveInput` of its
+/// own (e.g. as an `InputData`). The keys of the container's attribute, i.e.
+/// all other fields, are parsed by a struct by the same name, declared within
+/// the implementation so that errors and help name the deriving struct.
+///
+fn expand_input_struct(
+    ident: Ident,
+    vis: Visibility,
+    fields: Punctuated<Field, Token![,]>,
+    options: ContainerOptions,
+    doc: String,
+) -> Result<TokenStream> {
+    let (input_fields, key_fields): (Vec<_>, Vec<_>) = fields.into_iter().partition(|field| {
+        field
+            .ident
+            .as_ref()
+            .is_some_and(|ident| INPUT_FIELDS.iter().any(|name| ident == name))
+    });
+
+    let key_fields = key_fields
+        .into_iter()
+        .collect::<Punctuated<Field, Token![,]>>();
+    let key_idents = key_fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let key_tys = key_fields.iter().map(|field| &field.ty);
+    let keys = expand_named_struct(ident.clone(), vis, key_fields.iter(), options, doc)?;
+
+    let input_values = input_fields.iter().map(|field| {
+        let field_ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+
+        if field_ident == "data" {
+            quote_spanned! {ty.span()=>
+                let data = errors.handle(
+                    <#ty as ::squattr::input::FromDeriveInput>::from_derive_input(input, path),
+                );
+            }
+        } else {
+            quote_spanned! {ty.span()=>
+                let #field_ident: #ty = ::std::clone::Clone::clone(&input.#field_ident);
+            }
+        }
+    });
+
+    let input_idents = input_fields.iter().map(|field| {
+        let field_ident = field.ident.as_ref().unwrap();
+
+        if field_ident == "data" {
+            quote!(data: data.expect("data has been parsed"),)
+        } else {
+            quote!(#field_ident,)
+        }
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::squattr::input::FromDeriveInput for #ident {
+            fn from_derive_input<P>(input: &::syn::DeriveInput, path: &P) -> ::syn::Result<Self>
+            where
+                P: ::squattr::attribute::AttributePath + ?::std::marker::Sized,
+            {
+                struct #ident {
+                    #(#key_idents: #key_tys,)*
+                }
+
+                #keys
+
+                let mut errors = ::squattr::errors::Accumulator::new();
+                let keys = errors.handle(
+                    <#ident as ::squattr::attribute::Attribute>::from_attributes(&input.attrs, path),
+                );
+                #(#input_values)*
+
+                errors.finish()?;
+
+                // The keys are bound last, as they may shadow `input` or `path`.
+                let #ident { #(#key_idents),* } = keys.expect("keys have been parsed");
+
+                ::std::result::Result::Ok(Self {
+                    #(#input_idents)*
+                    #(#key_idents,)*
+                })
+            }
+        }
+    })
+}
+
+fn expand_flags_struct(
+    ident: Ident,
+    fields: punctuated::Iter<Field>,
+    options: ContainerOptions,
+    doc: String,
+) -> Result<TokenStream> {
+    let mut variables = TokenStream::new();
+    let mut match_arms = TokenStream::new();
+    let mut struct_fields = TokenStream::new();
+    let mut flag_strs = TokenStream::new();
+    let mut flag_names = Vec::new();
+
+    let mut errors = Accumulator::new();
+
+    let to_tokens = expand_to_tokens(&ident, fields.clone(), &options);
+    let schema_flags = expand_schema_keys(fields.clone());
+
+    for field in fields {
+        if !is_boolean(&field.ty) {
+            errors.push(Error::new(field.ty.span(), "flags must be of type `bool`"));
+            continue;
+        }
+
+        let ident = field.ident.as_ref().unwrap();
+        let ident_str = ident.to_string();
+
+        flag_names.push(ident_str.clone());
+
+        flag_strs.extend(quote! {
+            #ident_str,
+        });
+
+        variables.extend(quote! {
+            let mut #ident = false;
+        });
+
+        match_arms.extend(quote! {
+            #ident_str => &mut #ident,
+        });
+
+        struct_fields.extend(quote! {
+            #ident,
+        });
+    }
+
+    errors.finish()?;
+
+    let path = expand_path(&options);
+    let parse = expand_parse(&ident, &options);
+    let ident_str = ident.to_string();
+    let help = expand_help(&flag_names, quote!(&value));
+    let parse_flag = expand_suggesting(
+        &options,
+        quote!(::squattr::types),
+        "parse_flag",
+        quote!(value, &[#flag_strs]),
+    );
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::squattr::attribute::Attribute for #ident {
+            #path
+
+            const SCHEMA: ::squattr::schema::Schema = ::squattr::schema::Schema::Flags {
+                name: #ident_str,
+                doc: #doc,
+                flags: #schema_flags,
+            };
+
+            fn from_values(values: ::squattr::ast::Values) -> ::syn::Result<Self> {
+                ::std::result::Result::Ok(Self::parse_values(values)?)
+            }
+
+            fn parse_values(values: ::squattr::ast::Values) -> ::squattr::errors::Result<Self> {
+                #variables
+
+                let mut errors = ::squattr::errors::Accumulator::new();
+
+                for value in values {
+                    let span = value.span();
+
+                    #help
+
+                    let name = match #parse_flag {
+                        ::std::result::Result::Ok(name) => name,
+                        ::std::result::Result::Err(error) => {
+                            errors.push(error);
+                            continue;
+                        }
+                    };
+
+                    let flag = match name {
+                        #match_arms
+                        _ => ::std::unreachable!("flag names are matched by `parse_flag`"),
+                    };
+
+                    if ::std::mem::replace(flag, true) {
+                        errors.push(::squattr::types::duplicate_flag(name, span));
+                    }
+                }
+
+                errors.finish()?;
+
+                ::std::result::Result::Ok(Self {
+                    #struct_fields
+                })
+            }
+        }
+
+        #parse
+
+        #to_tokens
+    })
+}
+
+/// Generate the `PATH` constant of an `Attribute` implementation, if a path was
+/// set through `#[squattr(path = "...")]`.
+///
+fn expand_path(options: &ContainerOptions) -> Option<TokenStream> {
+    options.path.as_ref().map(|path| {
+        quote! {
+            const PATH: ::std::option::Option<&'static str> = ::std::option::Option::Some(#path);
+        }
+    })
+}
+
+/// Generate a call to the suggesting function `name` at `path` (e.g.
+/// `parse_variant`), or to its `_with` counterpart if suggestions were
+/// configured through `#[squattr(suggest(...))]`.
+///
+fn expand_suggesting(
+    options: &ContainerOptions,
+    path: TokenStream,
+    name: &str,
+    args: TokenStream,
+) -> TokenStream {
+    match &options.suggest {
+        Some(_) => {
+            let name = format_ident!("{}_with", name);
+            let suggestions = expand_suggestions(options);
+            quote!(#path::#name(#args, &#suggestions))
+        }
+        None => {
+            let name = format_ident!("{}", name);
+            quote!(#path::#name(#args))
+        }
+    }
+}
+
+/// Generate the suggestions configured through `#[squattr(suggest(...))]`, or
+/// the default ones.
+///
+fn expand_suggestions(options: &ContainerOptions) -> TokenStream {
+    match &options.suggest {
+        Some(Suggestions {
+            threshold,
+            max,
+            scorer,
+        }) => {
+            let scorer = match scorer {
+                Scorer::JaroWinkler => quote!(JaroWinkler),
+                Scorer::Levenshtein => quote!(Levenshtein),
+            };
+
+            quote! {
+                ::squattr::dym::Suggestions {
+                    threshold: #threshold,
+                    max: #max,
+                    scorer: ::squattr::dym::Scorer::#scorer,
+                }
+            }
+        }
+        None => quote!(::squattr::dym::Suggestions::DEFAULT),
+    }
+}
+
+/// Generate a check returning the help of the attribute (see `Schema::help`) if
+/// `value` is the `help` pseudo-key, unless one of the `names` the attribute
+/// accepts already is `help`.
+///
+fn expand_help(names: &[String], value: TokenStream) -> Option<TokenStream> {
+    (!names.iter().any(|name| name == "help")).then(|| {
+        quote! {
+            if ::squattr::schema::is_help(#value) {
+                return ::std::result::Result::Err(::std::convert::From::from(::syn::Error::new(
+                    ::squattr::ast::Value::span(#value),
+                    <Self as ::squattr::attribute::Attribute>::SCHEMA.help(),
+                )));
+            }
+        }
+    })
+}
+
+/// Generate an implementation of `syn::parse::Parse` delegating to
+/// `Attribute::from_input`, if requested through `#[squattr(parse)]`.
+///
+fn expand_parse(ident: &Ident, options: &ContainerOptions) -> Option<TokenStream> {
+    options.parse.then(|| {
+        quote! {
+            #[automatically_derived]
+            impl ::syn::parse::Parse for #ident {
+                fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<Self> {
+                    <Self as ::squattr::attribute::Attribute>::from_input(input)
+                }
+            }
+        }
+    })
+}
+
+/// Generate a `Partial*` struct holding every key as an `Option`, along with
+/// a `from_values_partial` function that keeps whatever could be parsed next to
+/// the errors, if requested through `#[squattr(partial)]`.
+///
+fn expand_partial(
+    ident: &Ident,
+    vis: &Visibility,
+    fields: punctuated::Iter<Field>,
+    options: &ContainerOptions,
+) -> Result<Option<TokenStream>> {
+    if !options.partial {
+        return Ok(None);
+    }
+
+    let partial_ident = format_ident!("Partial{}", ident);
+    let partial_doc = format!(
+        "The keys of [`{}`] that could be parsed, see `{}::from_values_partial`.",
+        ident, ident
+    );
+
+    let mut partial_fields = TokenStream::new();
+
+    for field in fields.clone() {
+        let field_vis = &field.vis;
+        let field_ident = &field.ident;
+        let ty = &field.ty;
+
+        let options = parse_options::<FieldOptions>(&field.attrs)?;
+
+        partial_fields.extend(if options.spans || is_optional_field(ty, &options) {
+            quote!(#field_vis #field_ident: #ty,)
+        } else {
+            quote!(#field_vis #field_ident: ::std::option::Option<#ty>,)
+        });
+    }
+
+    let body = expand_named_fields(ident, quote!(#partial_ident), fields, options, true)?;
+
+    Ok(Some(quote! {
+        #[doc = #partial_doc]
+        #vis struct #partial_ident {
+            #partial_fields
+        }
+
+        #[automatically_derived]
+        impl #ident {
+            /// Parse `values` like `Attribute::from_values`, but keep going past
+            /// errors and return every key that could be parsed along with
+            /// them.
+            #vis fn from_values_partial(
+                values: ::squattr::ast::Values,
+            ) -> (#partial_ident, ::std::vec::Vec<::squattr::errors::Error>) {
+                #body
+            }
+        }
+    }))
+}
+
+/// Generate `merge_over`, which takes every key that was not set from a base
+/// attribute (e.g. one set on the container, providing the defaults of those
+/// on its fields), and `merge`, which combines two attributes key by key
+/// according to the [MergeStrategy] of every field.
+///
+/// Whether a key was set is told by the field collecting the spans, if any,
+/// and otherwise by optional fields being `None` and flags being absent. Any
+/// other field is always taken as set, which the generated methods document.
+///
+fn expand_merge(
+    ident: &Ident,
+    vis: &Visibility,
+    fields: punctuated::Iter<Field>,
+    options: &ContainerOptions,
+) -> Result<Option<TokenStream>> {
+    let fields = fields
+        .map(|field| Ok((field, parse_options::<FieldOptions>(&field.attrs)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    if !options.merge {
+        if let Some(strategy) = fields
+            .iter()
+            .find_map(|(_, options)| options.merge.as_ref())
+        {
             return Err(Error::new(
-                Span::call_site(),
-                "unnamed structs are not supported",
+                strategy.key_span(),
+                "a merge strategy is only used along with `#[squattr(merge)]`",
             ));
         }
-        Fields::Unit => {
+
+        return Ok(None);
+    }
+
+    let spans = fields
+        .iter()
+        .find(|(_, options)| options.spans)
+        .and_then(|(field, _)| field.ident.as_ref());
+
+    let merged = fields.iter().map(|(field, options)| {
+        let field_ident = field.ident.as_ref().unwrap();
+        let ident_str = field_ident.to_string();
+        let ty = &field.ty;
+        let base = quote!(::std::clone::Clone::clone(&base.#field_ident));
+
+        let value = match spans {
+            _ if options.spans => quote!(self.#field_ident),
+            Some(spans) => quote! {
+                if self.#spans.contains(#ident_str) {
+                    self.#field_ident
+                } else {
+                    #base
+                }
+            },
+            None if is_optional_field(ty, options) => quote!(self.#field_ident.or_else(|| #base)),
+            None if is_squattr_type(ty, "Flag") => quote! {
+                if self.#field_ident.is_present() {
+                    self.#field_ident
+                } else {
+                    #base
+                }
+            },
+            None => quote!(self.#field_ident),
+        };
+
+        quote_spanned!(ty.span()=> #field_ident: #value,)
+    });
+
+    let combined = fields
+        .iter()
+        .map(|(field, options)| expand_merge_field(field, options, spans))
+        .collect::<Result<Vec<_>>>()?;
+
+    let always_set = spans.is_none()
+        && fields.iter().any(|(field, options)| {
+            !is_optional_field(&field.ty, options) && !is_squattr_type(&field.ty, "Flag")
+        });
+
+    let (merge_over_doc, merge_doc) = if always_set {
+        (
+            quote! {
+                ///
+                /// As no field collects the spans, whether a key was set is
+                /// only known for optional fields and flags: any other field
+                /// is always taken from this attribute.
+            },
+            quote! {
+                ///
+                /// As no field collects the spans, whether a key was set is
+                /// only known for optional fields and flags: any other field
+                /// is taken as set on both, and so from `other` (or from this
+                /// attribute if merged with `first`), even if `other` only
+                /// holds its default.
+            },
+        )
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    };
+
+    let spans = spans.map(|spans| {
+        quote! {
+            let mut spans = ::std::clone::Clone::clone(&self.#spans);
+            for (key, span) in other.#spans.iter() {
+                spans.insert(key, span);
+            }
+        }
+    });
+
+    Ok(Some(quote! {
+        #[automatically_derived]
+        impl #ident {
+            /// Layer this attribute over `base`, taking every key that was not
+            /// set from it.
+            #merge_over_doc
+            #vis fn merge_over(self, base: &Self) -> Self {
+                Self {
+                    #(#merged)*
+                }
+            }
+
+            /// Combine this attribute with `other` (e.g. one given at a later
+            /// site), key by key. A key set on both is taken from `other`,
+            /// unless its field is merged otherwise.
+            #merge_doc
+            #vis fn merge(self, other: Self) -> ::syn::Result<Self> {
+                #[allow(unused_mut)]
+                let mut errors = ::squattr::errors::Accumulator::new();
+                #spans
+
+                let merged = Self {
+                    #(#combined)*
+                };
+
+                errors.finish()?;
+
+                ::std::result::Result::Ok(merged)
+            }
+        }
+    }))
+}
+
+/// Generate the value of a field combined by `merge` from `self` and `other`,
+/// see [expand_merge].
+///
+fn expand_merge_field(
+    field: &Field,
+    options: &FieldOptions,
+    spans: Option<&Ident>,
+) -> Result<TokenStream> {
+    let field_ident = field.ident.as_ref().unwrap();
+    let ident_str = field_ident.to_string();
+    let ty = &field.ty;
+    let optional = is_optional_field(ty, options);
+
+    if options.spans {
+        return Ok(quote!(#field_ident: spans,));
+    }
+
+    let is_set = |receiver: TokenStream| match spans {
+        Some(spans) => Some(quote!(#receiver.#spans.contains(#ident_str))),
+        None if optional => Some(quote!(#receiver.#field_ident.is_some())),
+        None if is_squattr_type(ty, "Flag") => Some(quote!(#receiver.#field_ident.is_present())),
+        None => None,
+    };
+
+    let strategy = options.merge.as_deref().copied().unwrap_or_default();
+
+    let value = match (strategy, is_set(quote!(self)), is_set(quote!(other))) {
+        (MergeStrategy::First, Some(self_set), _) => quote! {
+            if #self_set { self.#field_ident } else { other.#field_ident }
+        },
+        (MergeStrategy::First, None, _) => quote!(self.#field_ident),
+        (MergeStrategy::Last, _, Some(other_set)) => quote! {
+            if #other_set { other.#field_ident } else { self.#field_ident }
+        },
+        (MergeStrategy::Last, _, None) => quote!(other.#field_ident),
+        (MergeStrategy::Append, _, _) if optional => quote! {
+            match (self.#field_ident, other.#field_ident) {
+                (::std::option::Option::Some(mut first), ::std::option::Option::Some(last)) => {
+                    ::std::iter::Extend::extend(&mut first, last);
+                    ::std::option::Option::Some(first)
+                }
+                (first, last) => ::std::option::Option::or(first, last),
+            }
+        },
+        (MergeStrategy::Append, _, _) => quote! {{
+            let mut first = self.#field_ident;
+            ::std::iter::Extend::extend(&mut first, other.#field_ident);
+            first
+        }},
+        (MergeStrategy::Error, Some(self_set), Some(other_set)) => {
+            let (span, first) = match spans {
+                Some(spans) => (
+                    quote! {
+                        other.#spans
+                            .get(#ident_str)
+                            .unwrap_or_else(::proc_macro2::Span::call_site)
+                    },
+                    quote!(self.#spans.get(#ident_str)),
+                ),
+                None => (
+                    quote!(::proc_macro2::Span::call_site()),
+                    quote!(::std::option::Option::None),
+                ),
+            };
+
+            quote! {
+                if #self_set && #other_set {
+                    errors.push(::squattr::errors::Error::DuplicateKey {
+                        key: ::std::string::ToString::to_string(#ident_str),
+                        span: #span,
+                        first: #first,
+                    });
+                    self.#field_ident
+                } else if #other_set {
+                    other.#field_ident
+                } else {
+                    self.#field_ident
+                }
+            }
+        }
+        (MergeStrategy::Error, _, _) => {
             return Err(Error::new(
-                Span::call_site(),
-                "unit structs are not supported",
+                options.merge.as_ref().unwrap().value_span(),
+                "whether the key is set on both attributes is unknown, as the field \
+                 is neither optional nor a flag, and no field collects the spans",
             ));
         }
+    };
+
+    Ok(quote_spanned!(ty.span()=> #field_ident: #value,))
+}
+
+/// Generate an implementation of `quote::ToTokens` that writes the attribute
+/// back as its values (e.g. `key = "a", list(b, c)`), if requested through
+/// `#[squattr(to_tokens)]`.
+///
+fn expand_to_tokens(
+    ident: &Ident,
+    fields: punctuated::Iter<Field>,
+    options: &ContainerOptions,
+) -> Option<TokenStream> {
+    if !options.to_tokens {
+        return None;
     }
+
+    let entries = expand_entries(fields, |ident| quote!(self.#ident));
+
+    Some(quote! {
+        #[automatically_derived]
+        impl ::quote::ToTokens for #ident {
+            fn to_tokens(&self, tokens: &mut ::proc_macro2::TokenStream) {
+                ::std::iter::Extend::extend(tokens, #entries);
+            }
+        }
+    })
 }
 
-fn expand_named_struct(ident: Ident, fields: punctuated::Iter<Field>) -> Result<TokenStream> {
-    let mut variables = TokenStream::new();
-    let mut match_arms = TokenStream::new();
-    let mut required_checks = TokenStream::new();
-    let mut struct_fields = TokenStream::new();
-    let mut field_strs = TokenStream::new();
+/// Generate an expression writing the named `fields` as the entries of an
+/// attribute (e.g. `key = "a", list(b, c)`), of which `access` is the value of
+/// a field by its identifier.
+///
+fn expand_entries<A>(fields: punctuated::Iter<Field>, access: A) -> TokenStream
+where
+    A: Fn(&Ident) -> TokenStream,
+{
+    let entries = fields
+        .filter_map(|field| {
+            let options = parse_options::<FieldOptions>(&field.attrs).ok()?;
+            (!options.spans).then_some((field, options))
+        })
+        .map(|(field, options)| {
+            let ident = field.ident.as_ref().unwrap();
+            let ident_str = ident.unraw().to_string();
+            let value = access(ident);
+            let ty = &field.ty;
 
-    for field in fields {
-        let ident = field.ident.as_ref().unwrap();
-        let ident_str = ident.to_string();
-        let ty = &field.ty;
+            // A raw identifier (e.g. `r#type`) is written as such in the
+            // attribute too, as its keyword is not a valid key.
+            let key = if *ident != ident_str {
+                quote!(&::syn::Ident::new_raw(#ident_str, ::proc_macro2::Span::call_site()))
+            } else {
+                quote!(&::syn::Ident::new(#ident_str, ::proc_macro2::Span::call_site()))
+            };
 
-        field_strs.extend(quote! {
-            #ident_str,
+            if is_optional_field(ty, &options) {
+                quote_spanned! {
+                    ty.span()=>
+                    #value.as_ref().and_then(|value| {
+                        ::squattr::tokens::ToValueTokens::to_key_tokens(value, #key)
+                    })
+                }
+            } else {
+                quote_spanned! {
+                    ty.span()=>
+                    ::squattr::tokens::ToValueTokens::to_key_tokens(&#value, #key)
+                }
+            }
         });
 
-        match_arms.extend(quote_spanned! {
-            ty.span()=>
-            id_str if id_str == #ident_str => {
-                #ident.insert_value(id_str, value, &mut errors);
+    quote! {{
+        let entries: ::std::vec::Vec<::std::option::Option<::proc_macro2::TokenStream>> =
+            ::std::vec![#(#entries),*];
+
+        ::squattr::tokens::join_entries(::std::iter::Iterator::flatten(
+            ::std::iter::IntoIterator::into_iter(entries),
+        ))
+    }}
+}
+
+/// Generate the schema keys describing the named `fields`, i.e. a slice of
+/// `squattr::schema::Key` (fields collecting the spans are not keys).
+///
+fn expand_schema_keys(fields: punctuated::Iter<Field>) -> TokenStream {
+    let keys = fields
+        .filter_map(|field| {
+            let options = parse_options::<FieldOptions>(&field.attrs).ok()?;
+            (!options.spans).then_some((field, options))
+        })
+        .map(|(field, options)| {
+            let ident_str = field.ident.as_ref().unwrap().to_string();
+            let doc = doc_string(&field.attrs);
+            let ty = &field.ty;
+
+            let (value_ty, required, default) = match optional_inner(ty) {
+                _ if options.required => (quote!(#ty), true, None),
+                Some(inner) if is_optional(ty) => (quote!(#inner), false, None),
+                _ if options.optional => (
+                    quote!(<#ty as ::squattr::types::Optional>::Value),
+                    false,
+                    None,
+                ),
+                _ if is_boolean(ty) => (quote!(#ty), false, Some("false")),
+                _ if has_default(ty) => (quote!(#ty), false, None),
+                _ => (quote!(#ty), true, None),
+            };
+
+            let value_ty = match options.into.as_ref().or(options.try_from.as_ref()) {
+                Some(parsed_ty) => quote!(#parsed_ty),
+                None => value_ty,
+            };
+
+            let default = optional_expr(default);
+
+            let deprecated = match &options.deprecated {
+                Some(deprecated) => {
+                    let since = optional_expr(deprecated.since.as_deref());
+                    let note = optional_expr(deprecated.note.as_deref());
+
+                    quote! {
+                        ::std::option::Option::Some(::squattr::schema::Deprecation {
+                            since: #since,
+                            note: #note,
+                        })
+                    }
+                }
+                None => quote!(::std::option::Option::None),
+            };
+
+            quote_spanned! {
+                ty.span()=>
+                ::squattr::schema::Key {
+                    name: #ident_str,
+                    kind: <#value_ty as ::squattr::types::ParseValue>::KIND,
+                    required: #required,
+                    default: #default,
+                    doc: #doc,
+                    deprecated: #deprecated,
+                }
             }
         });
 
-        if is_optional(ty) {
-            variables.extend(quote! {
-                let mut #ident: #ty = ::std::option::Option::None;
+    quote!(&[#(#keys),*])
+}
+
+/// Generate the body of a `from_values` implementation that parses `values`
+/// into the named `fields` and constructs the result through `constructor`
+/// (e.g. `Self` or `Self::Variant`).
+///
+fn expand_named_fields(
+    ident: &Ident,
+    constructor: TokenStream,
+    fields: punctuated::Iter<Field>,
+    container: &ContainerOptions,
+    partial: bool,
+) -> Result<TokenStream> {
+    let mut slots = TokenStream::new();
+    let mut table_fields = TokenStream::new();
+    let mut struct_fields = TokenStream::new();
+
+    let mut errors = Accumulator::new();
+    let mut spans_field = None;
+
+    let fields = fields
+        .filter_map(|field| {
+            let options = errors.handle(parse_options::<FieldOptions>(&field.attrs))?;
+            Some((field, options))
+        })
+        .collect::<Vec<_>>();
+
+    for (field, options) in &fields {
+        if options.spans {
+            if spans_field.is_some() {
+                errors.push(Error::new(
+                    field.span(),
+                    "only one field can collect the spans",
+                ));
+            }
+            spans_field = field.ident.as_ref();
+        }
+
+        if options.required && options.optional {
+            errors.push(Error::new(
+                field.span(),
+                "a field cannot be both required and optional",
+            ));
+        }
+
+        if options.into.is_some() && options.try_from.is_some() {
+            errors.push(Error::new(
+                field.span(),
+                "a field is converted either through `into` or `try_from`",
+            ));
+        }
+
+        for key in options.conflicts_with.iter().chain(&options.requires) {
+            let known = fields.iter().any(|(field, _)| {
+                field
+                    .ident
+                    .as_ref()
+                    .is_some_and(|ident| ident == key.as_str())
+            });
+
+            if !known {
+                errors.push(Error::new(
+                    key.value_span(),
+                    format!("unknown key `{}`", **key),
+                ));
+            }
+        }
+    }
+
+    errors.finish()?;
+
+    let table_spans = match spans_field {
+        Some(spans) => {
+            slots.extend(quote! {
+                #spans: ::squattr::types::Spans,
             });
 
             struct_fields.extend(quote! {
-                #ident,
-            })
-        } else {
-            variables.extend(quote! {
-                let mut #ident: ::std::option::Option<#ty> = ::std::option::Option::None;
+                #spans: slots.#spans,
             });
 
-            if is_boolean(ty) {
-                struct_fields.extend(quote! {
-                    #ident: #ident.unwrap_or_default(),
-                });
-            } else {
-                let error_msg = format!("required key `{}` not found", ident);
-                required_checks.extend(quote! {
-                    if #ident.is_none() {
-                        errors.push(::syn::Error::new(span, #error_msg));
-                    };
-                });
+            quote!(::std::option::Option::Some(|slots| &mut slots.#spans))
+        }
+        None => quote!(::std::option::Option::None),
+    };
 
-                struct_fields.extend(quote! {
-                    #ident: #ident.expect("values existence has been confirmed"),
-                });
-            }
+    for (field, options) in &fields {
+        if options.spans {
+            continue;
         }
-    }
 
-    Ok(quote! {
-        #[automatically_derived]
-        impl ::squattr::attribute::Attribute for #ident {
-            fn from_values(values: ::squattr::ast::Values) -> ::syn::Result<Self> {
-                use ::squattr::{errors::ErrorsExt, types::ValueStorageExt};
+        let field_ident = field.ident.as_ref().unwrap();
+        let ident_str = field_ident.to_string();
+        let ty = &field.ty;
+        let optional = is_optional_field(ty, options);
+        let required = options.required || (!optional && !has_default(ty));
 
-                #variables
+        // Values are stored as the type they are parsed as, which is only
+        // converted into that of the field once all are parsed.
+        let (stored_ty, convert) = match (&options.into, &options.try_from) {
+            (Some(into), _) => (quote!(#into), Some(quote!(::std::convert::Into::into))),
+            (None, Some(try_from)) => {
+                let value_ty = match optional_inner(ty) {
+                    Some(inner) if is_optional(ty) => quote!(#inner),
+                    _ if optional => quote!(<#ty as ::squattr::types::Optional>::Value),
+                    _ => quote!(#ty),
+                };
 
-                let span = values.span();
-                let mut errors = ::std::vec::Vec::new();
+                (
+                    quote!(::squattr::types::TryFromValue<#try_from, #value_ty>),
+                    Some(quote!(::squattr::types::TryFromValue::into_inner)),
+                )
+            }
+            (None, None) => (quote!(#ty), None),
+        };
 
-                for value in values {
-                    let id = match value.identifier() {
-                        ::std::option::Option::Some(id) => id,
-                        ::std::option::Option::None => {
-                            errors.push(::syn::Error::new(
-                                value.span(),
-                                ::std::format!("expected an identifier"),
-                            ));
-                            continue;
-                        },
-                    };
+        let slot_ty = match convert {
+            Some(_) if optional => quote!(::std::option::Option<#stored_ty>),
+            _ => stored_ty,
+        };
+        let slot_ty = replace_self(slot_ty, ident);
 
-                    match id.as_str() {
-                        #match_arms
+        let appendable =
+            options.try_from.is_none() && is_appendable(options.into.as_ref().unwrap_or(ty));
 
-                        id_str => {
-                            let dym = match ::squattr::dym::did_you_mean(
-                                &[#field_strs],
-                                id_str,
-                            ) {
-                                Some(best_match) => format!(", did you mean `{}`?", best_match),
-                                None => "".into()
-                            };
-
-                            errors.push(::syn::Error::new(
-                                value.span(),
-                                ::std::format!("unrecognized key `{}`{}", id_str, dym),
-                            ));
-                        }
+        // A stored value that is an `Option` itself (i.e. that of a double
+        // option, or of a required option) is `None` for a bare key.
+        let nullable = convert.is_none()
+            && match optional {
+                true => is_optional(ty) && optional_inner(ty).is_some_and(is_optional),
+                false => is_optional(ty),
+            };
+
+        let checks = expand_checks(options);
+
+        let store = match (checks.is_empty(), appendable) {
+            _ if nullable => quote! {
+                ::squattr::types::OptionalStorageExt::insert_optional_value(
+                    &mut slots.#field_ident,
+                    id,
+                    ty,
+                    value,
+                    errors,
+                )
+            },
+            (_, true) => quote!(slots.#field_ident.append_typed_value(id, ty, value, errors)),
+            (true, false) => quote!(slots.#field_ident.insert_typed_value(id, ty, value, errors)),
+            (false, false) => quote! {
+                ::squattr::types::CheckedStorageExt::insert_checked_value(
+                    &mut slots.#field_ident,
+                    id,
+                    ty,
+                    value,
+                    errors,
+                    |value, written| {
+                        #(#checks)*
+                        ::std::result::Result::Ok(())
+                    },
+                )
+            },
+        };
+
+        // The values of a repeated key are checked once all are appended.
+        let check = match checks.is_empty() || !appendable {
+            true => quote!(::std::option::Option::None),
+            false => quote! {
+                ::std::option::Option::Some(|slots, written| match &slots.#field_ident {
+                    ::std::option::Option::Some(value) => {
+                        #(#checks)*
+                        ::std::result::Result::Ok(())
                     }
+                    ::std::option::Option::None => ::std::result::Result::Ok(()),
+                })
+            },
+        };
+
+        let ty_str = match optional_inner(ty) {
+            Some(inner) if is_optional(ty) => type_string(inner),
+            _ => type_string(ty),
+        };
+
+        let deprecated = match &options.deprecated {
+            Some(deprecated) => {
+                let mut message = format!("key `{}` is deprecated", ident_str);
+
+                if let Some(since) = &deprecated.since {
+                    message.push_str(&format!(" since {}", since));
+                }
+
+                if let Some(note) = &deprecated.note {
+                    message.push_str(&format!(", {}", note));
                 }
 
-                #required_checks
+                quote!(::std::option::Option::Some(#message))
+            }
+            None => quote!(::std::option::Option::None),
+        };
+
+        let conflicts_with = options.conflicts_with.iter().map(|key| key.as_str());
+        let requires = options.requires.iter().map(|key| key.as_str());
+
+        table_fields.extend(quote_spanned! {
+            ty.span()=>
+            ::squattr::runtime::Field {
+                name: #ident_str,
+                ty: #ty_str,
+                required: #required,
+                deprecated: #deprecated,
+                conflicts_with: &[#(#conflicts_with),*],
+                requires: &[#(#requires),*],
+                store: |slots, id, ty, value, errors| #store,
+                is_set: |slots| slots.#field_ident.is_some(),
+                check: #check,
+            },
+        });
+
+        if optional {
+            slots.extend(quote! {
+                #field_ident: #slot_ty,
+            });
+        } else {
+            slots.extend(quote! {
+                #field_ident: ::std::option::Option<#slot_ty>,
+            });
+        }
+
+        let value = if partial || optional {
+            quote!(slots.#field_ident)
+        } else if has_default(ty) {
+            quote!(slots.#field_ident.unwrap_or_default())
+        } else {
+            quote!(slots.#field_ident.expect("values existence has been confirmed"))
+        };
+
+        struct_fields.extend(match convert {
+            Some(convert) if partial || optional => quote!(#field_ident: #value.map(#convert),),
+            Some(convert) => quote!(#field_ident: #convert(#value),),
+            None => quote!(#field_ident: #value,),
+        });
+    }
+
+    let suggestions = expand_suggestions(container);
+
+    let parse = quote! {
+        TABLE.parse(
+            values,
+            &mut slots,
+            &<Self as ::squattr::attribute::Attribute>::SCHEMA,
+        )
+    };
+
+    // Keys omitted from the attribute are first filled from the defaults file.
+    let parse = match &container.defaults_file {
+        Some(file) => quote! {
+            ::squattr::defaults::fill(
+                values,
+                #file,
+                <Self as ::squattr::attribute::Attribute>::PATH,
+            )
+            .and_then(|values| #parse)
+        },
+        None => parse,
+    };
 
-                if let ::std::option::Option::Some(error) = errors.combine() {
-                    return Err(error);
+    // A partial result holds whatever was parsed, next to all errors.
+    let result = if partial {
+        quote! {
+            let errors = match errors {
+                ::std::result::Result::Ok(errors) => {
+                    <::std::vec::Vec<_> as ::std::convert::From<_>>::from(errors)
+                }
+                ::std::result::Result::Err(help) => {
+                    ::std::vec![<::squattr::errors::Error as ::std::convert::From<_>>::from(help)]
                 }
+            };
 
-                Ok(Self {
+            (
+                #constructor {
                     #struct_fields
-                })
-            }
+                },
+                errors,
+            )
+        }
+    } else {
+        quote! {
+            errors?.finish()?;
+
+            ::std::result::Result::Ok(#constructor {
+                #struct_fields
+            })
+        }
+    };
+
+    Ok(quote! {
+        use ::squattr::types::ValueStorageExt;
+
+        #[derive(::std::default::Default)]
+        struct Slots {
+            #slots
         }
+
+        const TABLE: ::squattr::runtime::Table<Slots> = ::squattr::runtime::Table {
+            fields: &[#table_fields],
+            spans: #table_spans,
+            suggestions: #suggestions,
+        };
+
+        let mut slots = <Slots as ::std::default::Default>::default();
+        let errors = #parse;
+
+        #result
     })
 }
 
+/// Replace every `Self` in `tokens` with `ident`, for types that are moved
+/// into an item of their own (where `Self` refers to that item instead).
+///
+fn replace_self(tokens: TokenStream, ident: &Ident) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Ident(token) if token == "Self" => {
+                TokenTree::Ident(Ident::new(&ident.to_string(), token.span()))
+            }
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    Group::new(group.delimiter(), replace_self(group.stream(), ident));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            token => token,
+        })
+        .collect()
+}
+
+/// Write `ty` the way it is declared, e.g. `Vec<u16>` rather than the
+/// `Vec < u16 >` of its tokens.
+///
+fn type_string(ty: &Type) -> String {
+    let tokens = quote!(#ty).to_string();
+    let chars = tokens.chars().collect::<Vec<_>>();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+
+    let mut string = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            // Only spaces separating two words (e.g. `dyn Trait`) are kept.
+            ' ' if i == 0
+                || !is_word(chars[i - 1])
+                || !chars.get(i + 1).is_some_and(|&next| is_word(next)) => {}
+            ',' => string.push_str(", "),
+            c => string.push(c),
+        }
+    }
+
+    string
+}
+
+/// Generate the checks of a parsed `value` against the constraints set on its
+/// field, each returning early with an error at the `written` value.
+///
+fn expand_checks(options: &FieldOptions) -> Vec<TokenStream> {
+    let mut checks = Vec::new();
+
+    if options.min.is_some() || options.max.is_some() {
+        let min = optional_expr(options.min.as_ref());
+        let max = optional_expr(options.max.as_ref());
+
+        checks.push(quote! {
+            ::squattr::types::check_range(value, #min, #max, written)?;
+        });
+    }
+
+    if options.min_items.is_some() || options.max_items.is_some() {
+        let min = optional_expr(options.min_items);
+        let max = optional_expr(options.max_items);
+
+        checks.push(quote! {
+            ::squattr::types::check_items(value, #min, #max, written)?;
+        });
+    }
+
+    if options.unique {
+        checks.push(quote! {
+            ::squattr::types::check_unique(value, written)?;
+        });
+    }
+
+    if options.non_empty {
+        checks.push(quote! {
+            ::squattr::types::check_non_empty(value, written)?;
+        });
+    }
+
+    checks
+}
+
+/// Write an optional value (e.g. a string or literal) as an expression of
+/// type `Option<T>`.
+///
+fn optional_expr<T: ToTokens>(value: Option<T>) -> TokenStream {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+/// Collect the doc comments among `attributes` (i.e. `#[doc = "..."]`) into a
+/// single string, without the leading space of every line.
+///
+fn doc_string(attributes: &[syn::Attribute]) -> String {
+    let lines = attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("doc"))
+        .filter_map(|attribute| match &attribute.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    syn::Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit_str),
+                        ..
+                    }),
+                ..
+            }) => Some(lit_str.value()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
 /// Determine wether a type is a `::std::option::Option` (i.e. may be omitted).
 ///
 /// See [matches_type_path] for more info.
@@ -170,6 +1737,77 @@ fn is_optional(ty: &Type) -> bool {
     )
 }
 
+/// Determine wether the type of a field is optional, either by its path or as
+/// set through `#[squattr(optional)]` (e.g. for an alias of `Option`), unless
+/// it is marked `#[squattr(required)]`.
+///
+#[inline]
+fn is_optional_field(ty: &Type, options: &FieldOptions) -> bool {
+    !options.required && (options.optional || is_optional(ty))
+}
+
+/// Determine wether a type is a `::std::vec::Vec` or a
+/// `::squattr::types::Repeated`, an optional one, or a
+/// `::squattr::types::Count` (i.e. repeated keys are appended to it).
+///
+/// See [matches_type_path] for more info.
+///
+#[inline]
+fn is_appendable(ty: &Type) -> bool {
+    let is_vec = |ty: &Type| {
+        matches_type_path(
+            ty,
+            &[
+                PathSegment {
+                    ident: Ident::new("std", Span::call_site()),
+                    arguments: PathArguments::None,
+                },
+                PathSegment {
+                    ident: Ident::new("vec", Span::call_site()),
+                    arguments: PathArguments::None,
+                },
+                PathSegment {
+                    ident: Ident::new("Vec", Span::call_site()),
+                    arguments: PathArguments::None,
+                },
+            ],
+        )
+    };
+
+    let is_list = |ty: &Type| is_vec(ty) || is_squattr_type(ty, "Repeated");
+
+    if is_optional(ty) {
+        optional_inner(ty).is_some_and(is_list)
+    } else {
+        is_list(ty) || is_squattr_type(ty, "Count")
+    }
+}
+
+/// Get the type wrapped by an optional type (e.g. `T` for `Option<T>`).
+///
+#[inline]
+fn optional_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(TypePath { path, .. }) => path.segments.last()?,
+        _ => return None,
+    };
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Determine wether fields hold exactly one unnamed field (e.g. `Variant(T)`).
+///
+#[inline]
+fn is_newtype(fields: &Fields) -> bool {
+    matches!(fields, Fields::Unnamed(FieldsUnnamed { unnamed, .. }) if unnamed.len() == 1)
+}
+
 /// Determine wether a type is a `::std::primitive::bool`.
 ///
 /// See [matches_type_path] for more info.
@@ -195,6 +1833,40 @@ fn is_boolean(ty: &Type) -> bool {
     )
 }
 
+/// Determine wether a field of a type falls back to its default when its key
+/// is omitted, rather than being required (i.e. `bool`,
+/// `::squattr::types::Flag` and `::squattr::types::Count`).
+///
+#[inline]
+fn has_default(ty: &Type) -> bool {
+    is_boolean(ty) || is_squattr_type(ty, "Flag") || is_squattr_type(ty, "Count")
+}
+
+/// Determine wether a type is the type `name` of `::squattr::types`.
+///
+/// See [matches_type_path] for more info.
+///
+#[inline]
+fn is_squattr_type(ty: &Type, name: &str) -> bool {
+    matches_type_path(
+        ty,
+        &[
+            PathSegment {
+                ident: Ident::new("squattr", Span::call_site()),
+                arguments: PathArguments::None,
+            },
+            PathSegment {
+                ident: Ident::new("types", Span::call_site()),
+                arguments: PathArguments::None,
+            },
+            PathSegment {
+                ident: Ident::new(name, Span::call_site()),
+                arguments: PathArguments::None,
+            },
+        ],
+    )
+}
+
 /// Check wether a type matches the `expected` path segments.
 ///
 /// From back to front, the given type needs to completely match at least part
@@ -203,6 +1875,9 @@ fn is_boolean(ty: &Type) -> bool {
 /// However, the type path cannot be renamed and the user must make sure that
 /// they use the default path and not some other path with the same name.
 ///
+/// A leading `std` segment also matches `core` and `alloc`, which re-export the
+/// same items (e.g. `core::option::Option`).
+///
 #[inline]
 fn matches_type_path(ty: &Type, expected: &[PathSegment]) -> bool {
     let ty_segments = match ty {
@@ -214,18 +1889,21 @@ fn matches_type_path(ty: &Type, expected: &[PathSegment]) -> bool {
         .iter()
         .rev()
         .zip(expected.iter().rev())
-        .all(|(expected_seg, ty_seg)| expected_seg.ident == ty_seg.ident)
+        .all(|(ty_seg, expected_seg)| {
+            ty_seg.ident == expected_seg.ident
+                || expected_seg.ident == "std"
+                    && (ty_seg.ident == "core" || ty_seg.ident == "alloc")
+        })
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Instant;
-
-    use colored::Colorize;
-    use proc_macro2::TokenStream;
     use quote::quote;
 
-    use super::expand;
+    use syn::{Type, parse_quote};
+
+    use super::{check_defaults_file, expand, type_string};
+    use crate::testing::expand_pretty;
 
     #[test]
     fn expand_named_struct() {
@@ -237,104 +1915,78 @@ mod tests {
             }
         };
 
-        let expect = quote! {
-            #[automatically_derived]
-            impl ::squattr::attribute::Attribute for FooAttribute {
-                fn from_values(values: ::squattr::ast::Values) -> ::syn::Result<Self> {
-                    use ::squattr::{errors::ErrorsExt, types::ValueStorageExt};
-
-                    let mut bar: ::std::option::Option<String> = ::std::option::Option::None;
-                    let mut baz: Option<bool> = ::std::option::Option::None;
-                    let mut ban: ::std::option::Option<bool> = ::std::option::Option::None;
-
-                    let span = values.span();
-                    let mut errors = ::std::vec::Vec::new();
-
-                    for value in values {
-                        let id = match value.identifier() {
-                            ::std::option::Option::Some(id) => id,
-                            ::std::option::Option::None => {
-                                errors.push(::syn::Error::new(
-                                    value.span(),
-                                    ::std::format!("expected an identifier"),
-                                ));
-                                continue;
-                            },
-                        };
-                        match id.as_str() {
-                            id_str if id_str == "bar" => {
-                                bar.insert_value(id_str, value, &mut errors);
-                            }
-                            id_str if id_str == "baz" => {
-                                baz.insert_value(id_str, value, &mut errors);
-                            }
-                            id_str if id_str == "ban" => {
-                                ban.insert_value(id_str, value, &mut errors);
-                            }
-                            id_str => {
-                                let dym = match ::squattr::dym::did_you_mean(
-                                    &["bar", "baz", "ban"],
-                                    id_str,
-                                ) {
-                                    Some(best_match) => format!(", did you mean `{}`?", best_match),
-                                    None => "".into(),
-                                };
-
-                                errors
-                                    .push(
-                                        ::syn::Error::new(
-                                            value.span(),
-                                        ::std::format!("unrecognized key `{}`{}", id_str, dym),
-                                        ),
-                                    );
-                            }
-                        }
-                    }
+        insta::assert_snapshot!(expand_pretty(expand, input));
+    }
 
-                    if bar.is_none() {
-                        errors.push(::syn::Error::new(span, "required key `bar` not found"));
-                    }
+    #[test]
+    fn expand_unit_enum() {
+        let input = quote! {
+            #[squattr(rename_all = "kebab-case")]
+            enum Mode {
+                Fast,
+                VerySlow,
+            }
+        };
 
-                    if let ::std::option::Option::Some(error) = errors.combine() {
-                        return Err(error);
-                    }
+        insta::assert_snapshot!(expand_pretty(expand, input));
+    }
 
-                    Ok(Self {
-                        bar: bar.expect("values existence has been confirmed"),
-                        baz,
-                        ban: ban.unwrap_or_default(),
-                    })
-                }
+    #[test]
+    fn expand_untagged_enum() {
+        let input = quote! {
+            enum IntOrString {
+                Int(u64),
+                Str(String),
             }
         };
 
-        let time_start = Instant::now();
-        let expanded = expand(input).unwrap();
-        let time_end = Instant::now();
-
-        assert_eq_token_streams(&expanded, &expect);
-        assess_expansion_duration(time_start, time_end, 500);
+        insta::assert_snapshot!(expand_pretty(expand, input));
     }
 
-    pub fn assert_eq_token_streams(a: &TokenStream, b: &TokenStream) {
-        let a_str = a.to_string();
-        let a_parsed = syn::parse_file(&a_str).unwrap();
-        let a_pretty = prettyplease::unparse(&a_parsed);
-
-        let b_str = b.to_string();
-        let b_parsed = syn::parse_file(&b_str).unwrap();
-        let b_pretty = prettyplease::unparse(&b_parsed);
+    #[test]
+    fn expand_input_struct() {
+        let input = quote! {
+            #[squattr(from_derive_input)]
+            struct MyInput {
+                ident: syn::Ident,
+                data: InputData<VariantOptions, FieldOptions>,
+                rename: Option<String>,
+            }
+        };
 
-        pretty_assertions::assert_eq!(a_pretty, b_pretty);
+        insta::assert_snapshot!(expand_pretty(expand, input));
     }
 
-    fn assess_expansion_duration(start: Instant, end: Instant, lt_us: u128) {
-        let duration = (end - start).as_micros();
-        let duration_str = format!("expansion duration: {}us", duration);
-        if duration >= lt_us {
-            println!("{}", duration_str.red(),);
-        } else {
-            println!("{}", duration_str.yellow(),);
+    #[test]
+    fn write_type_strings() {
+        let cases: [(Type, &str); 5] = [
+            (parse_quote!(Vec<u16>), "Vec<u16>"),
+            (parse_quote!((u8, bool)), "(u8, bool)"),
+            (parse_quote!(&'static str), "&'static str"),
+            (parse_quote!(::std::path::PathBuf), "::std::path::PathBuf"),
+            (parse_quote!(Box<dyn Fn(u8) -> u8>), "Box<dyn Fn(u8)->u8>"),
+        ];
+
+        for (ty, expect) in cases {
+            assert_eq!(type_string(&ty), expect);
         }
     }
+
+    #[test]
+    fn check_defaults_file_extension() {
+        assert_eq!(
+            check_defaults_file("squattr.yaml").unwrap_err().to_string(),
+            "expected a `.toml` or `.json` file for `defaults_file`"
+        );
+
+        assert_eq!(
+            check_defaults_file("squattr.toml").is_ok(),
+            cfg!(feature = "toml")
+        );
+
+        assert_eq!(
+            check_defaults_file("squattr.json").is_ok(),
+            cfg!(feature = "json")
+        );
+    }
 }
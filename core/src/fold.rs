@@ -0,0 +1,195 @@
+use syn::{Ident, Lit};
+
+use crate::ast::{Closure, Dotted, Expr, List, Neg, Not, Range, Tuple, Value, Values};
+
+/// Traversal of parsed values by value, producing rewritten values, e.g. to
+/// rename a key:
+///
+/// ```ignore
+/// struct Rename;
+///
+/// impl Fold for Rename {
+///     fn fold_expr(&mut self, mut expr: Expr) -> Expr {
+///         if expr.ident == "old" {
+///             expr.ident = Ident::new("new", expr.ident.span());
+///         }
+///         fold_expr(self, expr)
+///     }
+/// }
+/// ```
+///
+/// Nodes that are not overridden are rebuilt from their folded children, see
+/// the free functions (e.g. [fold_expr]) for the default behavior.
+///
+pub trait Fold {
+    fn fold_values(&mut self, node: Values) -> Values {
+        fold_values(self, node)
+    }
+
+    fn fold_value(&mut self, node: Value) -> Value {
+        fold_value(self, node)
+    }
+
+    fn fold_dotted(&mut self, node: Dotted) -> Dotted {
+        fold_dotted(self, node)
+    }
+
+    fn fold_expr(&mut self, node: Expr) -> Expr {
+        fold_expr(self, node)
+    }
+
+    fn fold_list(&mut self, node: List) -> List {
+        fold_list(self, node)
+    }
+
+    fn fold_tuple(&mut self, node: Tuple) -> Tuple {
+        fold_tuple(self, node)
+    }
+
+    fn fold_not(&mut self, node: Not) -> Not {
+        fold_not(self, node)
+    }
+
+    fn fold_neg(&mut self, node: Neg) -> Neg {
+        fold_neg(self, node)
+    }
+
+    fn fold_range(&mut self, node: Range) -> Range {
+        fold_range(self, node)
+    }
+
+    fn fold_ident(&mut self, node: Ident) -> Ident {
+        node
+    }
+
+    fn fold_lit(&mut self, node: Lit) -> Lit {
+        node
+    }
+
+    fn fold_closure(&mut self, node: Closure) -> Closure {
+        node
+    }
+}
+
+pub fn fold_values<F: Fold + ?Sized>(f: &mut F, node: Values) -> Values {
+    let mut values = Values::new(node.span());
+
+    for value in node {
+        values.push(f.fold_value(value));
+    }
+
+    values
+}
+
+pub fn fold_value<F: Fold + ?Sized>(f: &mut F, node: Value) -> Value {
+    match node {
+        Value::Closure(closure) => Value::Closure(f.fold_closure(closure)),
+        Value::Dotted(dotted) => Value::Dotted(f.fold_dotted(dotted)),
+        Value::Expr(expr) => Value::Expr(f.fold_expr(expr)),
+        Value::Ident(ident) => Value::Ident(f.fold_ident(ident)),
+        Value::List(list) => Value::List(f.fold_list(list)),
+        Value::Lit(lit) => Value::Lit(f.fold_lit(lit)),
+        Value::Neg(neg) => Value::Neg(f.fold_neg(neg)),
+        Value::Not(not) => Value::Not(f.fold_not(not)),
+        Value::Range(range) => Value::Range(f.fold_range(range)),
+        Value::Tuple(tuple) => Value::Tuple(f.fold_tuple(tuple)),
+    }
+}
+
+pub fn fold_dotted<F: Fold + ?Sized>(f: &mut F, node: Dotted) -> Dotted {
+    Dotted {
+        ident: f.fold_ident(node.ident),
+        dot_token: node.dot_token,
+        value: Box::new(f.fold_value(*node.value)),
+    }
+}
+
+pub fn fold_expr<F: Fold + ?Sized>(f: &mut F, node: Expr) -> Expr {
+    Expr {
+        ident: f.fold_ident(node.ident),
+        eq_token: node.eq_token,
+        value: Box::new(f.fold_value(*node.value)),
+        end_span: node.end_span,
+    }
+}
+
+pub fn fold_list<F: Fold + ?Sized>(f: &mut F, node: List) -> List {
+    List {
+        ident: f.fold_ident(node.ident),
+        paren_token: node.paren_token,
+        values: f.fold_values(node.values),
+    }
+}
+
+pub fn fold_tuple<F: Fold + ?Sized>(f: &mut F, node: Tuple) -> Tuple {
+    Tuple {
+        paren_token: node.paren_token,
+        values: f.fold_values(node.values),
+    }
+}
+
+pub fn fold_not<F: Fold + ?Sized>(f: &mut F, node: Not) -> Not {
+    Not {
+        not_token: node.not_token,
+        ident: f.fold_ident(node.ident),
+    }
+}
+
+pub fn fold_neg<F: Fold + ?Sized>(f: &mut F, node: Neg) -> Neg {
+    Neg {
+        minus_token: node.minus_token,
+        ident: f.fold_ident(node.ident),
+    }
+}
+
+pub fn fold_range<F: Fold + ?Sized>(f: &mut F, node: Range) -> Range {
+    Range {
+        start: f.fold_lit(node.start),
+        limits: node.limits,
+        end: f.fold_lit(node.end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+    use quote::quote;
+    use syn::{Ident, Lit, LitStr, parse2};
+
+    use super::{Fold, fold_expr};
+    use crate::ast::{Expr, Values};
+
+    #[test]
+    fn rewrite_values() {
+        struct Rewrite;
+
+        impl Fold for Rewrite {
+            fn fold_expr(&mut self, mut expr: Expr) -> Expr {
+                if expr.ident == "path" {
+                    expr.ident = Ident::new("route", expr.ident.span());
+                }
+                fold_expr(self, expr)
+            }
+
+            fn fold_lit(&mut self, lit: Lit) -> Lit {
+                match lit {
+                    Lit::Str(lit_str) => Lit::Str(LitStr::new(
+                        &format!("/api{}", lit_str.value()),
+                        Span::call_site(),
+                    )),
+                    lit => lit,
+                }
+            }
+        }
+
+        let values = parse2::<Values>(quote! {
+            path = "/a", nested(path = "/b", other = 1), flag
+        })
+        .unwrap();
+
+        assert_eq!(
+            Rewrite.fold_values(values).to_string(),
+            r#"route = "/api/a", nested(route = "/api/b", other = 1), flag"#
+        );
+    }
+}
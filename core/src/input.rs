@@ -0,0 +1,162 @@
+use syn::{Data, DataEnum, DataStruct, DeriveInput, Error, Generics, Ident, Result, Type};
+
+use crate::{
+    attribute::{Attribute, AttributePath},
+    errors::Accumulator,
+};
+
+/// The attributes of a whole derive input: the options of the container and
+/// those of each of its fields (for structs) or variants (for enums), all
+/// parsed from attributes with the same path.
+///
+/// This saves derive macro authors from walking the fields and variants of an
+/// item themselves, e.g. to parse `#[my_attr(...)]` on a struct and its fields:
+///
+/// ```ignore
+/// let input: Input<MyContainer, MyVariant, MyField> =
+///     Input::from_derive_input(&derive_input, "my_attr")?;
+/// ```
+///
+/// The options of items without a matching attribute are parsed from an empty
+/// list of values, so option types with only optional keys get their defaults.
+///
+/// Alternatively, `#[squattr(from_derive_input)]` derives [FromDeriveInput]
+/// for a struct of your own, of which the fields named `ident`, `vis`,
+/// `generics`, `attrs` and `data` (e.g. an [InputData]) are taken from the
+/// input, and all others are the keys of the container's attribute:
+///
+/// ```ignore
+/// #[derive(Squattr)]
+/// #[squattr(from_derive_input)]
+/// struct MyInput {
+///     ident: syn::Ident,
+///     data: InputData<MyVariant, MyField>,
+///     rename: Option<String>,
+/// }
+///
+/// let input = MyInput::from_derive_input(&derive_input, "my_attr")?;
+/// ```
+///
+pub struct Input<C, V, F> {
+    pub ident: Ident,
+    pub generics: Generics,
+    pub options: C,
+    pub data: InputData<V, F>,
+}
+
+pub enum InputData<V, F> {
+    Struct(Vec<InputField<F>>),
+    Enum(Vec<InputVariant<V, F>>),
+}
+
+pub struct InputVariant<V, F> {
+    pub ident: Ident,
+    pub fields: Vec<InputField<F>>,
+    pub options: V,
+}
+
+pub struct InputField<F> {
+    /// The identifier of the field, or `None` for tuple fields.
+    pub ident: Option<Ident>,
+    pub ty: Type,
+    pub options: F,
+}
+
+pub trait FromDeriveInput: Sized {
+    fn from_derive_input<P>(input: &DeriveInput, path: &P) -> Result<Self>
+    where
+        P: AttributePath + ?Sized;
+}
+
+impl<C, V, F> FromDeriveInput for Input<C, V, F>
+where
+    C: Attribute,
+    V: Attribute,
+    F: Attribute,
+{
+    fn from_derive_input<P>(input: &DeriveInput, path: &P) -> Result<Self>
+    where
+        P: AttributePath + ?Sized,
+    {
+        let mut errors = Accumulator::new();
+
+        let options = errors.handle(C::from_attributes(&input.attrs, path));
+        let data = errors.handle(InputData::from_derive_input(input, path));
+
+        errors.finish()?;
+
+        Ok(Self {
+            ident: input.ident.clone(),
+            generics: input.generics.clone(),
+            options: options.expect("options have been parsed"),
+            data: data.expect("data has been parsed"),
+        })
+    }
+}
+
+/// Parses the options of the fields (for structs) or variants (for enums) of
+/// the input only.
+///
+impl<V, F> FromDeriveInput for InputData<V, F>
+where
+    V: Attribute,
+    F: Attribute,
+{
+    fn from_derive_input<P>(input: &DeriveInput, path: &P) -> Result<Self>
+    where
+        P: AttributePath + ?Sized,
+    {
+        let mut errors = Accumulator::new();
+
+        let data = match &input.data {
+            Data::Struct(DataStruct { fields, .. }) => {
+                InputData::Struct(parse_fields(fields, path, &mut errors))
+            }
+            Data::Enum(DataEnum { variants, .. }) => InputData::Enum(
+                variants
+                    .iter()
+                    .filter_map(|variant| {
+                        let fields = parse_fields(&variant.fields, path, &mut errors);
+                        let options = errors.handle(V::from_attributes(&variant.attrs, path))?;
+
+                        Some(InputVariant {
+                            ident: variant.ident.clone(),
+                            fields,
+                            options,
+                        })
+                    })
+                    .collect(),
+            ),
+            Data::Union(_) => {
+                return Err(Error::new(input.ident.span(), "unions are not supported"));
+            }
+        };
+
+        errors.finish()?;
+
+        Ok(data)
+    }
+}
+
+fn parse_fields<F, P>(
+    fields: &syn::Fields,
+    path: &P,
+    errors: &mut Accumulator,
+) -> Vec<InputField<F>>
+where
+    F: Attribute,
+    P: AttributePath + ?Sized,
+{
+    fields
+        .iter()
+        .filter_map(|field| {
+            let options = errors.handle(F::from_attributes(&field.attrs, path))?;
+
+            Some(InputField {
+                ident: field.ident.clone(),
+                ty: field.ty.clone(),
+                options,
+            })
+        })
+        .collect()
+}
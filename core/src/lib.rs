@@ -1,6 +1,30 @@
+#![cfg_attr(squattr_nightly, feature(proc_macro_diagnostic))]
+
+extern crate proc_macro;
+
 pub mod ast;
 pub mod attribute;
+#[cfg(feature = "darling")]
+pub mod darling;
+pub mod defaults;
+pub mod diagnostic;
 pub mod dym;
 pub mod errors;
 pub mod expand;
+pub mod fold;
+pub mod input;
+mod options;
+pub mod runtime;
+pub mod schema;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tokens;
 pub mod types;
+pub mod visit;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use quote::quote;
+}
@@ -0,0 +1,624 @@
+use syn::{Error, Lit, Result, Type};
+
+use crate::{
+    ast::{Value, Values},
+    attribute::Attribute,
+    dym::{Scorer, Suggestions, did_you_mean},
+    errors::Accumulator,
+    types::{SpannedValue, ValueStorageExt, format_error},
+};
+
+/// The path of the derive's own helper attribute (i.e. `#[squattr(...)]`).
+///
+pub const OPTIONS_PATH: &str = "squattr";
+
+/// Options set on the deriving item through `#[squattr(...)]` attributes.
+///
+#[derive(Default)]
+pub struct ContainerOptions {
+    /// The rule used to convert enum variant names into values.
+    pub rename_all: Option<RenameRule>,
+    /// The struct is a set of flags, given as a list of identifiers.
+    pub flags: bool,
+    /// The path of the attribute (i.e. `Attribute::PATH`).
+    pub path: Option<String>,
+    /// Also implement `syn::parse::Parse` for the attribute.
+    pub parse: bool,
+    /// Also implement `quote::ToTokens` for the attribute.
+    pub to_tokens: bool,
+    /// How keys and variants similar to a mistyped one are suggested.
+    pub suggest: Option<Suggestions>,
+    /// Also generate a partial parse that keeps going past errors.
+    pub partial: bool,
+    /// The file (e.g. `squattr.toml`) that keys omitted from the attribute are
+    /// taken from, see [defaults::fill](crate::defaults::fill).
+    pub defaults_file: Option<String>,
+    /// Also generate `merge_over`, which layers the attribute over another,
+    /// and `merge`, which combines two attributes key by key.
+    pub merge: bool,
+    /// Implement `FromDeriveInput` rather than `Attribute`, taking the fields
+    /// named after parts of the input from it, see [Input](crate::input::Input).
+    pub from_derive_input: bool,
+}
+
+impl ContainerOptions {
+    const KEYS: &[&str] = &[
+        "rename_all",
+        "flags",
+        "path",
+        "parse",
+        "to_tokens",
+        "suggest",
+        "partial",
+        "merge",
+        "defaults_file",
+        "from_derive_input",
+    ];
+}
+
+impl Attribute for ContainerOptions {
+    fn from_values(values: Values) -> Result<Self> {
+        let mut errors = Accumulator::new();
+
+        let mut rename_all: Option<SpannedValue<String>> = None;
+        let mut flags: Option<bool> = None;
+        let mut path: Option<SpannedValue<String>> = None;
+        let mut parse: Option<bool> = None;
+        let mut to_tokens: Option<bool> = None;
+        let mut suggest: Option<Suggestions> = None;
+        let mut partial: Option<bool> = None;
+        let mut merge: Option<bool> = None;
+        let mut defaults_file: Option<String> = None;
+        let mut from_derive_input: Option<bool> = None;
+
+        for value in values {
+            let id = match identifier(&value, &mut errors) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            match id.as_str() {
+                "rename_all" => rename_all.insert_value(&id, value, &mut errors),
+                "flags" => flags.insert_value(&id, value, &mut errors),
+                "path" => path.insert_value(&id, value, &mut errors),
+                "parse" => parse.insert_value(&id, value, &mut errors),
+                "to_tokens" => to_tokens.insert_value(&id, value, &mut errors),
+                "suggest" => suggest.insert_value(&id, value, &mut errors),
+                "partial" => partial.insert_value(&id, value, &mut errors),
+                "merge" => merge.insert_value(&id, value, &mut errors),
+                "defaults_file" => defaults_file.insert_value(&id, value, &mut errors),
+                "from_derive_input" => from_derive_input.insert_value(&id, value, &mut errors),
+                id_str => errors.push(unrecognized_key(&value, id_str, Self::KEYS)),
+            }
+        }
+
+        let rename_all = rename_all.and_then(|rule| match RenameRule::parse(&rule) {
+            Ok(rule) => Some(rule),
+            Err(error) => {
+                errors.push(Error::new(rule.value_span(), error));
+                None
+            }
+        });
+
+        let path = path.and_then(|path| match syn::parse_str::<syn::Path>(&path) {
+            Ok(_) => Some(path.into_inner()),
+            Err(_) => {
+                errors.push(Error::new(
+                    path.value_span(),
+                    format!("invalid attribute path `{}`", *path),
+                ));
+                None
+            }
+        });
+
+        errors.finish()?;
+
+        Ok(Self {
+            rename_all,
+            flags: flags.unwrap_or_default(),
+            path,
+            parse: parse.unwrap_or_default(),
+            to_tokens: to_tokens.unwrap_or_default(),
+            suggest,
+            partial: partial.unwrap_or_default(),
+            defaults_file,
+            merge: merge.unwrap_or_default(),
+            from_derive_input: from_derive_input.unwrap_or_default(),
+        })
+    }
+}
+
+/// Parsed from `suggest(threshold = 0.6, max = 2, scorer = "levenshtein")`,
+/// where every key defaults to that of [Suggestions::DEFAULT].
+///
+impl Attribute for Suggestions {
+    fn from_values(values: Values) -> Result<Self> {
+        const KEYS: &[&str] = &["threshold", "max", "scorer"];
+        const SCORERS: &[&str] = &["jaro_winkler", "levenshtein"];
+
+        let mut errors = Accumulator::new();
+
+        let mut threshold: Option<SpannedValue<f64>> = None;
+        let mut max: Option<SpannedValue<usize>> = None;
+        let mut scorer: Option<SpannedValue<String>> = None;
+
+        for value in values {
+            let id = match identifier(&value, &mut errors) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            match id.as_str() {
+                "threshold" => threshold.insert_value(&id, value, &mut errors),
+                "max" => max.insert_value(&id, value, &mut errors),
+                "scorer" => scorer.insert_value(&id, value, &mut errors),
+                id_str => errors.push(unrecognized_key(&value, id_str, KEYS)),
+            }
+        }
+
+        let mut suggestions = Self::DEFAULT;
+
+        if let Some(threshold) = threshold {
+            if (0.0..=1.0).contains(&*threshold) {
+                suggestions.threshold = *threshold;
+            } else {
+                errors.push(Error::new(
+                    threshold.value_span(),
+                    "the threshold must be between 0 and 1",
+                ));
+            }
+        }
+
+        if let Some(max) = max {
+            if *max > 0 {
+                suggestions.max = *max;
+            } else {
+                errors.push(Error::new(
+                    max.value_span(),
+                    "at least one suggestion must be allowed",
+                ));
+            }
+        }
+
+        if let Some(scorer) = scorer {
+            match scorer.as_str() {
+                "jaro_winkler" => suggestions.scorer = Scorer::JaroWinkler,
+                "levenshtein" => suggestions.scorer = Scorer::Levenshtein,
+                name => errors.push(Error::new(
+                    scorer.value_span(),
+                    match did_you_mean(SCORERS, name) {
+                        Some(best_match) => format!(
+                            "unrecognized scorer `{}`, did you mean `{}`?",
+                            name, best_match
+                        ),
+                        None => format!("unrecognized scorer `{}`", name),
+                    },
+                )),
+            }
+        }
+
+        Ok(errors.finish_with(suggestions)?)
+    }
+}
+
+/// A case convention that `PascalCase` enum variant names are converted into.
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    const NAMES: &[&str] = &[
+        "lowercase",
+        "UPPERCASE",
+        "PascalCase",
+        "camelCase",
+        "snake_case",
+        "SCREAMING_SNAKE_CASE",
+        "kebab-case",
+        "SCREAMING-KEBAB-CASE",
+    ];
+
+    fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name {
+            "lowercase" => Ok(Self::Lower),
+            "UPPERCASE" => Ok(Self::Upper),
+            "PascalCase" => Ok(Self::Pascal),
+            "camelCase" => Ok(Self::Camel),
+            "snake_case" => Ok(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            "kebab-case" => Ok(Self::Kebab),
+            "SCREAMING-KEBAB-CASE" => Ok(Self::ScreamingKebab),
+            name => Err(match did_you_mean(Self::NAMES, name) {
+                Some(best_match) => format!(
+                    "unrecognized case convention `{}`, did you mean `{}`?",
+                    name, best_match
+                ),
+                None => format!("unrecognized case convention `{}`", name),
+            }),
+        }
+    }
+
+    /// Convert a `PascalCase` name according to this rule.
+    ///
+    pub fn apply(&self, name: &str) -> String {
+        let words = split_words(name);
+
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        };
+
+        match self {
+            Self::Lower => words.concat().to_lowercase(),
+            Self::Upper => words.concat().to_uppercase(),
+            Self::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            Self::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| match i {
+                    0 => word.to_lowercase(),
+                    _ => capitalize(word),
+                })
+                .collect(),
+            Self::Snake => words.join("_").to_lowercase(),
+            Self::ScreamingSnake => words.join("_").to_uppercase(),
+            Self::Kebab => words.join("-").to_lowercase(),
+            Self::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+}
+
+/// Split a `PascalCase` (or `snake_case`) name into its words, keeping
+/// acronyms together (e.g. `HTTPServer` becomes `HTTP` and `Server`).
+///
+fn split_words(name: &str) -> Vec<&str> {
+    let chars = name.char_indices().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    for (i, &(index, c)) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            if start < index {
+                words.push(&name[start..index]);
+            }
+            start = index + c.len_utf8();
+            continue;
+        }
+
+        if i == 0 || start == index || !c.is_uppercase() {
+            continue;
+        }
+
+        let prev = chars[i - 1].1;
+        let next = chars.get(i + 1).map(|&(_, next)| next);
+
+        if prev.is_lowercase()
+            || prev.is_ascii_digit()
+            || (prev.is_uppercase() && next.is_some_and(char::is_lowercase))
+        {
+            words.push(&name[start..index]);
+            start = index;
+        }
+    }
+
+    if start < name.len() {
+        words.push(&name[start..]);
+    }
+
+    words
+}
+
+/// Options set on a field through `#[squattr(...)]` attributes.
+///
+#[derive(Default)]
+pub struct FieldOptions {
+    /// The field collects the spans of all keys that were set.
+    pub spans: bool,
+    /// Setting the key emits a warning, given as `deprecated = "note"` or as
+    /// `deprecated_since = "0.4"` with an optional `note = "..."`.
+    pub deprecated: Option<Deprecated>,
+    /// The type is an `Option` under another name (e.g. a type alias), so the
+    /// key may be omitted.
+    pub optional: bool,
+    /// The key must be given even though the type is an `Option`, which is
+    /// then set to `None` by a bare key (e.g. `key` rather than `key = 1`).
+    pub required: bool,
+    /// The type the value is parsed as, before it is converted into the type
+    /// of the field through `Into` (e.g. `String` for an `Arc<str>`).
+    pub into: Option<Type>,
+    /// The type the value is parsed as, before it is converted into the type
+    /// of the field through `TryFrom` (e.g. `u16` for a `Port`).
+    pub try_from: Option<Type>,
+    /// The smallest number accepted as the value of the key (inclusive).
+    pub min: Option<Lit>,
+    /// The largest number accepted as the value of the key (inclusive).
+    pub max: Option<Lit>,
+    /// The fewest values accepted in the list of the key.
+    pub min_items: Option<usize>,
+    /// The most values accepted in the list of the key.
+    pub max_items: Option<usize>,
+    /// The list of the key may not hold the same value twice.
+    pub unique: bool,
+    /// The string of the key may not be empty.
+    pub non_empty: bool,
+    /// The keys that may not be set along with that of the field, e.g.
+    /// `conflicts_with("path")`.
+    pub conflicts_with: Vec<SpannedValue<String>>,
+    /// The keys that must be set along with that of the field.
+    pub requires: Vec<SpannedValue<String>>,
+    /// How `merge` combines the values of the key, e.g. `merge = "append"`.
+    pub merge: Option<SpannedValue<MergeStrategy>>,
+}
+
+impl FieldOptions {
+    const KEYS: &[&str] = &[
+        "spans",
+        "deprecated",
+        "deprecated_since",
+        "note",
+        "optional",
+        "required",
+        "into",
+        "try_from",
+        "min",
+        "max",
+        "min_items",
+        "max_items",
+        "unique",
+        "non_empty",
+        "conflicts_with",
+        "requires",
+        "merge",
+    ];
+}
+
+impl Attribute for FieldOptions {
+    fn from_values(values: Values) -> Result<Self> {
+        let mut errors = Accumulator::new();
+
+        let mut spans: Option<bool> = None;
+        let mut deprecated: Option<String> = None;
+        let mut deprecated_since: Option<String> = None;
+        let mut note: Option<SpannedValue<String>> = None;
+        let mut optional: Option<bool> = None;
+        let mut required: Option<bool> = None;
+        let mut into: Option<Type> = None;
+        let mut try_from: Option<Type> = None;
+        let mut min: Option<Lit> = None;
+        let mut max: Option<Lit> = None;
+        let mut min_items: Option<usize> = None;
+        let mut max_items: Option<usize> = None;
+        let mut unique: Option<bool> = None;
+        let mut non_empty: Option<bool> = None;
+        let mut conflicts_with: Option<Vec<SpannedValue<String>>> = None;
+        let mut requires: Option<Vec<SpannedValue<String>>> = None;
+        let mut merge: Option<SpannedValue<String>> = None;
+
+        for value in values {
+            let id = match identifier(&value, &mut errors) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            match id.as_str() {
+                "spans" => spans.insert_value(&id, value, &mut errors),
+                "deprecated" => deprecated.insert_value(&id, value, &mut errors),
+                "deprecated_since" => deprecated_since.insert_value(&id, value, &mut errors),
+                "note" => note.insert_value(&id, value, &mut errors),
+                "optional" => optional.insert_value(&id, value, &mut errors),
+                "required" => required.insert_value(&id, value, &mut errors),
+                "into" => into.insert_value(&id, value, &mut errors),
+                "try_from" => try_from.insert_value(&id, value, &mut errors),
+                "min" => min.insert_value(&id, value, &mut errors),
+                "max" => max.insert_value(&id, value, &mut errors),
+                "min_items" => min_items.insert_value(&id, value, &mut errors),
+                "max_items" => max_items.insert_value(&id, value, &mut errors),
+                "unique" => unique.insert_value(&id, value, &mut errors),
+                "non_empty" => non_empty.insert_value(&id, value, &mut errors),
+                "conflicts_with" => conflicts_with.append_value(&id, value, &mut errors),
+                "requires" => requires.append_value(&id, value, &mut errors),
+                "merge" => merge.insert_value(&id, value, &mut errors),
+                id_str => errors.push(unrecognized_key(&value, id_str, Self::KEYS)),
+            }
+        }
+
+        let deprecated = match (deprecated, deprecated_since, note) {
+            (None, None, None) => None,
+            (_, None, Some(note)) => {
+                errors.push(Error::new(
+                    note.key_span(),
+                    "a `note` is only given along with `deprecated_since`",
+                ));
+                None
+            }
+            (Some(_), Some(_), Some(note)) => {
+                errors.push(Error::new(
+                    note.key_span(),
+                    "the note is already given by `deprecated`",
+                ));
+                None
+            }
+            (deprecated, since, note) => Some(Deprecated {
+                since,
+                note: deprecated.or(note.map(SpannedValue::into_inner)),
+            }),
+        };
+
+        let min = min.and_then(|min| number(min, &mut errors));
+        let max = max.and_then(|max| number(max, &mut errors));
+
+        let merge = merge.and_then(|name| match MergeStrategy::parse(&name) {
+            Ok(strategy) => Some(SpannedValue::new(
+                strategy,
+                name.key_span(),
+                name.value_span(),
+            )),
+            Err(error) => {
+                errors.push(Error::new(name.value_span(), error));
+                None
+            }
+        });
+
+        errors.finish()?;
+
+        Ok(Self {
+            spans: spans.unwrap_or_default(),
+            deprecated,
+            optional: optional.unwrap_or_default(),
+            required: required.unwrap_or_default(),
+            into,
+            try_from,
+            min,
+            max,
+            min_items,
+            max_items,
+            unique: unique.unwrap_or_default(),
+            non_empty: non_empty.unwrap_or_default(),
+            conflicts_with: conflicts_with.unwrap_or_default(),
+            requires: requires.unwrap_or_default(),
+            merge,
+        })
+    }
+}
+
+/// How the key of a field is deprecated.
+///
+pub struct Deprecated {
+    /// The version since which the key is deprecated (e.g. "0.4").
+    pub since: Option<String>,
+    /// What to do instead (e.g. "use `x` instead").
+    pub note: Option<String>,
+}
+
+/// How `merge` combines the values of a key set on both attributes.
+///
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum MergeStrategy {
+    /// Keep the value of the first attribute.
+    First,
+    /// Take the value of the last attribute.
+    #[default]
+    Last,
+    /// Extend the value of the first attribute with that of the last.
+    Append,
+    /// Fail with a duplicate key error.
+    Error,
+}
+
+impl MergeStrategy {
+    const NAMES: &[&str] = &["first", "last", "append", "error"];
+
+    fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name {
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            "append" => Ok(Self::Append),
+            "error" => Ok(Self::Error),
+            name => Err(match did_you_mean(Self::NAMES, name) {
+                Some(best_match) => format!(
+                    "unrecognized merge strategy `{}`, did you mean `{}`?",
+                    name, best_match
+                ),
+                None => format!("unrecognized merge strategy `{}`", name),
+            }),
+        }
+    }
+}
+
+/// Parse the options of all `#[squattr(...)]` attributes in `attributes`.
+///
+pub fn parse_options<T: Attribute>(attributes: &[syn::Attribute]) -> Result<T> {
+    T::from_attributes(attributes, OPTIONS_PATH)
+}
+
+/// Accept only a numeric literal as a bound.
+///
+#[inline]
+fn number(lit: Lit, errors: &mut Accumulator) -> Option<Lit> {
+    match lit {
+        lit @ (Lit::Int(_) | Lit::Float(_)) => Some(lit),
+        lit => {
+            errors.push(Error::new(lit.span(), "expected a number"));
+            None
+        }
+    }
+}
+
+#[inline]
+fn identifier(value: &Value, errors: &mut Accumulator) -> Option<String> {
+    let id = value.identifier();
+
+    if id.is_none() {
+        errors.push(format_error(value, "identifier"));
+    }
+
+    id
+}
+
+#[inline]
+fn unrecognized_key(value: &Value, id: &str, keys: &[&'static str]) -> Error {
+    let dym = match did_you_mean(keys, id) {
+        Some(best_match) => format!(", did you mean `{}`?", best_match),
+        None => "".into(),
+    };
+
+    Error::new(value.span(), format!("unrecognized option `{}`{}", id, dym))
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{DeriveInput, parse_quote};
+
+    use super::{FieldOptions, RenameRule, parse_options};
+
+    #[test]
+    fn apply_rename_rules() {
+        let cases = [
+            (RenameRule::Lower, "httpserver"),
+            (RenameRule::Upper, "HTTPSERVER"),
+            (RenameRule::Pascal, "HttpServer"),
+            (RenameRule::Camel, "httpServer"),
+            (RenameRule::Snake, "http_server"),
+            (RenameRule::ScreamingSnake, "HTTP_SERVER"),
+            (RenameRule::Kebab, "http-server"),
+            (RenameRule::ScreamingKebab, "HTTP-SERVER"),
+        ];
+
+        for (rule, expect) in cases {
+            assert_eq!(rule.apply("HTTPServer"), expect);
+        }
+
+        assert_eq!(RenameRule::Snake.apply("VerySlow2Go"), "very_slow2_go");
+    }
+
+    #[test]
+    fn parse_options_with_trailing_commas() {
+        let item: DeriveInput = parse_quote! {
+            #[squattr(spans,)]
+            #[squattr()]
+            #[squattr(unique, max_items = 2,)]
+            struct Foo;
+        };
+
+        let options = parse_options::<FieldOptions>(&item.attrs).unwrap();
+
+        assert!(options.spans);
+        assert!(options.unique);
+        assert_eq!(options.max_items, Some(2));
+    }
+}
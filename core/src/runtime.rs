@@ -0,0 +1,585 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Write},
+};
+
+use proc_macro2::Span;
+use syn::{Ident, Result, token::Paren};
+
+use crate::{
+    ast::{Expr, List, Tuple, Value, Values},
+    diagnostic,
+    dym::Suggestions,
+    errors::{self, Accumulator},
+    schema::{self, Schema},
+    types::{Spans, format_error},
+};
+
+thread_local! {
+    static OPTIONS: Cell<ParseOptions> = const { Cell::new(ParseOptions::STRICT) };
+}
+
+/// How derived attributes are parsed at runtime, e.g. leniently while an IDE
+/// expands the macro and strictly otherwise. See
+/// [Attribute::from_values_with](crate::attribute::Attribute::from_values_with).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// What to do with a key the attribute does not accept.
+    pub on_unknown: OnUnknown,
+}
+
+impl ParseOptions {
+    /// Every problem is an error, as when parsing without options.
+    pub const STRICT: Self = Self {
+        on_unknown: OnUnknown::Error,
+    };
+
+    /// The options of the parse in progress on this thread.
+    ///
+    pub fn current() -> Self {
+        OPTIONS.get()
+    }
+
+    /// Run `f` with these options for every attribute it parses (including
+    /// nested ones), restoring the previous options afterwards.
+    ///
+    pub fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        struct Restore(ParseOptions);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                OPTIONS.set(self.0);
+            }
+        }
+
+        let _restore = Restore(OPTIONS.replace(self));
+        f()
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::STRICT
+    }
+}
+
+/// What to do with a key an attribute does not accept.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnUnknown {
+    /// Skip the key.
+    Ignore,
+    /// Skip the key, emitting a warning (see [diagnostic::warn]).
+    Warn,
+    /// Fail with an [Error::UnknownKey](errors::Error::UnknownKey).
+    #[default]
+    Error,
+}
+
+/// A key of a derived attribute, describing how its values are stored in the
+/// slots `S` (i.e. a struct with an `Option` for every key) while parsing.
+///
+pub struct Field<S> {
+    pub name: &'static str,
+    /// The declared Rust type, named in type mismatch errors.
+    pub ty: &'static str,
+    pub required: bool,
+    /// The warning emitted when the key is set, if it is deprecated.
+    pub deprecated: Option<&'static str>,
+    /// The keys that may not be set along with this one.
+    pub conflicts_with: &'static [&'static str],
+    /// The keys that must be set along with this one.
+    pub requires: &'static [&'static str],
+    /// Store a value of the key, given its name and type.
+    pub store: fn(&mut S, &'static str, &'static str, Value, &mut Accumulator),
+    pub is_set: fn(&S) -> bool,
+    /// Check the stored value once all values are stored, given the value as
+    /// written (the values of a repeated key are joined into a single list).
+    pub check: Option<fn(&S, &Value) -> Result<()>>,
+}
+
+/// The keys of a derived attribute, with which the derive parses its values
+/// through [Table::parse] rather than expanding a parse loop of its own:
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Slots {
+///     path: Option<String>,
+/// }
+///
+/// const TABLE: Table<Slots> = Table {
+///     fields: &[Field {
+///         name: "path",
+///         ty: "String",
+///         required: true,
+///         deprecated: None,
+///         conflicts_with: &[],
+///         requires: &[],
+///         store: |slots, id, ty, value, errors| {
+///             slots.path.insert_typed_value(id, ty, value, errors)
+///         },
+///         is_set: |slots| slots.path.is_some(),
+///         check: None,
+///     }],
+///     spans: None,
+///     suggestions: Suggestions::DEFAULT,
+/// };
+///
+/// let mut slots = Slots::default();
+/// TABLE.parse(values, &mut slots, &Route::SCHEMA)?.finish()?;
+/// ```
+///
+pub struct Table<S: 'static> {
+    pub fields: &'static [Field<S>],
+    /// The slot collecting the spans of all keys that were set, if any.
+    pub spans: Option<fn(&mut S) -> &mut Spans>,
+    pub suggestions: Suggestions,
+}
+
+impl<S> Table<S> {
+    /// Store every entry of `values` in the `slots` of its key, and return the
+    /// errors found along the way, including those of missing required keys.
+    ///
+    /// Fails right away only if help is requested (see [Schema::help]).
+    ///
+    pub fn parse(&self, values: Values, slots: &mut S, schema: &Schema) -> Result<Accumulator> {
+        let span = values.span();
+        let mut errors = Accumulator::new();
+        let mut given = Vec::new();
+        let mut written = Vec::new();
+
+        for value in values {
+            let Some(ident) = value.ident() else {
+                errors.push(format_error(&value, "identifier"));
+                continue;
+            };
+
+            let Some(field) = self.find(ident) else {
+                if schema::is_help(&value) {
+                    return Err(syn::Error::new(value.span(), schema.help()));
+                }
+
+                let known = self.names();
+                let error = errors::Error::unknown_key_with(
+                    &ident.to_string(),
+                    value.span(),
+                    &known,
+                    &self.suggestions,
+                );
+
+                match ParseOptions::current().on_unknown {
+                    OnUnknown::Ignore => {}
+                    OnUnknown::Warn => diagnostic::warn(value.span(), &error),
+                    OnUnknown::Error => errors.push(error),
+                }
+                continue;
+            };
+
+            if let Some(message) = field.deprecated {
+                diagnostic::warn(value.span(), message);
+            }
+
+            if let Some(spans) = self.spans {
+                spans(slots).insert(field.name, value.span());
+            }
+
+            let first = given
+                .iter()
+                .find(|(name, _)| *name == field.name)
+                .map(|&(_, span)| span);
+
+            given.push((field.name, value.span()));
+
+            // The values of a checked key are kept until all are stored, as
+            // those of a repeated key are checked together.
+            let copy = field.check.is_some().then(|| value.clone());
+
+            let mut value_errors = Accumulator::new();
+            (field.store)(slots, field.name, field.ty, value, &mut value_errors);
+
+            if let Some(copy) = copy {
+                written.push((field.name, copy, value_errors.is_empty()));
+            }
+
+            errors.extend(value_errors.into_iter().map(|error| match error {
+                errors::Error::DuplicateKey { key, span, .. } => {
+                    errors::Error::DuplicateKey { key, span, first }
+                }
+                error => error,
+            }));
+        }
+
+        // A required key that was given but failed to parse is already
+        // reported as such, rather than as missing.
+        for field in self.fields {
+            let given = given.iter().any(|&(name, _)| name == field.name);
+
+            if field.required && !given && !(field.is_set)(slots) {
+                errors.push(errors::Error::MissingKey {
+                    key: field.name.to_string(),
+                    span,
+                });
+            }
+        }
+
+        self.check_values(slots, written, &mut errors);
+        self.check_relations(&given, &mut errors);
+
+        Ok(errors)
+    }
+
+    /// Check the stored values of the keys with a check, given the values as
+    /// they were `written` (by name, along with whether they were stored). A
+    /// key of which a value could not be stored is not checked.
+    ///
+    fn check_values(
+        &self,
+        slots: &S,
+        written: Vec<(&'static str, Value, bool)>,
+        errors: &mut Accumulator,
+    ) {
+        for field in self.fields {
+            let Some(check) = field.check else {
+                continue;
+            };
+
+            let mut values = Vec::new();
+            let mut stored = true;
+
+            for (name, value, ok) in &written {
+                if *name == field.name {
+                    values.push(value);
+                    stored &= ok;
+                }
+            }
+
+            if values.is_empty() || !stored {
+                continue;
+            }
+
+            errors.handle(check(slots, &join_written(values)));
+        }
+    }
+
+    /// Report the keys among those `given` (by name, along with their spans)
+    /// that conflict with another given key, or require one that is not. Every
+    /// key involved is pointed at.
+    ///
+    fn check_relations(&self, given: &[(&'static str, Span)], errors: &mut Accumulator) {
+        let find = |name: &str| {
+            given
+                .iter()
+                .find(|(given, _)| *given == name)
+                .map(|&(_, span)| span)
+        };
+
+        for field in self.fields {
+            let Some(span) = find(field.name) else {
+                continue;
+            };
+
+            for &other in field.conflicts_with {
+                if let Some(other_span) = find(other) {
+                    errors.push(errors::with_notes(
+                        span,
+                        format!("key `{}` conflicts with `{}`", field.name, other),
+                        [(other_span, format!("`{}` is set here", other))],
+                    ));
+                }
+            }
+
+            for &other in field.requires {
+                if find(other).is_none() {
+                    errors.push(syn::Error::new(
+                        span,
+                        format!("key `{}` requires `{}` to be set", field.name, other),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Find the field of the key `ident`. Its name is written on the stack,
+    /// as an identifier can only be read through [Display](fmt::Display),
+    /// unless it is too long to fit.
+    ///
+    fn find(&self, ident: &Ident) -> Option<&Field<S>> {
+        let mut name = StackStr::default();
+
+        match write!(name, "{}", ident) {
+            Ok(()) => self.find_name(name.as_str()),
+            Err(_) => self.find_name(&ident.to_string()),
+        }
+    }
+
+    fn find_name(&self, name: &str) -> Option<&Field<S>> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        self.fields.iter().map(|field| field.name).collect()
+    }
+}
+
+/// Join the values of a repeated key (e.g. `methods("a"), methods("b")`) into
+/// a single list, written as the last of them, so that a check of all values
+/// points at the entries as they were written.
+///
+fn join_written(mut values: Vec<&Value>) -> Value {
+    let last = match values.pop() {
+        Some(last) if values.is_empty() => return last.clone(),
+        Some(last) => last,
+        None => unreachable!("a key is only checked once it is written"),
+    };
+
+    let mut entries = Values::new(last.value_span());
+
+    for value in values.into_iter().chain([last]) {
+        match value {
+            Value::List(List { values, .. }) => entries.extend(values.iter().cloned()),
+            Value::Expr(Expr { value, .. }) => match value.as_ref() {
+                Value::Tuple(Tuple { values, .. }) => entries.extend(values.iter().cloned()),
+                value => entries.push(value.clone()),
+            },
+            value => entries.push(value.clone()),
+        }
+    }
+
+    let ident = match last.ident() {
+        Some(ident) => ident.clone(),
+        None => unreachable!("a key is only checked once it is written"),
+    };
+
+    Value::List(List {
+        ident,
+        paren_token: Paren(last.value_span()),
+        values: entries,
+    })
+}
+
+/// A string of limited length on the stack, to which writing fails once it
+/// would no longer fit.
+///
+struct StackStr {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl Default for StackStr {
+    fn default() -> Self {
+        Self {
+            bytes: [0; 64],
+            len: 0,
+        }
+    }
+}
+
+impl StackStr {
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len]).expect("only whole strings are written")
+    }
+}
+
+impl Write for StackStr {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let bytes = self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?;
+
+        bytes.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+    use quote::quote;
+    use syn::{Ident, parse2};
+
+    use super::{Field, OnUnknown, ParseOptions, Table};
+    use crate::{
+        ast::Values,
+        dym::Suggestions,
+        errors::{Accumulator, Error},
+        schema::{Key, Kind, Schema},
+        types::ValueStorageExt,
+    };
+
+    #[derive(Default)]
+    struct Slots {
+        path: Option<String>,
+        strict: Option<bool>,
+    }
+
+    const TABLE: Table<Slots> = Table {
+        fields: &[
+            Field {
+                name: "path",
+                ty: "String",
+                required: true,
+                deprecated: None,
+                conflicts_with: &[],
+                requires: &[],
+                store: |slots, id, ty, value, errors| {
+                    slots.path.insert_typed_value(id, ty, value, errors)
+                },
+                is_set: |slots| slots.path.is_some(),
+                check: None,
+            },
+            Field {
+                name: "strict",
+                ty: "bool",
+                required: false,
+                deprecated: None,
+                conflicts_with: &[],
+                requires: &[],
+                store: |slots, id, ty, value, errors| {
+                    slots.strict.insert_typed_value(id, ty, value, errors)
+                },
+                is_set: |slots| slots.strict.is_some(),
+                check: None,
+            },
+        ],
+        spans: None,
+        suggestions: Suggestions::DEFAULT,
+    };
+
+    const SCHEMA: Schema = Schema::Struct {
+        name: "Route",
+        doc: "",
+        keys: &[Key {
+            name: "path",
+            kind: Kind::String,
+            required: true,
+            default: None,
+            doc: "",
+            deprecated: None,
+        }],
+    };
+
+    fn parse(input: proc_macro2::TokenStream) -> syn::Result<(Slots, Accumulator)> {
+        let values: Values = parse2(input).unwrap();
+        let mut slots = Slots::default();
+        let errors = TABLE.parse(values, &mut slots, &SCHEMA)?;
+
+        Ok((slots, errors))
+    }
+
+    #[test]
+    fn parse_keys() {
+        let (slots, errors) = parse(quote!(strict = true, path = "/")).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(slots.path.as_deref(), Some("/"));
+        assert_eq!(slots.strict, Some(true));
+    }
+
+    #[test]
+    fn report_missing_keys() {
+        let (_, errors) = parse(quote!(strict = true)).unwrap();
+
+        assert!(matches!(
+            errors.iter().collect::<Vec<_>>()[..],
+            [Error::MissingKey { key, .. }] if key == "path"
+        ));
+    }
+
+    #[test]
+    fn report_invalid_required_keys_once() {
+        let (_, errors) = parse(quote!(path = 1)).unwrap();
+
+        assert!(matches!(
+            errors.iter().collect::<Vec<_>>()[..],
+            [Error::TypeMismatch { key: Some(key), .. }] if key == "path"
+        ));
+    }
+
+    #[test]
+    fn report_duplicate_keys() {
+        let (slots, errors) = parse(quote!(path = "/a", path = "/b")).unwrap();
+
+        assert_eq!(slots.path.as_deref(), Some("/a"));
+        assert!(matches!(
+            errors.iter().collect::<Vec<_>>()[..],
+            [Error::DuplicateKey { key, first: Some(_), .. }] if key == "path"
+        ));
+    }
+
+    #[test]
+    fn report_unknown_keys() {
+        let (_, errors) = parse(quote!(path = "/", paht = "/")).unwrap();
+
+        assert!(matches!(
+            errors.iter().collect::<Vec<_>>()[..],
+            [Error::UnknownKey { key, suggestions, .. }]
+                if key == "paht" && suggestions == &["path"]
+        ));
+
+        let options = ParseOptions {
+            on_unknown: OnUnknown::Ignore,
+        };
+        let (_, errors) = options
+            .scope(|| parse(quote!(path = "/", paht = "/")))
+            .unwrap();
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn fail_on_help() {
+        let error = parse(quote!(path = "/", help)).err().unwrap();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("`Route` accepts the following keys:")
+        );
+    }
+
+    #[test]
+    fn find_fields() {
+        const LONG: &str = "a_key_with_a_name_that_is_far_too_long_to_be_written_on_the_stack";
+
+        const TABLE: Table<()> = Table {
+            fields: &[
+                Field {
+                    name: "path",
+                    ty: "String",
+                    required: false,
+                    deprecated: None,
+                    conflicts_with: &[],
+                    requires: &[],
+                    store: |_, _, _, _, _| {},
+                    is_set: |_| false,
+                    check: None,
+                },
+                Field {
+                    name: LONG,
+                    ty: "bool",
+                    required: false,
+                    deprecated: None,
+                    conflicts_with: &[],
+                    requires: &[],
+                    store: |_, _, _, _, _| {},
+                    is_set: |_| false,
+                    check: None,
+                },
+            ],
+            spans: None,
+            suggestions: Suggestions::DEFAULT,
+        };
+
+        let find = |name: &str| {
+            TABLE
+                .find(&Ident::new(name, Span::call_site()))
+                .map(|field| field.name)
+        };
+
+        assert_eq!(find("path"), Some("path"));
+        assert_eq!(find(LONG), Some(LONG));
+        assert_eq!(find("pat"), None);
+        assert_eq!(find("paths"), None);
+    }
+}
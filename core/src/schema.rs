@@ -0,0 +1,665 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::ast::Value;
+
+/// A description of the keys an attribute accepts, available at compile time
+/// through [Attribute::SCHEMA](crate::attribute::Attribute::SCHEMA).
+///
+/// The derive describes every attribute it implements, so that tooling (e.g.
+/// documentation generators or runtime validators) and the parser share a
+/// single source of truth.
+///
+#[derive(Clone, Copy, Debug)]
+pub enum Schema {
+    /// An attribute with named keys, e.g. `route(path = "/", method = get)`.
+    Struct {
+        name: &'static str,
+        doc: &'static str,
+        keys: &'static [Key],
+    },
+    /// An attribute consisting of flags, e.g. `methods(get, post)`. Every flag
+    /// is described as an optional key of kind [Kind::Bool].
+    Flags {
+        name: &'static str,
+        doc: &'static str,
+        flags: &'static [Key],
+    },
+    /// An attribute with a single variant, each with its own keys, e.g.
+    /// `auth(token(header = "x"))`.
+    Tagged {
+        name: &'static str,
+        doc: &'static str,
+        variants: &'static [Variant],
+    },
+    /// An attribute that does not describe itself (e.g. implemented by hand).
+    Opaque,
+}
+
+impl Schema {
+    /// The name of the type implementing the attribute, if known.
+    ///
+    pub const fn name(&self) -> Option<&'static str> {
+        match self {
+            Schema::Struct { name, .. }
+            | Schema::Flags { name, .. }
+            | Schema::Tagged { name, .. } => Some(name),
+            Schema::Opaque => None,
+        }
+    }
+
+    /// The doc comment of the type implementing the attribute, or an empty
+    /// string if it has none.
+    ///
+    pub const fn doc(&self) -> &'static str {
+        match self {
+            Schema::Struct { doc, .. } | Schema::Flags { doc, .. } | Schema::Tagged { doc, .. } => {
+                doc
+            }
+            Schema::Opaque => "",
+        }
+    }
+
+    /// Render the schema as Markdown: a section with a table of the keys (their
+    /// kind, default and doc comment), followed by a section for every nested
+    /// attribute. Useful to keep the documentation of an attribute in sync with
+    /// its implementation, e.g. by comparing it to a README in a test.
+    ///
+    pub fn to_markdown(&self) -> String {
+        let mut sections = Vec::new();
+
+        for schema in self.attributes() {
+            let name = schema.name().expect("described attributes have a name");
+            let mut section = format!("## `{}`\n\n", name);
+
+            if !schema.doc().is_empty() {
+                section.push_str(schema.doc());
+                section.push_str("\n\n");
+            }
+
+            match schema {
+                Schema::Struct { keys, .. } => {
+                    section.push_str(&markdown_table(keys));
+                }
+                Schema::Flags { flags, .. } => {
+                    section.push_str("| Flag | Description |\n| --- | --- |\n");
+                    for flag in flags {
+                        section.push_str(&format!(
+                            "| `{}` | {} |\n",
+                            flag.name,
+                            markdown_cell(flag.doc)
+                        ));
+                    }
+                }
+                Schema::Tagged { variants, .. } => {
+                    section.push_str("Given as exactly one of the following variants.\n");
+                    for variant in variants {
+                        section.push_str(&format!("\n### `{}`\n\n", variant.name));
+                        if !variant.doc.is_empty() {
+                            section.push_str(variant.doc);
+                            section.push_str("\n\n");
+                        }
+                        if variant.keys.is_empty() {
+                            section.push_str("Takes no keys.\n");
+                        } else {
+                            section.push_str(&markdown_table(variant.keys));
+                        }
+                    }
+                }
+                Schema::Opaque => unreachable!("opaque schemas are not described"),
+            }
+
+            sections.push(section);
+        }
+
+        sections.join("\n")
+    }
+
+    /// Render the schema as JSON, for tools outside of Rust (e.g. editor plugins
+    /// or config validators). Nested attributes are referred to by name and
+    /// described under `"attributes"`:
+    ///
+    /// ```json
+    /// {
+    ///   "root": "Route",
+    ///   "attributes": {
+    ///     "Route": { "type": "struct", "doc": "", "keys": [...] },
+    ///     "Methods": { "type": "flags", "doc": "", "flags": [...] }
+    ///   }
+    /// }
+    /// ```
+    ///
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{Map, json};
+
+        let attributes = self
+            .attributes()
+            .into_iter()
+            .map(|schema| {
+                let name = schema.name().expect("described attributes have a name");
+                let value = match schema {
+                    Schema::Struct { doc, keys, .. } => json!({
+                        "type": "struct",
+                        "doc": doc,
+                        "keys": keys.iter().map(Key::json).collect::<Vec<_>>(),
+                    }),
+                    Schema::Flags { doc, flags, .. } => json!({
+                        "type": "flags",
+                        "doc": doc,
+                        "flags": flags.iter().map(Key::json).collect::<Vec<_>>(),
+                    }),
+                    Schema::Tagged { doc, variants, .. } => json!({
+                        "type": "tagged",
+                        "doc": doc,
+                        "variants": variants.iter().map(|variant| json!({
+                            "name": variant.name,
+                            "doc": variant.doc,
+                            "keys": variant.keys.iter().map(Key::json).collect::<Vec<_>>(),
+                        })).collect::<Vec<_>>(),
+                    }),
+                    Schema::Opaque => unreachable!("opaque schemas are not described"),
+                };
+                (name.to_string(), value)
+            })
+            .collect::<Map<_, _>>();
+
+        json!({
+            "root": self.name(),
+            "attributes": attributes,
+        })
+    }
+
+    /// List everything the attribute accepts, in the message of the error
+    /// raised when a user writes the `help` pseudo-key (e.g. `#[route(help)]`):
+    ///
+    /// ```text
+    /// `Route` accepts the following keys:
+    ///   `path`: string (required) - The path of the route.
+    ///   `verbose`: bool (default `false`)
+    /// ```
+    ///
+    pub fn help(&self) -> String {
+        let name = self.name().unwrap_or("the attribute");
+
+        match self {
+            Schema::Struct { keys, .. } => {
+                format!(
+                    "`{}` accepts the following keys:{}",
+                    name,
+                    help_keys(keys, 1)
+                )
+            }
+            Schema::Flags { flags, .. } => {
+                let flags = flags.iter().map(|flag| match summary(flag.doc) {
+                    Some(summary) => format!("\n  `{}` - {}", flag.name, summary),
+                    None => format!("\n  `{}`", flag.name),
+                });
+
+                format!(
+                    "`{}` accepts the following flags:{}",
+                    name,
+                    flags.collect::<String>()
+                )
+            }
+            Schema::Tagged { variants, .. } => {
+                let variants = variants.iter().map(|variant| {
+                    if variant.keys.is_empty() {
+                        format!("\n  `{}`", variant.name)
+                    } else {
+                        format!(
+                            "\n  `{}`, with the keys:{}",
+                            variant.name,
+                            help_keys(variant.keys, 2)
+                        )
+                    }
+                });
+
+                format!(
+                    "`{}` accepts one of the following variants:{}",
+                    name,
+                    variants.collect::<String>()
+                )
+            }
+            Schema::Opaque => format!("{} does not describe what it accepts", name),
+        }
+    }
+
+    /// This schema followed by the schemas of all attributes nested in it (e.g.
+    /// through its keys or variants), each described once. Opaque schemas are
+    /// left out, as there is nothing to describe.
+    ///
+    pub fn attributes(&self) -> Vec<Schema> {
+        let mut attributes = Vec::<Schema>::new();
+        let mut pending = VecDeque::from([*self]);
+
+        while let Some(schema) = pending.pop_front() {
+            if schema.name().is_none()
+                || attributes
+                    .iter()
+                    .any(|attribute| attribute.name() == schema.name())
+            {
+                continue;
+            }
+
+            let keys = match schema {
+                Schema::Struct { keys, .. } => keys.iter().collect(),
+                Schema::Tagged { variants, .. } => {
+                    variants.iter().flat_map(|variant| variant.keys).collect()
+                }
+                _ => Vec::new(),
+            };
+
+            pending.extend(keys.into_iter().flat_map(|key| key.kind.nested()));
+            attributes.push(schema);
+        }
+
+        attributes
+    }
+}
+
+#[cfg(feature = "json")]
+impl Key {
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "kind": self.kind.json(),
+            "required": self.required,
+            "default": self.default,
+            "doc": self.doc,
+            "deprecated": self.deprecated.map(|deprecation| serde_json::json!({
+                "since": deprecation.since,
+                "note": deprecation.note,
+            })),
+        })
+    }
+}
+
+/// List `keys` on separate lines, indented by `depth` levels.
+///
+fn help_keys(keys: &[Key], depth: usize) -> String {
+    let indent = "  ".repeat(depth);
+
+    keys.iter()
+        .map(|key| {
+            let mut line = format!("\n{}`{}`: {}", indent, key.name, key.kind);
+
+            match (key.required, key.default) {
+                (true, _) => line.push_str(" (required)"),
+                (false, Some(default)) => line.push_str(&format!(" (default `{}`)", default)),
+                (false, None) => {}
+            }
+
+            if let Some(deprecation) = key.deprecated {
+                line.push_str(&format!(" ({})", deprecation));
+            }
+
+            if let Some(summary) = summary(key.doc) {
+                line.push_str(" - ");
+                line.push_str(&summary);
+            }
+
+            line
+        })
+        .collect()
+}
+
+/// The first paragraph of a doc comment on a single line, if any.
+///
+fn summary(doc: &str) -> Option<String> {
+    let paragraph = doc.split("\n\n").next()?;
+    let summary = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!summary.is_empty()).then_some(summary)
+}
+
+/// Determine whether a value is the `help` pseudo-key, which lists everything
+/// an attribute accepts instead of being parsed (see [Schema::help]).
+///
+pub fn is_help(value: &Value) -> bool {
+    matches!(value, Value::Ident(ident) if ident == "help")
+}
+
+/// Render `keys` as a Markdown table.
+///
+fn markdown_table(keys: &[Key]) -> String {
+    let mut table =
+        String::from("| Key | Type | Default | Description |\n| --- | --- | --- | --- |\n");
+
+    for key in keys {
+        let default = match (key.required, key.default) {
+            (true, _) => "*required*".to_string(),
+            (false, Some(default)) => format!("`{}`", default),
+            (false, None) => "-".to_string(),
+        };
+
+        let description = match key.deprecated {
+            Some(deprecation) => format!("{} *({})*", key.doc, deprecation),
+            None => key.doc.to_string(),
+        };
+
+        table.push_str(&format!(
+            "| `{}` | {} | {} | {} |\n",
+            key.name,
+            markdown_cell(&key.kind.to_string()),
+            default,
+            markdown_cell(&description)
+        ));
+    }
+
+    table
+}
+
+/// Make `text` fit in a single cell of a Markdown table.
+///
+fn markdown_cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// A key of an attribute, e.g. `path` in `route(path = "/")`.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Key {
+    pub name: &'static str,
+    pub kind: Kind,
+    /// The key must be given (i.e. it has no default).
+    pub required: bool,
+    /// The value used when the key is omitted, if any (e.g. `false` for flags).
+    pub default: Option<&'static str>,
+    /// The doc comment of the field, or an empty string if it has none.
+    pub doc: &'static str,
+    /// Whether the key is deprecated, and since when.
+    pub deprecated: Option<Deprecation>,
+}
+
+/// The deprecation of a key, e.g. through
+/// `#[squattr(deprecated_since = "0.4", note = "use `path` instead")]`.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Deprecation {
+    /// The version since which the key is deprecated, if known.
+    pub since: Option<&'static str>,
+    /// What to do instead, if anything.
+    pub note: Option<&'static str>,
+}
+
+/// Written as in the warning emitted for the key, e.g. "deprecated since 0.4,
+/// use `path` instead".
+///
+impl Display for Deprecation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "deprecated")?;
+
+        if let Some(since) = self.since {
+            write!(f, " since {}", since)?;
+        }
+
+        if let Some(note) = self.note {
+            write!(f, ", {}", note)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A variant of a tagged attribute, e.g. `token` in `auth(token(header = "x"))`.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Variant {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub keys: &'static [Key],
+}
+
+/// The kind of value a key expects, available at compile time through
+/// [ParseValue::KIND](crate::types::ParseValue::KIND).
+///
+#[derive(Clone, Copy, Debug)]
+pub enum Kind {
+    /// A flag (`key`, `!key` or `key = true`).
+    Bool,
+    /// A flag that may be repeated to count it (`key, key`).
+    Count,
+    /// An integer literal.
+    Integer,
+    /// A decimal literal (or `inf`, `-inf`, `nan`).
+    Float,
+    /// A string literal.
+    String,
+    /// A string literal holding a value of the described kind, e.g. `"type"`
+    /// for `bound = "T: Clone"`.
+    StringOf(&'static str),
+    /// An identifier.
+    Ident,
+    /// Any literal.
+    Literal,
+    /// A duration (`"30s"`, milliseconds or seconds).
+    Duration,
+    /// A cfg-style predicate, e.g. `all(feature = "x", not(test))`.
+    Predicate,
+    /// A closure, e.g. `map = |x| x + 1`.
+    Closure,
+    /// One of the listed names, e.g. `mode = fast`.
+    OneOf(&'static [&'static str]),
+    /// A list of values of the same kind.
+    List(&'static Kind),
+    /// A list of a fixed number of values of the same kind.
+    Array(&'static Kind, usize),
+    /// A list of values of the listed kinds.
+    Tuple(&'static [Kind]),
+    /// A range between two values of the kind, which includes its end (i.e.
+    /// `a..=b` rather than `a..b`) if so marked.
+    Range(&'static Kind, bool),
+    /// A value of any of the listed kinds.
+    Either(&'static [Kind]),
+    /// A nested attribute. The schema is resolved through a function, so that
+    /// recursive attributes (e.g. `Option<Box<Self>>` fields) can be described.
+    Nested(fn() -> Schema),
+    /// A value that does not describe itself (e.g. implemented by hand).
+    Unknown,
+}
+
+impl Kind {
+    /// The schemas of the attributes nested in this kind (e.g. through a list).
+    ///
+    fn nested(&self) -> Vec<Schema> {
+        match self {
+            Kind::List(kind) | Kind::Array(kind, _) | Kind::Range(kind, _) => kind.nested(),
+            Kind::Tuple(kinds) | Kind::Either(kinds) => {
+                kinds.iter().flat_map(Kind::nested).collect()
+            }
+            Kind::Nested(schema) => vec![schema()],
+            _ => Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    fn json(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        let all = |kinds: &[Kind]| kinds.iter().map(Kind::json).collect::<Vec<_>>();
+
+        match self {
+            Kind::Bool => json!({ "type": "bool" }),
+            Kind::Count => json!({ "type": "count" }),
+            Kind::Integer => json!({ "type": "integer" }),
+            Kind::Float => json!({ "type": "float" }),
+            Kind::String => json!({ "type": "string" }),
+            Kind::StringOf(of) => json!({ "type": "string", "of": of }),
+            Kind::Ident => json!({ "type": "ident" }),
+            Kind::Literal => json!({ "type": "literal" }),
+            Kind::Duration => json!({ "type": "duration" }),
+            Kind::Predicate => json!({ "type": "predicate" }),
+            Kind::Closure => json!({ "type": "closure" }),
+            Kind::OneOf(names) => json!({ "type": "one_of", "names": names }),
+            Kind::List(kind) => json!({ "type": "list", "of": kind.json() }),
+            Kind::Array(kind, len) => json!({ "type": "array", "of": kind.json(), "len": len }),
+            Kind::Tuple(kinds) => json!({ "type": "tuple", "of": all(kinds) }),
+            Kind::Range(kind, inclusive) => {
+                json!({ "type": "range", "of": kind.json(), "inclusive": inclusive })
+            }
+            Kind::Either(kinds) => json!({ "type": "either", "of": all(kinds) }),
+            Kind::Nested(schema) => json!({ "type": "nested", "attribute": schema().name() }),
+            Kind::Unknown => json!({ "type": "unknown" }),
+        }
+    }
+}
+
+/// Describe the kind in words, e.g. `list of integer` or `` one of `a`, `b` ``.
+///
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Bool => write!(f, "bool"),
+            Kind::Count => write!(f, "count"),
+            Kind::Integer => write!(f, "integer"),
+            Kind::Float => write!(f, "float"),
+            Kind::String => write!(f, "string"),
+            Kind::StringOf(of) => write!(f, "string ({})", of),
+            Kind::Ident => write!(f, "identifier"),
+            Kind::Literal => write!(f, "literal"),
+            Kind::Duration => write!(f, "duration"),
+            Kind::Predicate => write!(f, "predicate"),
+            Kind::Closure => write!(f, "closure"),
+            Kind::OneOf(names) => {
+                let names = names.iter().map(|name| format!("`{}`", name));
+                write!(f, "one of {}", names.collect::<Vec<_>>().join(", "))
+            }
+            Kind::List(kind) => write!(f, "list of {}", kind),
+            Kind::Array(kind, len) => write!(f, "list of {} {}", len, kind),
+            Kind::Tuple(kinds) => {
+                let kinds = kinds.iter().map(Kind::to_string);
+                write!(f, "({})", kinds.collect::<Vec<_>>().join(", "))
+            }
+            Kind::Either(kinds) => {
+                let kinds = kinds.iter().map(Kind::to_string);
+                write!(f, "{}", kinds.collect::<Vec<_>>().join(" or "))
+            }
+            Kind::Range(kind, false) => write!(f, "range of {} (`a..b`)", kind),
+            Kind::Range(kind, true) => write!(f, "range of {} (`a..=b`)", kind),
+            Kind::Nested(schema) => match schema().name() {
+                Some(name) => write!(f, "`{}`", name),
+                None => write!(f, "nested attribute"),
+            },
+            Kind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "json")]
+    #[test]
+    fn schema_to_json() {
+        use serde_json::json;
+
+        use super::{Deprecation, Key, Kind, Schema};
+
+        const NODE: Schema = Schema::Struct {
+            name: "Node",
+            doc: "A node of a tree.",
+            keys: &[
+                Key {
+                    name: "weight",
+                    kind: Kind::Integer,
+                    required: true,
+                    default: None,
+                    doc: "",
+                    deprecated: None,
+                },
+                Key {
+                    name: "children",
+                    kind: Kind::List(&Kind::Nested(|| NODE)),
+                    required: false,
+                    default: None,
+                    doc: "The children of the node.",
+                    deprecated: None,
+                },
+                Key {
+                    name: "shape",
+                    kind: Kind::Nested(|| SHAPE),
+                    required: false,
+                    default: None,
+                    doc: "",
+                    deprecated: Some(Deprecation {
+                        since: Some("0.2"),
+                        note: None,
+                    }),
+                },
+            ],
+        };
+
+        const SHAPE: Schema = Schema::Flags {
+            name: "Shape",
+            doc: "",
+            flags: &[Key {
+                name: "round",
+                kind: Kind::Bool,
+                required: false,
+                default: Some("false"),
+                doc: "",
+                deprecated: None,
+            }],
+        };
+
+        assert_eq!(
+            NODE.to_json(),
+            json!({
+                "root": "Node",
+                "attributes": {
+                    "Node": {
+                        "type": "struct",
+                        "doc": "A node of a tree.",
+                        "keys": [
+                            {
+                                "name": "weight",
+                                "kind": { "type": "integer" },
+                                "required": true,
+                                "default": null,
+                                "doc": "",
+                                "deprecated": null,
+                            },
+                            {
+                                "name": "children",
+                                "kind": {
+                                    "type": "list",
+                                    "of": { "type": "nested", "attribute": "Node" },
+                                },
+                                "required": false,
+                                "default": null,
+                                "doc": "The children of the node.",
+                                "deprecated": null,
+                            },
+                            {
+                                "name": "shape",
+                                "kind": { "type": "nested", "attribute": "Shape" },
+                                "required": false,
+                                "default": null,
+                                "doc": "",
+                                "deprecated": { "since": "0.2", "note": null },
+                            },
+                        ],
+                    },
+                    "Shape": {
+                        "type": "flags",
+                        "doc": "",
+                        "flags": [
+                            {
+                                "name": "round",
+                                "kind": { "type": "bool" },
+                                "required": false,
+                                "default": "false",
+                                "doc": "",
+                                "deprecated": null,
+                            },
+                        ],
+                    },
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use proptest::{
+    collection, option,
+    prelude::{BoxedStrategy, Just, Strategy, any},
+    strategy::Union,
+};
+use quote::{ToTokens, quote};
+
+use crate::schema::{Key, Kind, Schema};
+
+/// How deeply nested attributes are generated, which also ends recursive
+/// schemas (e.g. those of `Option<Box<Self>>` fields).
+///
+const MAX_DEPTH: usize = 3;
+
+/// Generate the tokens of attributes conforming to `schema`, i.e. the `...` of
+/// `#[attr(...)]`, with the optional keys left out at random and the keys in
+/// random order:
+///
+/// ```ignore
+/// proptest! {
+///     #[test]
+///     fn parse_routes(tokens in strategy::values(&Route::SCHEMA)) {
+///         Route::from_tokens(tokens).unwrap();
+///     }
+/// }
+/// ```
+///
+/// Values are only generated for the kinds they can be derived from, so keys
+/// of other kinds (e.g. [Kind::Unknown], or a string holding an IP address)
+/// are left out.
+///
+/// Panics if the schema requires a key of such a kind, or describes no keys
+/// at all (i.e. [Schema::Opaque]).
+///
+pub fn values(schema: &Schema) -> BoxedStrategy<TokenStream> {
+    values_at(schema, MAX_DEPTH).unwrap_or_else(|| {
+        panic!(
+            "cannot generate values for `{}`",
+            schema.name().unwrap_or("an opaque attribute")
+        )
+    })
+}
+
+fn values_at(schema: &Schema, depth: usize) -> Option<BoxedStrategy<TokenStream>> {
+    match *schema {
+        Schema::Struct { keys, .. } => keys_at(keys, depth),
+        Schema::Flags { flags, .. } => {
+            let flags: Vec<_> = flags
+                .iter()
+                .map(|flag| {
+                    let ident = ident(flag.name);
+                    option::of(Just(quote!(#ident))).boxed()
+                })
+                .collect();
+
+            Some(join(flags))
+        }
+        Schema::Tagged { variants, .. } => {
+            let variants: Vec<_> = variants
+                .iter()
+                .filter_map(|variant| {
+                    let ident = ident(variant.name);
+
+                    if variant.keys.is_empty() {
+                        return Some(Just(quote!(#ident)).boxed());
+                    }
+
+                    keys_at(variant.keys, depth)
+                        .map(|values| values.prop_map(move |values| quote!(#ident(#values))))
+                        .map(Strategy::boxed)
+                })
+                .collect();
+
+            (!variants.is_empty()).then(|| Union::new(variants).boxed())
+        }
+        Schema::Opaque => None,
+    }
+}
+
+fn keys_at(keys: &'static [Key], depth: usize) -> Option<BoxedStrategy<TokenStream>> {
+    let mut entries = Vec::new();
+
+    for key in keys {
+        match (entry(key.name, &key.kind, depth), key.required) {
+            (Some(entry), true) => entries.push(entry.prop_map(Some).boxed()),
+            (Some(entry), false) => entries.push(option::of(entry).boxed()),
+            (None, true) => return None,
+            (None, false) => {}
+        }
+    }
+
+    Some(join(entries))
+}
+
+/// Shuffle the generated entries and separate them by commas.
+///
+fn join(entries: Vec<BoxedStrategy<Option<TokenStream>>>) -> BoxedStrategy<TokenStream> {
+    entries
+        .prop_shuffle()
+        .prop_map(|entries| {
+            let entries = entries.into_iter().flatten();
+            quote!(#(#entries),*)
+        })
+        .boxed()
+}
+
+/// Generate an entry setting the key `name` (e.g. `name = 1` or `name(1, 2)`).
+///
+fn entry(name: &'static str, kind: &Kind, depth: usize) -> Option<BoxedStrategy<TokenStream>> {
+    let ident = ident(name);
+
+    let strategy = match *kind {
+        Kind::List(kind) => collection::vec(value(kind, depth)?, 0..4)
+            .prop_map(move |values| quote!(#ident(#(#values),*)))
+            .boxed(),
+        Kind::Array(kind, len) => collection::vec(value(kind, depth)?, len)
+            .prop_map(move |values| quote!(#ident(#(#values),*)))
+            .boxed(),
+        Kind::Tuple(kinds) => kinds
+            .iter()
+            .map(|kind| value(kind, depth))
+            .collect::<Option<Vec<_>>>()?
+            .prop_map(move |values| quote!(#ident(#(#values),*)))
+            .boxed(),
+        Kind::Either(kinds) => {
+            let kinds: Vec<_> = kinds
+                .iter()
+                .filter_map(|kind| entry(name, kind, depth))
+                .collect();
+
+            (!kinds.is_empty()).then(|| Union::new(kinds).boxed())?
+        }
+        Kind::Nested(schema) => values_at(&schema(), depth.checked_sub(1)?)?
+            .prop_map(move |values| quote!(#ident(#values)))
+            .boxed(),
+        Kind::Predicate => predicate()
+            .prop_map(move |predicate| quote!(#ident(#predicate)))
+            .boxed(),
+        Kind::Count => (1..4usize)
+            .prop_map(move |count| {
+                let idents = std::iter::repeat_n(&ident, count);
+                quote!(#(#idents),*)
+            })
+            .boxed(),
+        _ => value(kind, depth)?
+            .prop_map(move |value| quote!(#ident = #value))
+            .boxed(),
+    };
+
+    Some(strategy)
+}
+
+/// Generate a value as written within a list (e.g. `1` or `(a = 1)`).
+///
+fn value(kind: &Kind, depth: usize) -> Option<BoxedStrategy<TokenStream>> {
+    let strategy = match *kind {
+        Kind::Bool => any::<bool>().prop_map(|value| quote!(#value)).boxed(),
+        Kind::Integer | Kind::Duration => (0..=i8::MAX)
+            .prop_map(|value| Literal::i8_unsuffixed(value).into_token_stream())
+            .boxed(),
+        Kind::Float => (0..1000u16)
+            .prop_map(|value| Literal::f64_unsuffixed(f64::from(value) / 8.0).into_token_stream())
+            .boxed(),
+        Kind::String => "[a-z /]{0,8}"
+            .prop_map(|value| Literal::string(&value).into_token_stream())
+            .boxed(),
+        Kind::StringOf("path" | "type" | "expression") => path()
+            .prop_map(|path| Literal::string(&path).into_token_stream())
+            .boxed(),
+        Kind::StringOf(_) | Kind::Count => return None,
+        Kind::Ident => identifier()
+            .prop_map(|name| ident(&name).into_token_stream())
+            .boxed(),
+        Kind::Literal => {
+            Union::new([value(&Kind::Integer, depth)?, value(&Kind::String, depth)?]).boxed()
+        }
+        Kind::Predicate => predicate(),
+        Kind::Closure => identifier()
+            .prop_map(|name| {
+                let ident = ident(&name);
+                quote!(|#ident| #ident)
+            })
+            .boxed(),
+        Kind::OneOf(names) => (0..names.len())
+            .prop_map(move |index| Literal::string(names[index]).into_token_stream())
+            .boxed(),
+        Kind::List(kind) => collection::vec(value(kind, depth)?, 0..4)
+            .prop_map(|values| quote!((#(#values),*)))
+            .boxed(),
+        Kind::Array(kind, len) => collection::vec(value(kind, depth)?, len)
+            .prop_map(|values| quote!((#(#values),*)))
+            .boxed(),
+        Kind::Tuple(kinds) => kinds
+            .iter()
+            .map(|kind| value(kind, depth))
+            .collect::<Option<Vec<_>>>()?
+            .prop_map(|values| quote!((#(#values),*)))
+            .boxed(),
+        Kind::Range(kind, inclusive) => (value(kind, depth)?, value(kind, depth)?)
+            .prop_map(move |(start, end)| match inclusive {
+                true => quote!(#start..=#end),
+                false => quote!(#start..#end),
+            })
+            .boxed(),
+        Kind::Either(kinds) => {
+            let kinds: Vec<_> = kinds.iter().filter_map(|kind| value(kind, depth)).collect();
+
+            (!kinds.is_empty()).then(|| Union::new(kinds).boxed())?
+        }
+        Kind::Nested(schema) => {
+            let schema = schema();
+            let values = values_at(&schema, depth.checked_sub(1)?)?;
+
+            // Tagged attributes are listed by their variants (e.g. `token(...)`)
+            // and all others between parentheses.
+            match schema {
+                Schema::Tagged { .. } => values,
+                _ => values.prop_map(|values| quote!((#values))).boxed(),
+            }
+        }
+        Kind::Unknown => return None,
+    };
+
+    Some(strategy)
+}
+
+/// Generate a cfg-style predicate (e.g. `all(test, not(feature = "x"))`).
+///
+fn predicate() -> BoxedStrategy<TokenStream> {
+    let leaf = Union::new([
+        identifier()
+            .prop_map(|name| ident(&name).into_token_stream())
+            .boxed(),
+        (identifier(), "[a-z]{0,4}")
+            .prop_map(|(name, value)| {
+                let ident = ident(&name);
+                quote!(#ident = #value)
+            })
+            .boxed(),
+    ]);
+
+    leaf.prop_recursive(3, 12, 3, |inner| {
+        Union::new([
+            collection::vec(inner.clone(), 0..3)
+                .prop_map(|predicates| quote!(any(#(#predicates),*)))
+                .boxed(),
+            collection::vec(inner.clone(), 0..3)
+                .prop_map(|predicates| quote!(all(#(#predicates),*)))
+                .boxed(),
+            inner.prop_map(|predicate| quote!(not(#predicate))).boxed(),
+        ])
+    })
+    .boxed()
+}
+
+/// Generate an identifier that cannot be mistaken for a keyword or a special
+/// value such as `inf`.
+///
+fn identifier() -> impl Strategy<Value = String> {
+    "x_[a-z0-9]{0,6}"
+}
+
+fn path() -> impl Strategy<Value = String> {
+    collection::vec(identifier(), 1..3).prop_map(|segments| segments.join("::"))
+}
+
+fn ident(name: &str) -> Ident {
+    Ident::new(name, Span::call_site())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prop_assert, proptest};
+
+    use super::values;
+    use crate::{
+        ast::Values,
+        schema::{Key, Kind, Schema},
+    };
+
+    const SCHEMA: Schema = Schema::Struct {
+        name: "Route",
+        doc: "",
+        keys: &[
+            Key {
+                name: "path",
+                kind: Kind::String,
+                required: true,
+                default: None,
+                doc: "",
+                deprecated: None,
+            },
+            Key {
+                name: "sizes",
+                kind: Kind::List(&Kind::Tuple(&[Kind::Integer, Kind::Float])),
+                required: false,
+                default: None,
+                doc: "",
+                deprecated: None,
+            },
+            Key {
+                name: "when",
+                kind: Kind::Predicate,
+                required: false,
+                default: None,
+                doc: "",
+                deprecated: None,
+            },
+        ],
+    };
+
+    proptest! {
+        #[test]
+        fn generate_values(tokens in values(&SCHEMA)) {
+            let values: Values = syn::parse2(tokens).unwrap();
+
+            prop_assert!(values.get_by_key("path").is_some());
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use proc_macro2::TokenStream;
+
+/// Assert that two token streams are equal, comparing them as pretty-printed
+/// Rust files so that a mismatch shows as a readable diff. Both streams must
+/// therefore parse as a file (i.e. a list of items):
+///
+/// ```ignore
+/// let expanded = my_derive::expand(quote!(struct Foo;))?;
+///
+/// assert_eq_token_streams(&expanded, &quote! {
+///     impl Foo {}
+/// });
+/// ```
+///
+pub fn assert_eq_token_streams(a: &TokenStream, b: &TokenStream) {
+    pretty_assertions::assert_eq!(unparse(a), unparse(b));
+}
+
+/// Expand the derive `input` with `expand` and pretty-print the output, e.g. to
+/// be snapshot with `insta` so that a change to the generated code is reviewed
+/// as a snapshot diff:
+///
+/// ```ignore
+/// let input = quote! {
+///     struct Route {
+///         path: String,
+///     }
+/// };
+///
+/// insta::assert_snapshot!(expand_pretty(my_derive::expand, input));
+/// ```
+///
+/// Panics if the expansion fails, since a failure has no output to show.
+///
+pub fn expand_pretty<F>(expand: F, input: TokenStream) -> String
+where
+    F: FnOnce(TokenStream) -> syn::Result<TokenStream>,
+{
+    match expand(input) {
+        Ok(output) => unparse(&output),
+        Err(error) => panic!("expansion failed: {}", error),
+    }
+}
+
+/// Pretty-print `tokens`, which must parse as a file (i.e. a list of items).
+///
+pub fn unparse(tokens: &TokenStream) -> String {
+    let file = syn::parse_file(&tokens.to_string()).unwrap();
+    prettyplease::unparse(&file)
+}
+
+/// Assert that an expansion from `start` to `end` took less than `lt_us`
+/// microseconds, returning how long it took (e.g. to report it):
+///
+/// ```ignore
+/// let start = Instant::now();
+/// let expanded = my_derive::expand(input)?;
+/// let end = Instant::now();
+///
+/// assess_expansion_duration(start, end, 50_000);
+/// ```
+///
+pub fn assess_expansion_duration(start: Instant, end: Instant, lt_us: u128) -> Duration {
+    let duration = end - start;
+
+    assert!(
+        duration.as_micros() < lt_us,
+        "expansion took {}us, expected less than {}us",
+        duration.as_micros(),
+        lt_us
+    );
+
+    duration
+}
@@ -0,0 +1,538 @@
+#[cfg(feature = "duration")]
+use std::time::Duration;
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
+};
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{
+    Ident, Lit, LitBool, LitFloat, LitInt, LitStr, Path, Type, TypeParamBound, WherePredicate,
+};
+
+use crate::{
+    attribute::Attribute,
+    types::{Count, Flag, FromStrValue, ParseStrValue, Predicate, Repeated, SpannedValue},
+};
+
+/// Write a value back as attribute syntax, i.e. the inverse of
+/// [ParseValue](crate::types::ParseValue).
+///
+pub trait ToValueTokens {
+    /// Write the value as an entry of a list, such as `"a"` in `key("a", "b")`.
+    ///
+    fn to_value_tokens(&self) -> TokenStream;
+
+    /// Write the value as the value of the key `id` (e.g. `key = "a"`), or
+    /// `None` if the key is to be omitted.
+    ///
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        let value = self.to_value_tokens();
+        Some(quote!(#id = #value))
+    }
+}
+
+/// Join `entries` with commas, e.g. `key = 1, list(a, b)`.
+///
+pub fn join_entries<I>(entries: I) -> TokenStream
+where
+    I: IntoIterator<Item = TokenStream>,
+{
+    let entries = entries.into_iter();
+    quote!(#(#entries),*)
+}
+
+/// Write `entries` as a list under `ident`, e.g. `file(path = "x")`.
+///
+pub fn list_tokens(ident: &Ident, entries: TokenStream) -> TokenStream {
+    quote!(#ident(#entries))
+}
+
+macro_rules! impl_integers {
+    ($( ($t:ty, $f:ident) ),*) => {
+        $(impl ToValueTokens for $t {
+            fn to_value_tokens(&self) -> TokenStream {
+                Literal::$f(*self).into_token_stream()
+            }
+        })*
+    };
+}
+
+impl_integers!(
+    (usize, usize_unsuffixed),
+    (u128, u128_unsuffixed),
+    (u64, u64_unsuffixed),
+    (u32, u32_unsuffixed),
+    (u16, u16_unsuffixed),
+    (u8, u8_unsuffixed),
+    (isize, isize_unsuffixed),
+    (i128, i128_unsuffixed),
+    (i64, i64_unsuffixed),
+    (i32, i32_unsuffixed),
+    (i16, i16_unsuffixed),
+    (i8, i8_unsuffixed)
+);
+
+macro_rules! impl_floats {
+    ($( ($t:ty, $f:ident) ),*) => {
+        $(impl ToValueTokens for $t {
+            fn to_value_tokens(&self) -> TokenStream {
+                if self.is_nan() {
+                    quote!(nan)
+                } else if self.is_infinite() && self.is_sign_negative() {
+                    quote!(-inf)
+                } else if self.is_infinite() {
+                    quote!(inf)
+                } else {
+                    Literal::$f(*self).into_token_stream()
+                }
+            }
+        })*
+    };
+}
+
+impl_floats!((f64, f64_unsuffixed), (f32, f32_unsuffixed));
+
+impl ToValueTokens for bool {
+    fn to_value_tokens(&self) -> TokenStream {
+        LitBool::new(*self, Span::call_site()).into_token_stream()
+    }
+
+    /// A set flag is written as just its key, an unset flag is omitted.
+    ///
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        self.then(|| id.to_token_stream())
+    }
+}
+
+impl ToValueTokens for Flag {
+    fn to_value_tokens(&self) -> TokenStream {
+        self.value().to_value_tokens()
+    }
+
+    /// A flag is written the way it was given, an omitted flag is omitted.
+    ///
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        match self {
+            Self::Absent => None,
+            Self::Set(_) => Some(id.to_token_stream()),
+            Self::Explicit(value, _) => Some(quote!(#id = #value)),
+            Self::Negated(_) => Some(quote!(!#id)),
+        }
+    }
+}
+
+impl ToValueTokens for Count {
+    fn to_value_tokens(&self) -> TokenStream {
+        self.0.to_value_tokens()
+    }
+
+    /// The key is repeated as many times as it was counted (e.g. `key, key`),
+    /// or omitted for a count of zero.
+    ///
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        (self.0 > 0).then(|| join_entries(std::iter::repeat_n(id.to_token_stream(), self.0)))
+    }
+}
+
+impl<T> ToValueTokens for Repeated<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        self.0.to_value_tokens()
+    }
+
+    /// The key is repeated for every value (e.g. `key(a = 1), key(a = 2)`), or
+    /// omitted without any.
+    ///
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        let entries = self
+            .iter()
+            .filter_map(|value| value.to_key_tokens(id))
+            .collect::<Vec<_>>();
+
+        (!entries.is_empty()).then(|| join_entries(entries))
+    }
+}
+
+impl ToValueTokens for String {
+    fn to_value_tokens(&self) -> TokenStream {
+        LitStr::new(self, Span::call_site()).into_token_stream()
+    }
+}
+
+impl ToValueTokens for Cow<'static, str> {
+    fn to_value_tokens(&self) -> TokenStream {
+        LitStr::new(self, Span::call_site()).into_token_stream()
+    }
+}
+
+impl ToValueTokens for PathBuf {
+    fn to_value_tokens(&self) -> TokenStream {
+        LitStr::new(&self.to_string_lossy(), Span::call_site()).into_token_stream()
+    }
+}
+
+/// Whole milliseconds are written as an integer, other durations as a string
+/// in nanoseconds (e.g. `"1500ns"`).
+///
+#[cfg(feature = "duration")]
+impl ToValueTokens for Duration {
+    fn to_value_tokens(&self) -> TokenStream {
+        if self.subsec_nanos().is_multiple_of(1_000_000) {
+            Literal::u128_unsuffixed(self.as_millis()).into_token_stream()
+        } else {
+            let nanos = format!("{}ns", self.as_nanos());
+            LitStr::new(&nanos, Span::call_site()).into_token_stream()
+        }
+    }
+}
+
+/// An identifier is written as just itself, as it is parsed from a bare key.
+///
+impl ToValueTokens for Ident {
+    fn to_value_tokens(&self) -> TokenStream {
+        self.to_token_stream()
+    }
+
+    fn to_key_tokens(&self, _id: &Ident) -> Option<TokenStream> {
+        Some(self.to_token_stream())
+    }
+}
+
+macro_rules! impl_literals {
+    ($( $t:ty ),*) => {
+        $(impl ToValueTokens for $t {
+            fn to_value_tokens(&self) -> TokenStream {
+                self.to_token_stream()
+            }
+        })*
+    };
+}
+
+impl_literals!(Lit, LitBool, LitFloat, LitInt, LitStr);
+
+macro_rules! impl_string_literals {
+    ($( $t:ty ),*) => {
+        $(impl ToValueTokens for $t {
+            fn to_value_tokens(&self) -> TokenStream {
+                LitStr::new(&self.to_string(), Span::call_site()).into_token_stream()
+            }
+        })*
+    };
+}
+
+impl_string_literals!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+macro_rules! impl_syn_string_literals {
+    ($( $t:ty ),*) => {
+        $(impl ToValueTokens for $t {
+            fn to_value_tokens(&self) -> TokenStream {
+                syn_string_literal(self)
+            }
+        })*
+    };
+}
+
+impl_syn_string_literals!(Type, Path, WherePredicate, TypeParamBound, syn::Expr);
+
+#[cfg(feature = "full")]
+impl ToValueTokens for syn::ExprClosure {
+    fn to_value_tokens(&self) -> TokenStream {
+        self.to_token_stream()
+    }
+}
+
+fn syn_string_literal<T: ToTokens>(value: &T) -> TokenStream {
+    LitStr::new(&value.to_token_stream().to_string(), Span::call_site()).into_token_stream()
+}
+
+macro_rules! impl_tuples {
+    ($( ($( ($t:ident, $v:tt) ),+) ),*) => {
+        $(impl<$( $t ),+> ToValueTokens for ($( $t, )+)
+        where
+            $( $t: ToValueTokens, )+
+        {
+            fn to_value_tokens(&self) -> TokenStream {
+                let values = join_entries([$( self.$v.to_value_tokens() ),+]);
+                quote!((#values))
+            }
+
+            fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+                let values = join_entries([$( self.$v.to_value_tokens() ),+]);
+                Some(quote!(#id(#values)))
+            }
+        })*
+    };
+}
+
+impl_tuples!(
+    ((A, 0), (B, 1)),
+    ((A, 0), (B, 1), (C, 2)),
+    ((A, 0), (B, 1), (C, 2), (D, 3)),
+    ((A, 0), (B, 1), (C, 2), (D, 3), (E, 4)),
+    ((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5)),
+    ((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5), (G, 6)),
+    (
+        (A, 0),
+        (B, 1),
+        (C, 2),
+        (D, 3),
+        (E, 4),
+        (F, 5),
+        (G, 6),
+        (H, 7)
+    )
+);
+
+impl<T> ToValueTokens for Vec<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        self.as_slice().to_value_tokens()
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        self.as_slice().to_key_tokens(id)
+    }
+}
+
+impl<T, const N: usize> ToValueTokens for [T; N]
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        self.as_slice().to_value_tokens()
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        self.as_slice().to_key_tokens(id)
+    }
+}
+
+impl<T> ToValueTokens for [T]
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        let values = join_entries(self.iter().map(T::to_value_tokens));
+        quote!((#values))
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        let values = join_entries(self.iter().map(T::to_value_tokens));
+        Some(quote!(#id(#values)))
+    }
+}
+
+impl<T> ToValueTokens for Range<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        let start = self.start.to_value_tokens();
+        let end = self.end.to_value_tokens();
+        quote!(#start..#end)
+    }
+}
+
+impl<T> ToValueTokens for RangeInclusive<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        let start = self.start().to_value_tokens();
+        let end = self.end().to_value_tokens();
+        quote!(#start..=#end)
+    }
+}
+
+impl<T> ToValueTokens for Option<T>
+where
+    T: ToValueTokens,
+{
+    /// A missing value has no tokens of its own, it is only written as a
+    /// bare key (see [to_key_tokens](Self::to_key_tokens)).
+    ///
+    fn to_value_tokens(&self) -> TokenStream {
+        self.as_ref().map(T::to_value_tokens).unwrap_or_default()
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        match self {
+            Some(value) => value.to_key_tokens(id),
+            None => Some(id.to_token_stream()),
+        }
+    }
+}
+
+impl<T> ToValueTokens for Rc<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        T::to_value_tokens(self)
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        T::to_key_tokens(self, id)
+    }
+}
+
+impl<T> ToValueTokens for Arc<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        T::to_value_tokens(self)
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        T::to_key_tokens(self, id)
+    }
+}
+
+macro_rules! impl_boxed {
+    ($( $t:ty ),*) => {
+        $(impl ToValueTokens for Box<$t> {
+            fn to_value_tokens(&self) -> TokenStream {
+                <$t>::to_value_tokens(self)
+            }
+
+            fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+                <$t>::to_key_tokens(self, id)
+            }
+        })*
+    };
+}
+
+impl_boxed!(
+    usize,
+    u128,
+    u64,
+    u32,
+    u16,
+    u8,
+    isize,
+    i128,
+    i64,
+    i32,
+    i16,
+    i8,
+    f64,
+    f32,
+    bool,
+    String,
+    Cow<'static, str>,
+    PathBuf,
+    Ident,
+    Lit,
+    LitBool,
+    LitFloat,
+    LitInt,
+    LitStr,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
+    Type,
+    Path,
+    WherePredicate,
+    TypeParamBound,
+    syn::Expr
+);
+
+#[cfg(feature = "duration")]
+impl_boxed!(Duration);
+
+impl<T> ToValueTokens for FromStrValue<T>
+where
+    T: Display,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        LitStr::new(&self.to_string(), Span::call_site()).into_token_stream()
+    }
+}
+
+impl<T> ToValueTokens for ParseStrValue<T>
+where
+    T: ToTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        syn_string_literal(&**self)
+    }
+}
+
+impl<T> ToValueTokens for SpannedValue<T>
+where
+    T: ToValueTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        T::to_value_tokens(self)
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        T::to_key_tokens(self, id)
+    }
+}
+
+impl ToValueTokens for Predicate {
+    fn to_value_tokens(&self) -> TokenStream {
+        match self {
+            Self::Any(predicates) => {
+                let predicates = join_entries(predicates.iter().map(Self::to_value_tokens));
+                quote!(any(#predicates))
+            }
+            Self::All(predicates) => {
+                let predicates = join_entries(predicates.iter().map(Self::to_value_tokens));
+                quote!(all(#predicates))
+            }
+            Self::Not(predicate) => {
+                let predicate = predicate.to_value_tokens();
+                quote!(not(#predicate))
+            }
+            Self::Flag(ident) => ident.to_token_stream(),
+            Self::KeyValue(key, value) => {
+                let value = LitStr::new(value, Span::call_site());
+                quote!(#key = #value)
+            }
+        }
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        let predicate = self.to_value_tokens();
+        Some(quote!(#id(#predicate)))
+    }
+}
+
+/// A nested attribute is written as a list of its values, e.g. `key(a = 1)`.
+///
+impl<T> ToValueTokens for T
+where
+    T: Attribute + ToTokens,
+{
+    fn to_value_tokens(&self) -> TokenStream {
+        quote!((#self))
+    }
+
+    fn to_key_tokens(&self, id: &Ident) -> Option<TokenStream> {
+        Some(quote!(#id(#self)))
+    }
+}
@@ -1,49 +1,47 @@
-use syn::{Error, Ident, Lit, LitBool, LitFloat, LitInt, LitStr, Result};
+#[cfg(feature = "duration")]
+use std::time::Duration;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    ops::{self, Deref, DerefMut},
+    path::PathBuf,
+    rc::Rc,
+    str::FromStr,
+    sync::Arc,
+};
+
+use proc_macro2::Span;
+use syn::{
+    Error, Ident, Lit, LitBool, LitFloat, LitInt, LitStr, Path, Type, TypeParamBound,
+    WherePredicate, parse::Parse,
+};
 
 use crate::{
-    ast::{Expr, List, Value},
-    errors::ErrorsExt,
+    ast::{Expr, List, Neg, Not, Tuple, Value, Values},
+    dym::{Suggestions, did_you_mean},
+    errors::{self, Accumulator, Result},
+    schema::Kind,
 };
 
 macro_rules! impl_integers {
     ($( $t:ty ),*) => {
         $(impl ParseValue for $t {
+            const KIND: Kind = Kind::Integer;
+
             fn parse(value: Value) -> Result<Self> {
                 match value {
-                    Value::Expr(Expr { value, .. }) => match value.as_ref() {
+                    Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
                         Value::Lit(Lit::Int(lit_int)) => Ok(lit_int.base10_parse()?),
-                        value => Err(format_error(value, "integer")),
+                        value => Err(format_value_error(&ident, value, "integer")),
                     },
                     Value::Lit(Lit::Int(lit_int)) => Ok(lit_int.base10_parse()?),
                     value => Err(format_error(&value, "integer")),
                 }
             }
-        }
-
-        impl ParseValue for Vec<$t> {
-            fn parse(value: Value) -> Result<Self> {
-                match value {
-                    Value::List(List { values, .. }) => {
-                        let mut errors = vec![];
-                        let mut lits = vec![];
-
-                        for value in values {
-                            match value {
-                                Value::Lit(Lit::Float(lit_float)) => lits.push(lit_float.base10_parse()?),
-                                Value::Lit(Lit::Int(lit_int)) => lits.push(lit_int.base10_parse()?),
-                                value => errors.push(format_error(&value, "decimal")),
-                            }
-                        }
-
-                        if let Some(error) = errors.combine() {
-                            return Err(error);
-                        }
-
-                        Ok(lits)
-                    }
-                    value => Err(format_error(&value, "list of decimals")),
-                }
-            }
         })*
     };
 }
@@ -55,42 +53,29 @@ impl_integers!(
 macro_rules! impl_floats {
     ($( $t:ty ),*) => {
         $(impl ParseValue for $t {
+            const KIND: Kind = Kind::Float;
+
             fn parse(value: Value) -> Result<Self> {
                 match value {
-                    Value::Expr(Expr { value, .. }) => match value.as_ref() {
+                    Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
                         Value::Lit(Lit::Float(lit_float)) => Ok(lit_float.base10_parse()?),
                         Value::Lit(Lit::Int(lit_int)) => Ok(lit_int.base10_parse()?),
-                        value => Err(format_error(value, "decimal")),
+                        value => match parse_special_float(value) {
+                            Some(float) => Ok(float as $t),
+                            None => Err(format_value_error_suggesting(
+                                &ident,
+                                value,
+                                "decimal",
+                                SPECIAL_FLOATS,
+                            )),
+                        },
                     },
                     Value::Lit(Lit::Float(lit_float)) => Ok(lit_float.base10_parse()?),
                     Value::Lit(Lit::Int(lit_int)) => Ok(lit_int.base10_parse()?),
-                    value => Err(format_error(&value, "decimal")),
-                }
-            }
-        }
-
-        impl ParseValue for Vec<$t> {
-            fn parse(value: Value) -> Result<Self> {
-                match value {
-                    Value::List(List { values, .. }) => {
-                        let mut errors = vec![];
-                        let mut lits = vec![];
-
-                        for value in values {
-                            match value {
-                                Value::Lit(Lit::Float(lit_float)) => lits.push(lit_float.base10_parse()?),
-                                Value::Lit(Lit::Int(lit_int)) => lits.push(lit_int.base10_parse()?),
-                                value => errors.push(format_error(&value, "decimal")),
-                            }
-                        }
-
-                        if let Some(error) = errors.combine() {
-                            return Err(error);
-                        }
-
-                        Ok(lits)
-                    }
-                    value => Err(format_error(&value, "list of decimals")),
+                    value => match parse_special_float(&value) {
+                        Some(float) => Ok(float as $t),
+                        None => Err(format_error_suggesting(&value, "decimal", SPECIAL_FLOATS)),
+                    },
                 }
             }
         })*
@@ -100,229 +85,1710 @@ macro_rules! impl_floats {
 impl_floats!(f64, f32);
 
 impl ParseValue for bool {
+    const KIND: Kind = Kind::Bool;
+
     fn parse(value: Value) -> Result<Self> {
         match value {
-            Value::Expr(Expr { value, .. }) => match value.as_ref() {
+            Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
                 Value::Lit(Lit::Bool(lit_bool)) => Ok(lit_bool.value()),
-                value => Err(format_error(value, "boolean (`true`, `false`)")),
+                value => Err(format_value_error_suggesting(
+                    &ident,
+                    value,
+                    "boolean (`true`, `false`)",
+                    &["true", "false"],
+                )),
             },
+            Value::Lit(Lit::Bool(lit_bool)) => Ok(lit_bool.value()),
             Value::Ident(_) => Ok(true),
+            Value::Not(_) => Ok(false),
             value => Err(format_error(&value, "boolean expression")),
         }
     }
 }
 
 impl ParseValue for String {
+    const KIND: Kind = Kind::String;
+
     fn parse(value: Value) -> Result<Self> {
         match value {
-            Value::Expr(Expr { value, .. }) => match value.as_ref() {
+            Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
                 Value::Lit(Lit::Str(lit_str)) => Ok(lit_str.value()),
-                value => Err(format_error(value, "string literal")),
+                value => Err(format_value_error(&ident, value, "string literal")),
             },
+            Value::Lit(Lit::Str(lit_str)) => Ok(lit_str.value()),
             value => Err(format_error(&value, "string literal")),
         }
     }
 }
 
-impl ParseValue for Vec<String> {
+impl ParseValue for Cow<'static, str> {
+    const KIND: Kind = Kind::String;
+
     fn parse(value: Value) -> Result<Self> {
-        match value {
-            Value::List(List { values, .. }) => {
-                let mut errors = vec![];
-                let mut strings = vec![];
-
-                for value in values {
-                    match value {
-                        Value::Lit(Lit::Str(lit_str)) => strings.push(lit_str.value()),
-                        value => errors.push(format_error(&value, "string literal")),
-                    }
-                }
+        String::parse(value).map(Cow::Owned)
+    }
+}
 
-                if let Some(error) = errors.combine() {
-                    return Err(error);
-                }
+impl ParseValue for PathBuf {
+    const KIND: Kind = Kind::StringOf("path");
 
-                Ok(strings)
-            }
-            value => Err(format_error(&value, "list of string literals")),
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
+                Value::Lit(Lit::Str(lit_str)) => Ok(PathBuf::from(lit_str.value())),
+                value => Err(format_value_error(&ident, value, "path string literal")),
+            },
+            Value::Lit(Lit::Str(lit_str)) => Ok(PathBuf::from(lit_str.value())),
+            value => Err(format_error(&value, "path string literal")),
         }
     }
 }
 
-impl ParseValue for Ident {
+#[cfg(feature = "duration")]
+impl ParseValue for Duration {
+    const KIND: Kind = Kind::Duration;
+
     fn parse(value: Value) -> Result<Self> {
         match value {
-            Value::Ident(ident) => Ok(ident),
-            value => Err(format_error(&value, "identifier")),
+            Value::Expr(Expr { ident, value, .. }) => match *value {
+                value @ Value::Lit(_) => Self::parse(value),
+                value => Err(format_value_error(
+                    &ident,
+                    &value,
+                    "duration (`\"30s\"`, milliseconds or seconds)",
+                )),
+            },
+            Value::Lit(Lit::Str(lit_str)) => Ok(parse_duration_str(&lit_str)?),
+            Value::Lit(Lit::Int(lit_int)) if lit_int.base10_digits().starts_with('-') => {
+                Err(negative_duration(lit_int.span()).into())
+            }
+            Value::Lit(Lit::Int(lit_int)) => Ok(Duration::from_millis(lit_int.base10_parse()?)),
+            Value::Lit(Lit::Float(lit_float)) if lit_float.base10_digits().starts_with('-') => {
+                Err(negative_duration(lit_float.span()).into())
+            }
+            Value::Lit(Lit::Float(lit_float)) => {
+                Ok(Duration::try_from_secs_f64(lit_float.base10_parse()?)
+                    .map_err(|error| Error::new(lit_float.span(), error))?)
+            }
+            value => Err(format_error(
+                &value,
+                "duration (`\"30s\"`, milliseconds or seconds)",
+            )),
         }
     }
 }
 
-impl ParseValue for Vec<Ident> {
-    fn parse(value: Value) -> Result<Self> {
-        match value {
-            Value::List(List { values, .. }) => {
-                let mut errors = vec![];
-                let mut idents = vec![];
-
-                for value in values {
-                    match value {
-                        Value::Ident(ident) => idents.push(ident),
-                        value => errors.push(format_error(&value, "identifier")),
-                    }
-                }
+/// Parse a duration string consisting of a number and a unit suffix (e.g.
+/// `"30s"`, `"1.5h"` or `"250 ms"`).
+///
+/// Integral numbers are converted without loss of precision; fractional
+/// numbers go through `f64` seconds.
+///
+#[cfg(feature = "duration")]
+fn parse_duration_str(lit_str: &LitStr) -> syn::Result<Duration> {
+    let string = lit_str.value();
+    let string = string.trim();
 
-                if let Some(error) = errors.combine() {
-                    return Err(error);
-                }
+    if string.starts_with('-') {
+        return Err(negative_duration(lit_str.span()));
+    }
 
-                Ok(idents)
-            }
-            value => Err(format_error(&value, "list of identifiers")),
+    let unit_start = string
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(string.len());
+    let (number, unit) = string.split_at(unit_start);
+
+    let nanos_per_unit: u128 = match unit.trim() {
+        "ns" => 1,
+        "us" | "µs" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        "m" | "min" => 60_000_000_000,
+        "h" => 3_600_000_000_000,
+        "d" => 86_400_000_000_000,
+        "" => {
+            return Err(Error::new(
+                lit_str.span(),
+                "missing duration unit, expected one of `ns`, `us`, `ms`, `s`, `m`, `h`, `d`",
+            ));
+        }
+        unit => {
+            return Err(Error::new(
+                lit_str.span(),
+                format!(
+                    "unknown duration unit `{}`, expected one of `ns`, `us`, `ms`, `s`, `m`, `h`, `d`",
+                    unit
+                ),
+            ));
+        }
+    };
+
+    let invalid_number = || Error::new(lit_str.span(), format!("invalid duration `{}`", string));
+    let overflow = || Error::new(lit_str.span(), "duration is too large");
+
+    if let Ok(number) = number.parse::<u128>() {
+        let nanos = number.checked_mul(nanos_per_unit).ok_or_else(overflow)?;
+        let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| overflow())?;
+        return Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32));
+    }
+
+    let number = number.parse::<f64>().map_err(|_| invalid_number())?;
+    Duration::try_from_secs_f64(number * nanos_per_unit as f64 / 1e9).map_err(|_| overflow())
+}
+
+#[cfg(feature = "duration")]
+fn negative_duration(span: Span) -> Error {
+    Error::new(span, "a duration cannot be negative")
+}
+
+impl ParseValue for Ident {
+    const KIND: Kind = Kind::Ident;
+
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::Ident(ident) => Ok(ident),
+            value => Err(format_error(&value, "identifier")),
         }
     }
 }
 
 impl ParseValue for Lit {
+    const KIND: Kind = Kind::Literal;
+
     fn parse(value: Value) -> Result<Self> {
         match value {
-            Value::Expr(Expr { value, .. }) => match value.as_ref() {
-                Value::Lit(lit) => Ok(lit.clone()),
-                value => Err(format_error(value, "literal")),
+            Value::Expr(Expr { ident, value, .. }) => match *value {
+                Value::Lit(lit) => Ok(lit),
+                value => Err(format_value_error(&ident, &value, "literal")),
             },
+            Value::Lit(lit) => Ok(lit),
             value => Err(format_error(&value, "literal expression")),
         }
     }
 }
 
-impl ParseValue for Vec<Lit> {
-    fn parse(value: Value) -> Result<Self> {
-        match value {
-            Value::List(List { values, .. }) => {
-                let mut errors = vec![];
-                let mut lits = vec![];
-
-                for value in values {
-                    match value {
-                        Value::Lit(lit) => lits.push(lit),
-                        value => errors.push(format_error(&value, "literal")),
-                    }
-                }
+macro_rules! impl_lit_variants {
+    ($( ($t:ty, $e:path, $k:expr, $x:literal) ),*) => {
+        $(impl ParseValue for $t {
+            const KIND: Kind = $k;
 
-                if let Some(error) = errors.combine() {
-                    return Err(error);
+            fn parse(value: Value) -> Result<Self> {
+                match value {
+                    Value::Expr(Expr { ident, value, .. }) => match *value {
+                        Value::Lit($e(lit)) => Ok(lit),
+                        value => Err(format_value_error(&ident, &value, $x)),
+                    },
+                    Value::Lit($e(lit)) => Ok(lit),
+                    value => Err(format_error(&value, concat!($x, " expression"))),
                 }
-
-                Ok(lits)
             }
-            value => Err(format_error(&value, "list of literals")),
-        }
+        })*
     }
 }
 
-macro_rules! impl_lit_variants {
-    ($( ($t:ty, $e:path, $x:literal, $xp:literal) ),*) => {
+impl_lit_variants!(
+    (LitBool, Lit::Bool, Kind::Bool, "boolean"),
+    (LitFloat, Lit::Float, Kind::Float, "decimal"),
+    (LitInt, Lit::Int, Kind::Integer, "integer"),
+    (LitStr, Lit::Str, Kind::String, "string literal")
+);
+
+macro_rules! impl_from_str_literals {
+    ($( ($t:ty, $x:literal) ),*) => {
         $(impl ParseValue for $t {
+            const KIND: Kind = Kind::StringOf($x);
+
             fn parse(value: Value) -> Result<Self> {
                 match value {
-                    Value::Expr(Expr { value, .. }) => match value.as_ref() {
-                        Value::Lit($e(lit)) => Ok(lit.clone()),
-                        value => Err(format_error(value, $x)),
+                    Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
+                        Value::Lit(Lit::Str(lit_str)) => parse_str_literal(lit_str),
+                        value => Err(format_value_error(
+                            &ident,
+                            value,
+                            concat!($x, " string literal"),
+                        )),
                     },
-                    value => Err(format_error(&value, concat!($x, " expression"))),
+                    Value::Lit(Lit::Str(lit_str)) => parse_str_literal(&lit_str),
+                    value => Err(format_error(&value, concat!($x, " string literal"))),
                 }
             }
-        }
+        })*
+    }
+}
+
+impl_from_str_literals!(
+    (IpAddr, "IP address"),
+    (Ipv4Addr, "IPv4 address"),
+    (Ipv6Addr, "IPv6 address"),
+    (SocketAddr, "socket address"),
+    (SocketAddrV4, "IPv4 socket address"),
+    (SocketAddrV6, "IPv6 socket address")
+);
+
+macro_rules! impl_syn_str_literals {
+    ($( ($t:ty, $x:literal) ),*) => {
+        $(impl ParseValue for $t {
+            const KIND: Kind = Kind::StringOf($x);
 
-        impl ParseValue for Vec<$t> {
             fn parse(value: Value) -> Result<Self> {
                 match value {
-                    Value::List(List { values, .. }) => {
-                        let mut errors = vec![];
-                        let mut lits = vec![];
-
-                        for value in values {
-                            match value {
-                                Value::Lit($e(lit)) => lits.push(lit),
-                                value => errors.push(format_error(&value, $x)),
-                            }
-                        }
-
-                        if let Some(error) = errors.combine() {
-                            return Err(error);
-                        }
-
-                        Ok(lits)
-                    }
-                    value => Err(format_error(&value, concat!("list of ", $xp))),
+                    Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
+                        Value::Lit(Lit::Str(lit_str)) => Ok(lit_str.parse()?),
+                        value => Err(format_value_error(
+                            &ident,
+                            value,
+                            concat!($x, " string literal"),
+                        )),
+                    },
+                    Value::Lit(Lit::Str(lit_str)) => Ok(lit_str.parse()?),
+                    value => Err(format_error(&value, concat!($x, " string literal"))),
                 }
             }
         })*
     }
 }
 
-impl_lit_variants!(
-    (LitBool, Lit::Bool, "boolean", "booleans"),
-    (LitFloat, Lit::Float, "decimal", "decimals"),
-    (LitInt, Lit::Int, "integer", "integers"),
-    (LitStr, Lit::Str, "string literal", "string literals")
+impl_syn_str_literals!(
+    (Type, "type"),
+    (Path, "path"),
+    (WherePredicate, "where predicate"),
+    (TypeParamBound, "trait bound"),
+    (syn::Expr, "expression")
 );
 
-/// Create a type conversion error.
+/// A closure written inline (e.g. `map = |x| x + 1`), for macros generating
+/// callback glue. Requires the `full` feature.
 ///
-#[inline]
-pub fn format_error(value: &Value, expect: &str) -> Error {
-    Error::new(
-        value.span(),
-        match value.identifier() {
-            Some(id) => format!("expected {} for `{}`", expect, id),
-            None => format!("expected {}", expect),
-        },
-    )
+#[cfg(feature = "full")]
+impl ParseValue for syn::ExprClosure {
+    const KIND: Kind = Kind::Closure;
+
+    fn parse(value: Value) -> Result<Self> {
+        let closure = match &value {
+            Value::Expr(Expr { value, .. }) => match value.as_ref() {
+                Value::Closure(closure) => Some(closure),
+                _ => None,
+            },
+            Value::Closure(closure) => Some(closure),
+            _ => None,
+        };
+
+        match closure {
+            Some(closure) => Ok(syn::parse2(closure.tokens.clone())?),
+            None => Err(format_error(&value, "closure")),
+        }
+    }
 }
 
-pub trait ParseValue: Sized {
-    fn parse(value: Value) -> Result<Self>;
+macro_rules! impl_tuples {
+    ($( ($n:literal; $( ($t:ident, $v:ident) ),+) ),*) => {
+        $(impl<$( $t ),+> ParseValue for ($( $t, )+)
+        where
+            $( $t: ParseValue, )+
+        {
+            const KIND: Kind = Kind::Tuple(&[$( $t::KIND ),+]);
+
+            fn parse(value: Value) -> Result<Self> {
+                let (span, id, values) = match value {
+                    Value::List(list) => (list.span(), Some(list.identifier()), list.values),
+                    Value::Tuple(tuple) => (tuple.span(), None, tuple.values),
+                    value => return Err(format_error(&value, concat!("list of ", $n, " values"))),
+                };
+
+                let values = values.into_iter().collect::<Vec<_>>();
+
+                if values.len() != $n {
+                    return Err(format_arity_error(span, id, $n, values.len()));
+                }
+
+                let mut errors = Accumulator::new();
+                let mut values = values.into_iter();
+
+                $(let $v = errors.handle(
+                    <$t as ParseValue>::parse(values.next().expect("arity has been confirmed"))
+                );)+
+
+                errors.finish()?;
+
+                Ok(($( $v.expect("values existence has been confirmed"), )+))
+            }
+        })*
+    };
 }
 
-pub trait ParseValueExt: Sized {
-    fn parse<T: ParseValue>(self) -> Result<T>;
+impl_tuples!(
+    (2; (A, a), (B, b)),
+    (3; (A, a), (B, b), (C, c)),
+    (4; (A, a), (B, b), (C, c), (D, d)),
+    (5; (A, a), (B, b), (C, c), (D, d), (E, e)),
+    (6; (A, a), (B, b), (C, c), (D, d), (E, e), (F, f)),
+    (7; (A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g)),
+    (8; (A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h))
+);
+
+impl<T> ParseValue for Vec<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = Kind::List(&T::KIND);
+
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::List(List { values, .. }) | Value::Tuple(Tuple { values, .. }) => {
+                T::parse_list(values)
+            }
+            value => Err(format_error(&value, "list of values")),
+        }
+    }
+
+    fn append(&mut self, _id: &str, value: Value) -> Result<()> {
+        self.extend(Self::parse(value)?);
+        Ok(())
+    }
 }
 
-impl ParseValueExt for Value {
-    fn parse<T: ParseValue>(self) -> Result<T> {
-        T::parse(self)
+impl<T, const N: usize> ParseValue for [T; N]
+where
+    T: ParseValue,
+{
+    const KIND: Kind = Kind::Array(&T::KIND, N);
+
+    fn parse(value: Value) -> Result<Self> {
+        let (span, id, values) = match value {
+            Value::List(list) => (list.span(), Some(list.identifier()), list.values),
+            Value::Tuple(tuple) => (tuple.span(), None, tuple.values),
+            value => return Err(format_error(&value, &format!("list of {} values", N))),
+        };
+
+        let values = values.into_iter().collect::<Vec<_>>();
+
+        if values.len() != N {
+            return Err(format_arity_error(span, id, N, values.len()));
+        }
+
+        let mut errors = Accumulator::new();
+        let mut parsed = Vec::with_capacity(N);
+
+        for value in values {
+            parsed.extend(errors.handle(T::parse(value)));
+        }
+
+        errors.finish()?;
+
+        match parsed.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("arity has been confirmed"),
+        }
     }
 }
 
-pub trait ValueStorageExt: Sized {
-    fn insert_value(&mut self, id: &str, value: Value, errors: &mut Vec<Error>);
-    fn append_value(&mut self, id: &str, value: Value, errors: &mut Vec<Error>);
+/// A range between two literals that leaves out its end, e.g. `bits = 0..8`.
+///
+impl<T> ParseValue for ops::Range<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = Kind::Range(&T::KIND, false);
+
+    fn parse(value: Value) -> Result<Self> {
+        let (start, end) = parse_range(value, false)?;
+        Ok(start..end)
+    }
 }
 
-impl<T> ValueStorageExt for Option<T>
+/// A range between two literals that includes its end, e.g. `bits = 0..=7`.
+///
+impl<T> ParseValue for ops::RangeInclusive<T>
 where
     T: ParseValue,
 {
-    fn insert_value(&mut self, id: &str, value: Value, errors: &mut Vec<Error>) {
-        if !self.is_some() {
-            match value.parse() {
-                Ok(value) => {
-                    self.replace(value);
-                }
-                Err(error) => {
-                    errors.push(error);
-                }
+    const KIND: Kind = Kind::Range(&T::KIND, true);
+
+    fn parse(value: Value) -> Result<Self> {
+        let (start, end) = parse_range(value, true)?;
+        Ok(start..=end)
+    }
+}
+
+/// Parse both ends of a range, which only matches if it is `inclusive` (i.e.
+/// written `a..=b` rather than `a..b`) when asked to be.
+///
+fn parse_range<T>(value: Value, inclusive: bool) -> Result<(T, T)>
+where
+    T: ParseValue,
+{
+    let range = match &value {
+        Value::Expr(Expr { value, .. }) => match value.as_ref() {
+            Value::Range(range) => Some(range),
+            _ => None,
+        },
+        Value::Range(range) => Some(range),
+        _ => None,
+    };
+
+    let Some(range) = range.filter(|range| range.is_inclusive() == inclusive) else {
+        return Err(format_error(
+            &value,
+            match inclusive {
+                true => "inclusive range (`a..=b`)",
+                false => "range (`a..b`)",
+            },
+        ));
+    };
+
+    let mut errors = Accumulator::new();
+    let start = errors.handle(T::parse(Value::Lit(range.start.clone())));
+    let end = errors.handle(T::parse(Value::Lit(range.end.clone())));
+
+    errors.finish()?;
+
+    Ok((
+        start.expect("start has been parsed"),
+        end.expect("end has been parsed"),
+    ))
+}
+
+/// A value that is always given once parsed (e.g. the entries of a
+/// `Vec<Option<T>>`), so that it can be set to `None` programmatically.
+///
+/// A key without a value (e.g. `default` rather than `default = "x"`) is only
+/// told apart by derived fields of type `Option<Option<T>>`, see
+/// [OptionalStorageExt].
+///
+impl<T> ParseValue for Option<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = T::KIND;
+
+    fn parse(value: Value) -> Result<Self> {
+        T::parse(value).map(Some)
+    }
+}
+
+impl<T> ParseValue for Rc<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = T::KIND;
+
+    fn parse(value: Value) -> Result<Self> {
+        T::parse(value).map(Rc::new)
+    }
+}
+
+impl<T> ParseValue for Arc<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = T::KIND;
+
+    fn parse(value: Value) -> Result<Self> {
+        T::parse(value).map(Arc::new)
+    }
+}
+
+/// Boxed values parse like the value they contain (e.g. a `Box<syn::Expr>`).
+///
+/// A generic `ParseValue for Box<T>` would overlap with the implementation for
+/// boxed attributes, so only the values supported out of the box can be boxed.
+///
+macro_rules! impl_boxed {
+    ($( $t:ty ),*) => {
+        $(impl ParseValue for Box<$t> {
+            const KIND: Kind = <$t as ParseValue>::KIND;
+
+            fn parse(value: Value) -> Result<Self> {
+                <$t as ParseValue>::parse(value).map(Box::new)
             }
-        } else {
-            errors.push(Error::new(value.span(), format!("duplicate key `{}`", id)));
+        })*
+    };
+}
+
+impl_boxed!(
+    usize,
+    u128,
+    u64,
+    u32,
+    u16,
+    u8,
+    isize,
+    i128,
+    i64,
+    i32,
+    i16,
+    i8,
+    f64,
+    f32,
+    bool,
+    String,
+    Cow<'static, str>,
+    PathBuf,
+    Ident,
+    Lit,
+    LitBool,
+    LitFloat,
+    LitInt,
+    LitStr,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
+    Type,
+    Path,
+    WherePredicate,
+    TypeParamBound,
+    syn::Expr
+);
+
+#[cfg(feature = "duration")]
+impl_boxed!(Duration);
+
+/// A value parsed as `I` and then converted into a `T` through [TryFrom], with
+/// conversion errors reported at the span of the value (e.g. `port = 0` for a
+/// `Port` that is `TryFrom<u16>`).
+///
+/// Derived attributes store fields marked `#[squattr(try_from = "I")]` like
+/// this while parsing.
+///
+pub struct TryFromValue<I, T>(pub T, PhantomData<fn() -> I>);
+
+impl<I, T> TryFromValue<I, T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<I, T> Deref for TryFromValue<I, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<I, T> DerefMut for TryFromValue<I, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<I, T> ParseValue for TryFromValue<I, T>
+where
+    I: ParseValue,
+    T: TryFrom<I>,
+    T::Error: Display,
+{
+    const KIND: Kind = I::KIND;
+
+    fn parse(value: Value) -> Result<Self> {
+        let span = value.value_span();
+
+        match T::try_from(I::parse(value)?) {
+            Ok(value) => Ok(Self(value, PhantomData)),
+            Err(error) => Err(Error::new(span, error).into()),
         }
     }
+}
 
-    fn append_value(&mut self, id: &str, value: Value, errors: &mut Vec<Error>) {
-        errors.push(Error::new(
-            value.span(),
-            format!("cannot append multiple values for `{}`", id),
-        ));
+/// A value parsed from a string literal using its [FromStr] implementation.
+///
+/// Parse errors are reported at the span of the string literal, e.g. for
+/// `id = "not-a-uuid"` in combination with `id: FromStrValue<Uuid>`.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FromStrValue<T>(pub T);
+
+impl<T> FromStrValue<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for FromStrValue<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for FromStrValue<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
     }
 }
+
+impl<T> ParseValue for FromStrValue<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    const KIND: Kind = Kind::String;
+
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
+                Value::Lit(Lit::Str(lit_str)) => parse_str_literal(lit_str).map(FromStrValue),
+                value => Err(format_value_error(&ident, value, "string literal")),
+            },
+            Value::Lit(Lit::Str(lit_str)) => parse_str_literal(&lit_str).map(FromStrValue),
+            value => Err(format_error(&value, "string literal")),
+        }
+    }
+}
+
+/// A value parsed from a string literal using its syn [Parse] implementation.
+///
+/// The spans of the parsed syntax tree (and thus of any parse errors) point at
+/// the string literal, e.g. for `bound = "T: Clone"` in combination with
+/// `bound: ParseStrValue<Generics>`.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ParseStrValue<T>(pub T);
+
+impl<T> ParseStrValue<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ParseStrValue<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ParseStrValue<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> ParseValue for ParseStrValue<T>
+where
+    T: Parse,
+{
+    const KIND: Kind = Kind::String;
+
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::Expr(Expr { ident, value, .. }) => match value.as_ref() {
+                Value::Lit(Lit::Str(lit_str)) => Ok(ParseStrValue(lit_str.parse()?)),
+                value => Err(format_value_error(&ident, value, "string literal")),
+            },
+            Value::Lit(Lit::Str(lit_str)) => Ok(ParseStrValue(lit_str.parse()?)),
+            value => Err(format_error(&value, "string literal")),
+        }
+    }
+}
+
+/// A parsed value along with the spans of its key and value, so that errors
+/// found after parsing can still point at the attribute tokens (e.g. "this path
+/// does not exist").
+///
+/// For flags and list entries without a key, the key span and the value span
+/// are the same. Comparisons and hashing only take the value into account.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct SpannedValue<T> {
+    value: T,
+    key_span: Span,
+    value_span: Span,
+}
+
+impl<T> SpannedValue<T> {
+    pub fn new(value: T, key_span: Span, value_span: Span) -> Self {
+        Self {
+            value,
+            key_span,
+            value_span,
+        }
+    }
+
+    pub fn key_span(&self) -> Span {
+        self.key_span
+    }
+
+    pub fn value_span(&self) -> Span {
+        self.value_span
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for SpannedValue<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for SpannedValue<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for SpannedValue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for SpannedValue<T> {}
+
+impl<T: Hash> Hash for SpannedValue<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T> ParseValue for SpannedValue<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = T::KIND;
+
+    fn parse(value: Value) -> Result<Self> {
+        let (key_span, value_span) = (value.span(), value.value_span());
+
+        Ok(SpannedValue::new(T::parse(value)?, key_span, value_span))
+    }
+}
+
+/// A boolean key that remembers how it was written, so that an explicit choice
+/// of the user can be told apart from the key being omitted (e.g. to let it
+/// override a container default either way).
+///
+/// A derived attribute leaves an omitted flag [Absent](Flag::Absent). The span
+/// is that of the key. Comparisons ignore spans.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Flag {
+    /// The key was omitted.
+    #[default]
+    Absent,
+    /// The key was given by itself (`key`).
+    Set(Span),
+    /// The key was given a boolean (`key = true` or `key = false`).
+    Explicit(bool, Span),
+    /// The key was negated (`!key`).
+    Negated(Span),
+}
+
+impl Flag {
+    /// The value of the flag, or `None` if it was omitted.
+    ///
+    pub fn get(&self) -> Option<bool> {
+        match self {
+            Self::Absent => None,
+            Self::Set(_) => Some(true),
+            Self::Explicit(value, _) => Some(*value),
+            Self::Negated(_) => Some(false),
+        }
+    }
+
+    /// The value of the flag, where an omitted flag is `false`.
+    ///
+    pub fn value(&self) -> bool {
+        self.get().unwrap_or_default()
+    }
+
+    pub fn is_present(&self) -> bool {
+        !matches!(self, Self::Absent)
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Absent => None,
+            Self::Set(span) | Self::Explicit(_, span) | Self::Negated(span) => Some(*span),
+        }
+    }
+}
+
+impl PartialEq for Flag {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Absent, Self::Absent)
+            | (Self::Set(_), Self::Set(_))
+            | (Self::Negated(_), Self::Negated(_)) => true,
+            (Self::Explicit(a, _), Self::Explicit(b, _)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Flag {}
+
+impl ParseValue for Flag {
+    const KIND: Kind = Kind::Bool;
+
+    fn parse(value: Value) -> Result<Self> {
+        let span = value.span();
+
+        match value {
+            Value::Expr(Expr { ident, value, .. }) => match *value {
+                Value::Lit(Lit::Bool(lit_bool)) => Ok(Self::Explicit(lit_bool.value(), span)),
+                value => Err(format_value_error_suggesting(
+                    &ident,
+                    &value,
+                    "boolean (`true`, `false`)",
+                    &["true", "false"],
+                )),
+            },
+            Value::Ident(_) => Ok(Self::Set(span)),
+            Value::Not(_) => Ok(Self::Negated(span)),
+            value => Err(format_error(&value, "boolean expression")),
+        }
+    }
+}
+
+/// The number of times a key was given by itself, e.g. 2 for `verbose, verbose`
+/// (like the repeated flags of command line tools).
+///
+/// A derived attribute counts an omitted key as zero.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Count(pub usize);
+
+impl Count {
+    pub fn get(&self) -> usize {
+        self.0
+    }
+}
+
+impl Deref for Count {
+    type Target = usize;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ParseValue for Count {
+    const KIND: Kind = Kind::Count;
+
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::Ident(_) => Ok(Self(1)),
+            value => Err(format_error(&value, "flag")),
+        }
+    }
+
+    /// Every repetition of the key counts once more, rather than being a
+    /// duplicate.
+    ///
+    fn append(&mut self, _id: &str, value: Value) -> Result<()> {
+        self.0 += Self::parse(value)?.0;
+        Ok(())
+    }
+}
+
+/// The values of a key that is repeated rather than given a list, e.g. the
+/// routes of `route(path = "/a"), route(path = "/b")`, as opposed to those of
+/// `routes(route(path = "/a"), route(path = "/b"))` for a `Vec`.
+///
+/// Every occurrence of the key is a single value, so that `route()` is a route
+/// (with every key omitted) rather than an empty list of routes.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Repeated<T>(pub Vec<T>);
+
+impl<T> Repeated<T> {
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Default for Repeated<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> Deref for Repeated<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> IntoIterator for Repeated<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<T> ParseValue for Repeated<T>
+where
+    T: ParseValue,
+{
+    const KIND: Kind = T::KIND;
+
+    fn parse(value: Value) -> Result<Self> {
+        Ok(Self(vec![T::parse(value)?]))
+    }
+
+    /// Every repetition of the key is another value, rather than being a
+    /// duplicate.
+    ///
+    fn append(&mut self, _id: &str, value: Value) -> Result<()> {
+        self.0.push(T::parse(value)?);
+        Ok(())
+    }
+}
+
+/// The spans of the keys that were set on a parsed attribute, by key.
+///
+/// A derived attribute collects these in a field marked `#[squattr(spans)]`, so
+/// that secondary diagnostics can point at a specific key after parsing.
+/// Comparisons only take the set keys into account.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Spans {
+    spans: HashMap<&'static str, Span>,
+}
+
+impl Spans {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: &'static str, span: Span) {
+        self.spans.insert(key, span);
+    }
+
+    pub fn get(&self, key: &str) -> Option<Span> {
+        self.spans.get(key).copied()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.spans.contains_key(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Span)> + '_ {
+        self.spans.iter().map(|(&key, &span)| (key, span))
+    }
+}
+
+impl PartialEq for Spans {
+    fn eq(&self, other: &Self) -> bool {
+        self.spans.len() == other.spans.len()
+            && self.spans.keys().all(|key| other.spans.contains_key(key))
+    }
+}
+
+impl Eq for Spans {}
+
+/// A cfg-style condition, e.g. `all(feature = "x", not(test))` in
+/// `enable_if(all(feature = "x", not(test)))`.
+///
+/// A negated identifier (`!test`) is shorthand for `not(test)`.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Predicate {
+    /// Satisfied if any of the predicates is satisfied.
+    Any(Vec<Predicate>),
+    /// Satisfied if all of the predicates are satisfied.
+    All(Vec<Predicate>),
+    /// Satisfied if the predicate is not satisfied.
+    Not(Box<Predicate>),
+    /// A bare identifier, e.g. `test`.
+    Flag(Ident),
+    /// A key with a string value, e.g. `feature = "x"`.
+    KeyValue(Ident, String),
+}
+
+impl Predicate {
+    const OPERATORS: &[&str] = &["any", "all", "not"];
+
+    /// Evaluate the predicate, using `is_set` to determine whether a flag
+    /// (`is_set(ident, None)`) or key-value pair (`is_set(key, Some(value))`)
+    /// is satisfied.
+    ///
+    pub fn evaluate<F>(&self, is_set: &mut F) -> bool
+    where
+        F: FnMut(&Ident, Option<&str>) -> bool,
+    {
+        match self {
+            Self::Any(predicates) => predicates
+                .iter()
+                .any(|predicate| predicate.evaluate(is_set)),
+            Self::All(predicates) => predicates
+                .iter()
+                .all(|predicate| predicate.evaluate(is_set)),
+            Self::Not(predicate) => !predicate.evaluate(is_set),
+            Self::Flag(ident) => is_set(ident, None),
+            Self::KeyValue(key, value) => is_set(key, Some(value)),
+        }
+    }
+
+    /// Parse the single predicate within a list, such as `not(test)`.
+    ///
+    fn parse_single(list: List) -> Result<Self> {
+        let id = list.identifier();
+        let span = list.span();
+        let mut values = list.into_iter();
+
+        match (values.next(), values.next()) {
+            (Some(value), None) => Self::parse_entry(value),
+            (_, Some(value)) => Err(Error::new(
+                value.span(),
+                format!("expected a single predicate for `{}`", id),
+            )
+            .into()),
+            (None, _) => Err(Error::new(span, format!("expected a predicate for `{}`", id)).into()),
+        }
+    }
+
+    fn parse_entry(value: Value) -> Result<Self> {
+        match value {
+            Value::Ident(ident) => Ok(Self::Flag(ident)),
+            Value::Not(Not { ident, .. }) => Ok(Self::Not(Box::new(Self::Flag(ident)))),
+            Value::Expr(Expr { ident, value, .. }) => match *value {
+                Value::Lit(Lit::Str(lit_str)) => Ok(Self::KeyValue(ident, lit_str.value())),
+                value => Err(format_value_error(&ident, &value, "string literal")),
+            },
+            Value::List(list) => match list.identifier().as_str() {
+                "any" => Self::parse_list(list.values).map(Self::Any),
+                "all" => Self::parse_list(list.values).map(Self::All),
+                "not" => Self::parse_single(list).map(Box::new).map(Self::Not),
+                name => Err(Error::new(
+                    list.span(),
+                    match did_you_mean(Self::OPERATORS, name) {
+                        Some(best_match) => format!(
+                            "unrecognized predicate `{}`, did you mean `{}`?",
+                            name, best_match
+                        ),
+                        None => format!("unrecognized predicate `{}`", name),
+                    },
+                )
+                .into()),
+            },
+            value => Err(format_error(&value, "predicate")),
+        }
+    }
+}
+
+impl ParseValue for Predicate {
+    const KIND: Kind = Kind::Predicate;
+
+    fn parse(value: Value) -> Result<Self> {
+        match value {
+            Value::List(list) => Self::parse_single(list),
+            value => Err(format_error(&value, "predicate")),
+        }
+    }
+
+    /// Every entry of the list is a predicate by itself, such as `test` in
+    /// `conditions(test, feature = "x")`.
+    ///
+    fn parse_list(values: Values) -> Result<Vec<Self>> {
+        let mut errors = Accumulator::new();
+        let mut parsed = vec![];
+
+        for value in values {
+            parsed.extend(errors.handle(Self::parse_entry(value)));
+        }
+
+        errors.finish_with(parsed)
+    }
+}
+
+/// Parse the name of a unit enum variant, given either as a string literal
+/// (`mode = "fast"`) or as an identifier (`mode = fast`), and return the
+/// matching entry of `variants`.
+///
+pub fn parse_variant(value: Value, variants: &[&'static str]) -> Result<&'static str> {
+    parse_variant_with(value, variants, &Suggestions::DEFAULT)
+}
+
+/// Parse the name of a unit enum variant like [parse_variant], suggesting the
+/// variants found by `suggestions` for an unrecognized name.
+///
+pub fn parse_variant_with(
+    value: Value,
+    variants: &[&'static str],
+    suggestions: &Suggestions,
+) -> Result<&'static str> {
+    let (id, name, span) = match value {
+        Value::Expr(Expr { ident, value, .. }) => match *value {
+            Value::Lit(Lit::Str(lit_str)) => (Some(ident), lit_str.value(), lit_str.span()),
+            Value::Ident(variant) => (Some(ident), variant.to_string(), variant.span()),
+            value => return Err(format_value_error(&ident, &value, "variant name")),
+        },
+        Value::Lit(Lit::Str(lit_str)) => (None, lit_str.value(), lit_str.span()),
+        Value::Ident(variant) => (None, variant.to_string(), variant.span()),
+        value => return Err(format_error(&value, "variant name")),
+    };
+
+    if let Some(&variant) = variants.iter().find(|&&variant| variant == name) {
+        return Ok(variant);
+    }
+
+    Err(errors::Error::TypeMismatch {
+        key: id.map(|id| id.to_string()),
+        ty: None,
+        expected: format!("variant ({})", format_names(variants)),
+        found: None,
+        span,
+        suggestions: suggestions.suggest(variants, &name),
+    })
+}
+
+/// Parse the name of a flag, given as an identifier (e.g. `read` in
+/// `caps(read, write)`), and return the matching entry of `flags`.
+///
+pub fn parse_flag(value: Value, flags: &[&'static str]) -> Result<&'static str> {
+    parse_flag_with(value, flags, &Suggestions::DEFAULT)
+}
+
+/// Create the error for a flag that was given more than once, kept out of the
+/// code generated for every flag.
+///
+#[cold]
+pub fn duplicate_flag(name: &str, span: Span) -> errors::Error {
+    errors::Error::DuplicateKey {
+        key: name.to_string(),
+        span,
+        first: None,
+    }
+}
+
+/// Parse the name of a flag like [parse_flag], suggesting the flags found by
+/// `suggestions` for an unrecognized name.
+///
+pub fn parse_flag_with(
+    value: Value,
+    flags: &[&'static str],
+    suggestions: &Suggestions,
+) -> Result<&'static str> {
+    let ident = match value {
+        Value::Ident(ident) => ident,
+        value => return Err(format_error(&value, "flag")),
+    };
+
+    let name = ident.to_string();
+
+    if let Some(&flag) = flags.iter().find(|&&flag| flag == name) {
+        return Ok(flag);
+    }
+
+    Err(errors::Error::unknown_key_with(
+        &name,
+        ident.span(),
+        flags,
+        suggestions,
+    ))
+}
+
+/// Parse the variant of a tagged enum, given as the single entry of `values`
+/// (e.g. `file(path = "x")` in `source(file(path = "x"))`), and return the
+/// matching entry of `variants` along with the values of the variant.
+///
+/// Variants without values may also be given as a bare identifier.
+///
+pub fn parse_tagged(values: Values, variants: &[&'static str]) -> Result<(&'static str, Values)> {
+    parse_tagged_with(values, variants, &Suggestions::DEFAULT)
+}
+
+/// Parse the variant of a tagged enum like [parse_tagged], suggesting the
+/// variants found by `suggestions` for an unrecognized name.
+///
+pub fn parse_tagged_with(
+    values: Values,
+    variants: &[&'static str],
+    suggestions: &Suggestions,
+) -> Result<(&'static str, Values)> {
+    let span = values.span();
+    let mut values = values.into_iter();
+
+    let (name, span, values) = match (values.next(), values.next()) {
+        (Some(Value::List(List { ident, values, .. })), None) => {
+            (ident.to_string(), ident.span(), values)
+        }
+        (Some(Value::Ident(ident)), None) => {
+            (ident.to_string(), ident.span(), Values::new(ident.span()))
+        }
+        (Some(value), None) => return Err(format_error(&value, "variant")),
+        (Some(_), Some(value)) => {
+            return Err(Error::new(value.span(), "expected a single variant").into());
+        }
+        (None, _) => {
+            return Err(
+                Error::new(span, format!("expected one of {}", format_names(variants))).into(),
+            );
+        }
+    };
+
+    if let Some(&variant) = variants.iter().find(|&&variant| variant == name) {
+        return Ok((variant, values));
+    }
+
+    Err(errors::Error::unknown_key_with(
+        &name,
+        span,
+        variants,
+        suggestions,
+    ))
+}
+
+#[inline]
+fn format_names(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("`{}`", name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse a value by trying each of the `variants` parsers in order (like an
+/// untagged enum) and return the first successful result.
+///
+/// If none of the parsers succeed, the errors of all of them are combined.
+///
+pub fn parse_untagged<T>(value: Value, variants: &[fn(Value) -> Result<T>]) -> Result<T> {
+    let mut errors = Accumulator::new();
+
+    for parse in variants {
+        if let Some(parsed) = errors.handle(parse(value.clone())) {
+            return Ok(parsed);
+        }
+    }
+
+    Err(errors
+        .finish()
+        .err()
+        .unwrap_or_else(|| format_error(&value, "value")))
+}
+
+/// Create an error for a list that does not have the expected number of
+/// values.
+///
+#[inline]
+fn format_arity_error(
+    span: Span,
+    id: Option<String>,
+    expected: usize,
+    found: usize,
+) -> errors::Error {
+    errors::Error::TypeMismatch {
+        key: id,
+        ty: None,
+        expected: format!("list of {} values", expected),
+        found: Some(found),
+        span,
+        suggestions: Vec::new(),
+    }
+}
+
+const SPECIAL_FLOATS: &[&str] = &["inf", "-inf", "nan"];
+
+/// Parse the special float values `inf`, `-inf` and `nan`.
+///
+#[inline]
+fn parse_special_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Ident(ident) if ident == "inf" => Some(f64::INFINITY),
+        Value::Ident(ident) if ident == "nan" => Some(f64::NAN),
+        Value::Neg(Neg { ident, .. }) if ident == "inf" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+/// Parse the contents of a string literal using [FromStr], reporting parse
+/// errors at the span of the literal.
+///
+#[inline]
+pub fn parse_str_literal<T>(lit_str: &LitStr) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    lit_str
+        .value()
+        .parse()
+        .map_err(|error| Error::new(lit_str.span(), error).into())
+}
+
+/// Parse every entry of a list as a separate value.
+///
+pub fn parse_list_entries<T: ParseValue>(values: Values) -> Result<Vec<T>> {
+    let mut errors = Accumulator::new();
+    let mut parsed = vec![];
+
+    for value in values {
+        parsed.extend(errors.handle(T::parse(value)));
+    }
+
+    errors.finish_with(parsed)
+}
+
+/// Create a type conversion error.
+///
+#[inline]
+pub fn format_error(value: &Value, expect: &str) -> errors::Error {
+    errors::Error::type_mismatch(value.identifier(), value, expect)
+}
+
+/// Create a type conversion error, suggesting the most similar of the `known`
+/// values (e.g. `true` for `ture`).
+///
+#[inline]
+pub fn format_error_suggesting(
+    value: &Value,
+    expect: &str,
+    known: &[&'static str],
+) -> errors::Error {
+    errors::Error::type_mismatch_suggesting(value.identifier(), value, expect, known)
+}
+
+/// Create a type conversion error for the `value` set for `key` (e.g. `ture`
+/// in `flag = ture`), which is reported as the value of that key.
+///
+#[inline]
+pub fn format_value_error(key: &Ident, value: &Value, expect: &str) -> errors::Error {
+    errors::Error::type_mismatch(Some(key.to_string()), value, expect)
+}
+
+/// Create a type conversion error for the `value` set for `key`, suggesting
+/// the most similar of the `known` values.
+///
+#[inline]
+pub fn format_value_error_suggesting(
+    key: &Ident,
+    value: &Value,
+    expect: &str,
+    known: &[&'static str],
+) -> errors::Error {
+    errors::Error::type_mismatch_suggesting(Some(key.to_string()), value, expect, known)
+}
+
+pub trait ParseValue: Sized {
+    /// The kind of value that is parsed, see [Schema](crate::schema::Schema).
+    ///
+    const KIND: Kind = Kind::Unknown;
+
+    fn parse(value: Value) -> Result<Self>;
+
+    /// Parse the entries of a list into values of this type (i.e. when parsing
+    /// a `Vec<Self>`).
+    ///
+    /// By default, every entry of the list is parsed as a separate value.
+    ///
+    fn parse_list(values: Values) -> Result<Vec<Self>> {
+        parse_list_entries(values)
+    }
+
+    /// Append the value of a repeated key to an already parsed value.
+    ///
+    /// By default, values cannot be appended to.
+    ///
+    fn append(&mut self, id: &str, value: Value) -> Result<()> {
+        Err(Error::new(
+            value.span(),
+            format!("cannot append multiple values for `{}`", id),
+        )
+        .into())
+    }
+}
+
+pub trait ParseValueExt: Sized {
+    fn parse<T: ParseValue>(self) -> Result<T>;
+}
+
+impl ParseValueExt for Value {
+    fn parse<T: ParseValue>(self) -> Result<T> {
+        T::parse(self)
+    }
+}
+
+pub trait ValueStorageExt: Sized {
+    fn insert_value(&mut self, id: &str, value: Value, errors: &mut Accumulator);
+    fn append_value(&mut self, id: &str, value: Value, errors: &mut Accumulator);
+
+    /// Insert a value like [insert_value](Self::insert_value), naming the
+    /// declared Rust type `ty` of the key in its type mismatch errors, e.g.
+    /// ``expected integer of type `u16` for `port` ``.
+    ///
+    fn insert_typed_value(
+        &mut self,
+        id: &str,
+        ty: &'static str,
+        value: Value,
+        errors: &mut Accumulator,
+    ) {
+        let mut value_errors = Accumulator::new();
+        self.insert_value(id, value, &mut value_errors);
+        errors.extend(with_type(value_errors, id, ty));
+    }
+
+    /// Append a value like [append_value](Self::append_value), naming the
+    /// declared Rust type `ty` of the key in its type mismatch errors.
+    ///
+    fn append_typed_value(
+        &mut self,
+        id: &str,
+        ty: &'static str,
+        value: Value,
+        errors: &mut Accumulator,
+    ) {
+        let mut value_errors = Accumulator::new();
+        self.append_value(id, value, &mut value_errors);
+        errors.extend(with_type(value_errors, id, ty));
+    }
+}
+
+/// Name the declared type `ty` in the type mismatches of the value set for
+/// `id` itself, leaving those of its entries (e.g. an integer of a list) as is.
+///
+#[inline]
+fn with_type(
+    errors: Accumulator,
+    id: &str,
+    ty: &'static str,
+) -> impl Iterator<Item = errors::Error> {
+    errors.into_iter().map(move |error| match error {
+        errors::Error::TypeMismatch {
+            key: error_key,
+            ty: None,
+            expected,
+            found,
+            span,
+            suggestions,
+        } if error_key.as_deref() == Some(id) => errors::Error::TypeMismatch {
+            key: error_key,
+            ty: Some(ty),
+            expected,
+            found,
+            span,
+            suggestions,
+        },
+        error => error,
+    })
+}
+
+/// An optional type, through which the derive finds the type of the value of
+/// an `Option` under another name (see `#[squattr(optional)]`).
+///
+pub trait Optional {
+    type Value;
+}
+
+impl<T> Optional for Option<T> {
+    type Value = T;
+}
+
+impl<T> ValueStorageExt for Option<T>
+where
+    T: ParseValue,
+{
+    fn insert_value(&mut self, id: &str, value: Value, errors: &mut Accumulator) {
+        if !self.is_some() {
+            *self = errors.handle(value.parse());
+        } else {
+            errors.push(errors::Error::DuplicateKey {
+                key: id.to_string(),
+                span: value.span(),
+                first: None,
+            });
+        }
+    }
+
+    fn append_value(&mut self, id: &str, value: Value, errors: &mut Accumulator) {
+        match self {
+            Some(existing) => {
+                errors.handle(existing.append(id, value));
+            }
+            None => self.insert_value(id, value, errors),
+        }
+    }
+}
+
+/// Storing the value of a key that may be given without a value, i.e. that of
+/// a derived field of type `Option<Option<T>>` (or `Option<T>`, marked
+/// `#[squattr(required)]`). A bare key (e.g. `default`) is stored as
+/// `Some(None)`, a key with a value (e.g. `default = "x"`) as `Some(Some(_))`:
+///
+/// ```ignore
+/// #[derive(Squattr)]
+/// struct Field {
+///     default: Option<Option<String>>,
+/// }
+/// ```
+///
+pub trait OptionalStorageExt {
+    fn insert_optional_value(
+        &mut self,
+        id: &str,
+        ty: &'static str,
+        value: Value,
+        errors: &mut Accumulator,
+    );
+}
+
+impl<T> OptionalStorageExt for Option<Option<T>>
+where
+    T: ParseValue,
+{
+    fn insert_optional_value(
+        &mut self,
+        id: &str,
+        ty: &'static str,
+        value: Value,
+        errors: &mut Accumulator,
+    ) {
+        match value {
+            Value::Ident(_) if self.is_none() => *self = Some(None),
+            value => self.insert_typed_value(id, ty, value, errors),
+        }
+    }
+}
+
+/// Storing values of type `T` that are checked once parsed, against the
+/// constraints set on their key (e.g. `#[squattr(min = 1)]`). The check is
+/// given the parsed value along with the value as written, to point its
+/// errors at.
+///
+pub trait CheckedStorageExt<T>: ValueStorageExt {
+    /// Insert a value like [insert_typed_value](ValueStorageExt::insert_typed_value)
+    /// and check it.
+    ///
+    fn insert_checked_value(
+        &mut self,
+        id: &str,
+        ty: &'static str,
+        value: Value,
+        errors: &mut Accumulator,
+        check: fn(&T, &Value) -> syn::Result<()>,
+    );
+}
+
+impl<T> CheckedStorageExt<T> for Option<T>
+where
+    T: ParseValue,
+{
+    fn insert_checked_value(
+        &mut self,
+        id: &str,
+        ty: &'static str,
+        value: Value,
+        errors: &mut Accumulator,
+        check: fn(&T, &Value) -> syn::Result<()>,
+    ) {
+        // A duplicate is reported as such, without checking it.
+        let written = self.is_none().then(|| value.clone());
+
+        self.insert_typed_value(id, ty, value, errors);
+
+        if let (Some(parsed), Some(written)) = (self.as_ref(), written) {
+            errors.handle(check(parsed, &written));
+        }
+    }
+}
+
+/// Check that a parsed number lies within the bounds set through
+/// `#[squattr(min = ..., max = ...)]`, reporting it at the value otherwise.
+///
+pub fn check_range<T>(value: &T, min: Option<T>, max: Option<T>, written: &Value) -> syn::Result<()>
+where
+    T: PartialOrd + Display,
+{
+    let below = min.as_ref().is_some_and(|min| value < min);
+    let above = max.as_ref().is_some_and(|max| value > max);
+
+    if !below && !above {
+        return Ok(());
+    }
+
+    let expected = match (min, max) {
+        (Some(min), Some(max)) => format!("between {} and {}", min, max),
+        (Some(min), None) => format!("of at least {}", min),
+        (None, Some(max)) => format!("of at most {}", max),
+        (None, None) => unreachable!("a value is only out of range given a bound"),
+    };
+
+    Err(Error::new(
+        written.value_span(),
+        format!("expected a value {}, found {}", expected, value),
+    ))
+}
+
+/// Check that the number of parsed values lies within the bounds set through
+/// `#[squattr(min_items = ..., max_items = ...)]`, reporting it at the list
+/// otherwise.
+///
+pub fn check_items<T>(
+    values: &[T],
+    min: Option<usize>,
+    max: Option<usize>,
+    written: &Value,
+) -> syn::Result<()> {
+    let count = |n: usize| match n {
+        1 => "1 value".to_string(),
+        n => format!("{} values", n),
+    };
+
+    let expected = match (min, max) {
+        (Some(min), Some(max)) if values.len() < min || values.len() > max => {
+            format!("between {} and {}", min, count(max))
+        }
+        (Some(min), _) if values.len() < min => format!("at least {}", count(min)),
+        (_, Some(max)) if values.len() > max => format!("at most {}", count(max)),
+        _ => return Ok(()),
+    };
+
+    Err(Error::new(
+        written.value_span(),
+        format!("expected {}, found {}", expected, values.len()),
+    ))
+}
+
+/// Check that a parsed string is not empty, as set through
+/// `#[squattr(non_empty)]`, reporting it at the literal otherwise.
+///
+pub fn check_non_empty<T: AsRef<str> + ?Sized>(value: &T, written: &Value) -> syn::Result<()> {
+    match value.as_ref().is_empty() {
+        true => Err(Error::new(
+            written.value_span(),
+            "expected a non-empty string",
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Check that no parsed value is listed twice, as set through
+/// `#[squattr(unique)]`, reporting the first duplicate at its entry.
+///
+pub fn check_unique<T: PartialEq>(values: &[T], written: &Value) -> syn::Result<()> {
+    let Some(index) = (1..values.len()).find(|&i| values[..i].contains(&values[i])) else {
+        return Ok(());
+    };
+
+    let entries = match written {
+        Value::List(List { values, .. }) => Some(values),
+        Value::Expr(Expr { value, .. }) => match value.as_ref() {
+            Value::Tuple(Tuple { values, .. }) => Some(values),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let span = entries
+        .and_then(|entries| entries.get(index))
+        .map_or_else(|| written.value_span(), Value::span);
+
+    Err(Error::new(span, "duplicate value"))
+}
@@ -0,0 +1,161 @@
+use syn::{Ident, Lit};
+
+use crate::ast::{Closure, Dotted, Expr, List, Neg, Not, Range, Tuple, Value, Values};
+
+/// Traversal of parsed values by reference, e.g. to collect all string
+/// literals of an attribute:
+///
+/// ```ignore
+/// struct Strings(Vec<String>);
+///
+/// impl Visit for Strings {
+///     fn visit_lit(&mut self, lit: &Lit) {
+///         if let Lit::Str(lit_str) = lit {
+///             self.0.push(lit_str.value());
+///         }
+///     }
+/// }
+/// ```
+///
+/// Every method defaults to visiting the children of its node through the
+/// function of the same name, which an overriding method can call to keep
+/// descending.
+///
+pub trait Visit {
+    fn visit_values(&mut self, node: &Values) {
+        visit_values(self, node);
+    }
+
+    fn visit_value(&mut self, node: &Value) {
+        visit_value(self, node);
+    }
+
+    fn visit_dotted(&mut self, node: &Dotted) {
+        visit_dotted(self, node);
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        visit_expr(self, node);
+    }
+
+    fn visit_list(&mut self, node: &List) {
+        visit_list(self, node);
+    }
+
+    fn visit_tuple(&mut self, node: &Tuple) {
+        visit_tuple(self, node);
+    }
+
+    fn visit_not(&mut self, node: &Not) {
+        visit_not(self, node);
+    }
+
+    fn visit_neg(&mut self, node: &Neg) {
+        visit_neg(self, node);
+    }
+
+    fn visit_range(&mut self, node: &Range) {
+        visit_range(self, node);
+    }
+
+    fn visit_ident(&mut self, _node: &Ident) {}
+
+    fn visit_lit(&mut self, _node: &Lit) {}
+
+    fn visit_closure(&mut self, _node: &Closure) {}
+}
+
+pub fn visit_values<V: Visit + ?Sized>(v: &mut V, node: &Values) {
+    for value in node.iter() {
+        v.visit_value(value);
+    }
+}
+
+pub fn visit_value<V: Visit + ?Sized>(v: &mut V, node: &Value) {
+    match node {
+        Value::Closure(closure) => v.visit_closure(closure),
+        Value::Dotted(dotted) => v.visit_dotted(dotted),
+        Value::Expr(expr) => v.visit_expr(expr),
+        Value::Ident(ident) => v.visit_ident(ident),
+        Value::List(list) => v.visit_list(list),
+        Value::Lit(lit) => v.visit_lit(lit),
+        Value::Neg(neg) => v.visit_neg(neg),
+        Value::Not(not) => v.visit_not(not),
+        Value::Range(range) => v.visit_range(range),
+        Value::Tuple(tuple) => v.visit_tuple(tuple),
+    }
+}
+
+pub fn visit_dotted<V: Visit + ?Sized>(v: &mut V, node: &Dotted) {
+    v.visit_ident(&node.ident);
+    v.visit_value(&node.value);
+}
+
+pub fn visit_expr<V: Visit + ?Sized>(v: &mut V, node: &Expr) {
+    v.visit_ident(&node.ident);
+    v.visit_value(&node.value);
+}
+
+pub fn visit_list<V: Visit + ?Sized>(v: &mut V, node: &List) {
+    v.visit_ident(&node.ident);
+    v.visit_values(&node.values);
+}
+
+pub fn visit_tuple<V: Visit + ?Sized>(v: &mut V, node: &Tuple) {
+    v.visit_values(&node.values);
+}
+
+pub fn visit_not<V: Visit + ?Sized>(v: &mut V, node: &Not) {
+    v.visit_ident(&node.ident);
+}
+
+pub fn visit_neg<V: Visit + ?Sized>(v: &mut V, node: &Neg) {
+    v.visit_ident(&node.ident);
+}
+
+pub fn visit_range<V: Visit + ?Sized>(v: &mut V, node: &Range) {
+    v.visit_lit(&node.start);
+    v.visit_lit(&node.end);
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{Lit, parse2};
+
+    use super::{Visit, visit_list};
+    use crate::ast::{List, Values};
+
+    #[test]
+    fn collect_string_literals() {
+        #[derive(Default)]
+        struct Strings {
+            strings: Vec<String>,
+            lists: usize,
+        }
+
+        impl Visit for Strings {
+            fn visit_lit(&mut self, lit: &Lit) {
+                if let Lit::Str(lit_str) = lit {
+                    self.strings.push(lit_str.value());
+                }
+            }
+
+            fn visit_list(&mut self, list: &List) {
+                self.lists += 1;
+                visit_list(self, list);
+            }
+        }
+
+        let values = parse2::<Values>(quote! {
+            name = "a", paths("b", (1, "c")), nested(inner(key = "d")), flag
+        })
+        .unwrap();
+
+        let mut strings = Strings::default();
+        strings.visit_values(&values);
+
+        assert_eq!(strings.strings, ["a", "b", "c", "d"]);
+        assert_eq!(strings.lists, 3);
+    }
+}
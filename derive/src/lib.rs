@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
 use squattr_core::expand::expand;
 
-#[proc_macro_derive(Squattr)]
+#[proc_macro_derive(Squattr, attributes(squattr))]
 pub fn derive_attribute_parser(input: TokenStream) -> TokenStream {
     match expand(input.into()) {
         Ok(token_stream) => token_stream.into(),
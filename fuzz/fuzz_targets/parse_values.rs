@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use squattr::ast::{Value, Values};
+
+// Parsing arbitrary tokens may fail, but must never panic or loop, and any
+// values that do parse must parse again from their own tokens.
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(tokens) = source.parse::<TokenStream>() else {
+        return;
+    };
+
+    if let Ok(values) = syn::parse2::<Values>(tokens.clone()) {
+        syn::parse2::<Values>(values.to_token_stream()).unwrap();
+    }
+
+    if let Ok(value) = syn::parse2::<Value>(tokens) {
+        syn::parse2::<Value>(value.to_token_stream()).unwrap();
+    }
+});
@@ -1,7 +1,23 @@
 pub use squattr_core::ast;
 pub use squattr_core::attribute;
+#[cfg(feature = "darling")]
+pub use squattr_core::darling;
+pub use squattr_core::defaults;
+pub use squattr_core::diagnostic;
 pub use squattr_core::dym;
 pub use squattr_core::errors;
+pub use squattr_core::fold;
+pub use squattr_core::input;
+pub use squattr_core::parse_attr;
+pub use squattr_core::runtime;
+pub use squattr_core::schema::{self, Schema};
+#[cfg(feature = "proptest")]
+pub use squattr_core::strategy;
+#[cfg(feature = "testing")]
+pub use squattr_core::testing;
+pub use squattr_core::tokens;
 pub use squattr_core::types;
+pub use squattr_core::values;
+pub use squattr_core::visit;
 pub use squattr_derive as derive;
 pub use syn;
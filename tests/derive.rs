@@ -2,6 +2,14 @@
 mod tests {
     use squattr::{attribute::Attribute, derive::Squattr};
 
+    use std::{
+        borrow::Cow,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+        path::PathBuf,
+        rc::Rc,
+        sync::Arc,
+    };
+
     use proc_macro2::Span;
     use quote::quote;
     use syn::{Ident, Lit, LitBool, LitFloat, LitInt, LitStr};
@@ -43,9 +51,28 @@ mod tests {
             tst_bool: bool,
             tst_bool_omitted: bool,
             tst_bool_expr: bool,
+            tst_bool_negated: Option<bool>,
 
             tst_str: String,
             tst_str_list: Vec<String>,
+            tst_str_cow: Cow<'static, str>,
+
+            tst_path: PathBuf,
+            tst_path_list: Vec<PathBuf>,
+
+            tst_ip: IpAddr,
+            tst_ip_list: Vec<IpAddr>,
+            tst_socket_addr: SocketAddr,
+
+            tst_tuple: (u32, u32),
+            tst_tuple_mixed: (String, u8, bool),
+            tst_array: [u8; 3],
+            tst_nested_list: Vec<Vec<u32>>,
+
+            tst_rc: Rc<String>,
+            tst_arc: Arc<u64>,
+            tst_box: Box<PathBuf>,
+            tst_box_int: Box<u64>,
 
             tst_ident: Ident,
             tst_lit: Lit,
@@ -55,6 +82,7 @@ mod tests {
             tst_lit_str: LitStr,
 
             tst_sub_attrs: SubAttribute,
+            tst_sub_attrs_boxed: Box<SubAttribute>,
         }
 
         #[derive(Squattr, PartialEq, Debug)]
@@ -126,9 +154,28 @@ mod tests {
             tst_bool,
             // tst_bool_omitted,
             tst_bool_expr = false,
+            !tst_bool_negated,
 
             tst_str = "foo",
             tst_str_list("foo", "bar"),
+            tst_str_cow = "baz",
+
+            tst_path = "foo/bar.json",
+            tst_path_list("foo", "bar/baz"),
+
+            tst_ip = "127.0.0.1",
+            tst_ip_list("10.0.0.1", "::1"),
+            tst_socket_addr = "0.0.0.0:8080",
+
+            tst_tuple(1, 10),
+            tst_tuple_mixed("k", 3, false),
+            tst_array(255, 128, 0),
+            tst_nested_list(row(1, 2), row(3, 4)),
+
+            tst_rc = "shared",
+            tst_arc = 42,
+            tst_box = "boxed.json",
+            tst_box_int = 7,
 
             tst_ident,
             tst_lit = "literal",
@@ -140,6 +187,7 @@ mod tests {
             tst_sub_attrs(
                 tst_sub_str = "hola, mundo"
             ),
+            tst_sub_attrs_boxed(),
         };
 
         pretty_assertions::assert_eq!(
@@ -178,9 +226,31 @@ mod tests {
                 tst_bool: true,
                 tst_bool_omitted: false,
                 tst_bool_expr: false,
+                tst_bool_negated: Some(false),
 
                 tst_str: "foo".into(),
                 tst_str_list: vec!["foo".into(), "bar".into()],
+                tst_str_cow: Cow::Borrowed("baz"),
+
+                tst_path: PathBuf::from("foo/bar.json"),
+                tst_path_list: vec![PathBuf::from("foo"), PathBuf::from("bar/baz")],
+
+                tst_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                tst_ip_list: vec![
+                    IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                    IpAddr::V6(Ipv6Addr::LOCALHOST)
+                ],
+                tst_socket_addr: SocketAddr::from(([0, 0, 0, 0], 8080)),
+
+                tst_tuple: (1, 10),
+                tst_tuple_mixed: ("k".into(), 3, false),
+                tst_array: [255, 128, 0],
+                tst_nested_list: vec![vec![1, 2], vec![3, 4]],
+
+                tst_rc: Rc::new("shared".into()),
+                tst_arc: Arc::new(42),
+                tst_box: Box::new(PathBuf::from("boxed.json")),
+                tst_box_int: Box::new(7),
 
                 tst_ident: Ident::new("tst_ident", Span::call_site()),
                 tst_lit: Lit::Str(LitStr::new("literal", Span::call_site())),
@@ -191,8 +261,3186 @@ mod tests {
 
                 tst_sub_attrs: SubAttribute {
                     tst_sub_str: Some("hola, mundo".into())
+                },
+                tst_sub_attrs_boxed: Box::new(SubAttribute { tst_sub_str: None }),
+            }
+        );
+    }
+
+    #[cfg(feature = "duration")]
+    #[test]
+    fn parse_duration_derived() {
+        use std::time::Duration;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct DurationAttribute {
+            tst_str_secs: Duration,
+            tst_str_millis: Duration,
+            tst_str_fraction: Duration,
+            tst_int_millis: Duration,
+            tst_float_secs: Duration,
+        }
+
+        let input = quote! {
+            tst_str_secs = "30s",
+            tst_str_millis = "250 ms",
+            tst_str_fraction = "1.5h",
+            tst_int_millis = 1500,
+            tst_float_secs = 2.5,
+        };
+
+        pretty_assertions::assert_eq!(
+            DurationAttribute::from_tokens(input).unwrap(),
+            DurationAttribute {
+                tst_str_secs: Duration::from_secs(30),
+                tst_str_millis: Duration::from_millis(250),
+                tst_str_fraction: Duration::from_secs(5400),
+                tst_int_millis: Duration::from_millis(1500),
+                tst_float_secs: Duration::from_millis(2500),
+            }
+        );
+
+        let input = quote! {
+            tst_str_secs = "30 parsecs",
+        };
+
+        assert_eq!(
+            DurationAttribute::from_tokens(input)
+                .unwrap_err()
+                .to_string(),
+            "unknown duration unit `parsecs`, expected one of `ns`, `us`, `ms`, `s`, `m`, `h`, `d`"
+        );
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Timeout {
+            timeout: Duration,
+        }
+
+        for input in [quote!(timeout = -1), quote!(timeout = "-5s")] {
+            assert_eq!(
+                Timeout::from_tokens(input)
+                    .unwrap_err()
+                    .into_iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>(),
+                ["a duration cannot be negative"]
+            );
+        }
+    }
+
+    #[test]
+    fn parse_tuple_arity_mismatch() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct TupleAttribute {
+            range: (u32, u32),
+        }
+
+        let input = quote! {
+            range(1, 10, 100),
+        };
+
+        assert_eq!(
+            TupleAttribute::from_tokens(input).unwrap_err().to_string(),
+            "squattr(E004): expected list of 2 values of type `(u32, u32)` for `range`, found 3 values"
+        );
+    }
+
+    #[test]
+    fn parse_array_length_mismatch() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct ArrayAttribute {
+            rgb: [u8; 3],
+        }
+
+        let input = quote! {
+            rgb(255, 128),
+        };
+
+        assert_eq!(
+            ArrayAttribute::from_tokens(input).unwrap_err().to_string(),
+            "squattr(E004): expected list of 3 values of type `[u8;3]` for `rgb`, found 2 values"
+        );
+    }
+
+    #[test]
+    fn parse_attribute_lists_derived() {
+        use squattr::types::Repeated;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RouterAttribute {
+            route: Repeated<Route>,
+            routes: Vec<Route>,
+            tags: Option<Vec<String>>,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Route {
+            path: String,
+            opts: Option<Vec<Route>>,
+        }
+
+        let input = quote! {
+            route(path = "/a"),
+            route(path = "/b"),
+            routes(route(path = "/c"), route(path = "/d")),
+            tags("foo"),
+            tags("bar", "baz"),
+        };
+
+        let route = |path: &str| Route {
+            path: path.into(),
+            opts: None,
+        };
+
+        pretty_assertions::assert_eq!(
+            RouterAttribute::from_tokens(input).unwrap(),
+            RouterAttribute {
+                route: Repeated(vec![route("/a"), route("/b")]),
+                routes: vec![route("/c"), route("/d")],
+                tags: Some(vec!["foo".into(), "bar".into(), "baz".into()]),
+            }
+        );
+
+        // A repeated key is one attribute per occurrence, whatever it holds.
+        let input = quote! {
+            route(path = "/a", opts(route(path = "/b"))),
+            routes(),
+        };
+
+        pretty_assertions::assert_eq!(
+            RouterAttribute::from_tokens(input).unwrap(),
+            RouterAttribute {
+                route: Repeated(vec![Route {
+                    path: "/a".into(),
+                    opts: Some(vec![route("/b")]),
+                }]),
+                routes: vec![],
+                tags: None,
+            }
+        );
+
+        assert_eq!(
+            RouterAttribute::from_tokens(quote!(route(), routes()))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E003): in `route`: required key `path` not found"
+        );
+        assert_eq!(
+            RouterAttribute::from_tokens(quote!(route(path = "/a"), routes(path = "/b")))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): expected list of values for `path`"
+        );
+    }
+
+    #[test]
+    fn parse_from_str_derived() {
+        use squattr::types::FromStrValue;
+        use std::str::FromStr;
+
+        #[derive(PartialEq, Debug)]
+        enum Mode {
+            Fast,
+            Slow,
+        }
+
+        impl FromStr for Mode {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "fast" => Ok(Mode::Fast),
+                    "slow" => Ok(Mode::Slow),
+                    s => Err(format!("unknown mode `{}`", s)),
                 }
             }
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct ModeAttribute {
+            mode: FromStrValue<Mode>,
+            fallback: Option<FromStrValue<Mode>>,
+            modes: Vec<FromStrValue<Mode>>,
+        }
+
+        let input = quote! {
+            mode = "fast",
+            modes("slow", "fast"),
+        };
+
+        pretty_assertions::assert_eq!(
+            ModeAttribute::from_tokens(input).unwrap(),
+            ModeAttribute {
+                mode: FromStrValue(Mode::Fast),
+                fallback: None,
+                modes: vec![FromStrValue(Mode::Slow), FromStrValue(Mode::Fast)],
+            }
+        );
+
+        let input = quote! {
+            mode = "medium",
+            modes(),
+        };
+
+        assert_eq!(
+            ModeAttribute::from_tokens(input).unwrap_err().to_string(),
+            "unknown mode `medium`"
+        );
+    }
+
+    #[test]
+    fn parse_spanned_values_derived() {
+        use squattr::types::SpannedValue;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct SpannedAttribute {
+            schema: SpannedValue<PathBuf>,
+            verbose: Option<SpannedValue<bool>>,
+            ports: SpannedValue<Vec<u16>>,
+        }
+
+        let input = quote! {
+            schema = "schemas/user.json",
+            verbose,
+            ports(80, 443),
+        };
+
+        let attribute = SpannedAttribute::from_tokens(input).unwrap();
+
+        assert_eq!(*attribute.schema, PathBuf::from("schemas/user.json"));
+        assert_eq!(attribute.verbose.map(SpannedValue::into_inner), Some(true));
+        assert_eq!(attribute.ports.len(), 2);
+    }
+
+    #[test]
+    fn collect_spans_derived() {
+        use squattr::types::Spans;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct SpansAttribute {
+            name: Option<String>,
+            verbose: bool,
+            #[squattr(spans)]
+            spans: Spans,
+        }
+
+        let input = quote! {
+            name = "foo",
+        };
+
+        let attribute = SpansAttribute::from_tokens(input).unwrap();
+
+        assert_eq!(attribute.name.as_deref(), Some("foo"));
+        assert!(attribute.spans.contains("name"));
+        assert!(attribute.spans.get("verbose").is_none());
+        assert!(!attribute.spans.contains("spans"));
+    }
+
+    #[test]
+    fn warn_deprecated_keys_derived() {
+        use squattr::diagnostic;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RouteAttribute {
+            path: Option<String>,
+            #[squattr(deprecated = "use `path` instead")]
+            url: Option<String>,
+        }
+
+        let input = quote! {
+            path = "/a",
+        };
+
+        let (attribute, warnings) = diagnostic::collect(|| RouteAttribute::from_tokens(input));
+        attribute.unwrap();
+        assert!(warnings.is_empty());
+
+        let input = quote! {
+            url = "/a",
+        };
+
+        let (attribute, warnings) = diagnostic::collect(|| RouteAttribute::from_tokens(input));
+        assert_eq!(
+            attribute.unwrap(),
+            RouteAttribute {
+                path: None,
+                url: Some("/a".to_string()),
+            }
+        );
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| warning.message())
+                .collect::<Vec<_>>(),
+            ["key `url` is deprecated, use `path` instead"]
+        );
+    }
+
+    #[test]
+    fn describe_deprecated_keys_derived() {
+        use squattr::{diagnostic, schema::Schema};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RouteAttribute {
+            path: Option<String>,
+            #[squattr(deprecated_since = "0.4", note = "use `path` instead")]
+            url: Option<String>,
+            #[squattr(deprecated_since = "0.5")]
+            name: Option<String>,
+        }
+
+        let (attribute, warnings) =
+            diagnostic::collect(|| RouteAttribute::from_tokens(quote!(url = "/a", name = "a")));
+
+        assert_eq!(
+            attribute.unwrap(),
+            RouteAttribute {
+                path: None,
+                url: Some("/a".to_string()),
+                name: Some("a".to_string()),
+            }
+        );
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| warning.message())
+                .collect::<Vec<_>>(),
+            [
+                "key `url` is deprecated since 0.4, use `path` instead",
+                "key `name` is deprecated since 0.5"
+            ]
+        );
+
+        let Schema::Struct { keys, .. } = RouteAttribute::SCHEMA else {
+            panic!("expected a struct schema");
+        };
+
+        assert_eq!(
+            keys.iter()
+                .map(|key| key.deprecated.map(|deprecation| deprecation.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                None,
+                Some("deprecated since 0.4, use `path` instead".to_string()),
+                Some("deprecated since 0.5".to_string()),
+            ]
+        );
+
+        assert!(
+            RouteAttribute::SCHEMA
+                .help()
+                .contains("`url`: string (deprecated since 0.4, use `path` instead)")
+        );
+    }
+
+    #[test]
+    fn parse_partial_derived() {
+        use squattr::types::Spans;
+
+        #[derive(Squattr, Debug)]
+        #[squattr(partial)]
+        #[allow(dead_code)]
+        struct ServerAttribute {
+            host: String,
+            port: u16,
+            verbose: bool,
+            name: Option<String>,
+            #[squattr(spans)]
+            spans: Spans,
+        }
+
+        let input = quote! {
+            host = "localhost",
+            port = "eighty",
+            nmae = "main",
+        };
+
+        let values = syn::parse2(input).unwrap();
+        let (partial, errors) = ServerAttribute::from_values_partial(values);
+
+        assert_eq!(partial.host.as_deref(), Some("localhost"));
+        assert_eq!(partial.port, None);
+        assert_eq!(partial.verbose, None);
+        assert_eq!(partial.name, None);
+        assert!(partial.spans.contains("host"));
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E004): expected integer of type `u16` for `port`",
+                "squattr(E001): unrecognized key `nmae`, did you mean `name`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unit_enum_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        enum Mode {
+            Fast,
+            VerySlow,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(rename_all = "SCREAMING_SNAKE_CASE")]
+        enum Level {
+            Info,
+            Warn,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct EnumAttribute {
+            mode: Mode,
+            mode_ident: Mode,
+            modes: Vec<Mode>,
+            level: Option<Level>,
+        }
+
+        let input = quote! {
+            mode = "fast",
+            mode_ident = very_slow,
+            modes(fast, "very_slow"),
+            level = WARN,
+        };
+
+        pretty_assertions::assert_eq!(
+            EnumAttribute::from_tokens(input).unwrap(),
+            EnumAttribute {
+                mode: Mode::Fast,
+                mode_ident: Mode::VerySlow,
+                modes: vec![Mode::Fast, Mode::VerySlow],
+                level: Some(Level::Warn),
+            }
+        );
+
+        let input = quote! {
+            mode = "fsat",
+            mode_ident = fast,
+            modes(),
+        };
+
+        assert_eq!(
+            EnumAttribute::from_tokens(input).unwrap_err().to_string(),
+            "squattr(E004): expected variant (`fast`, `very_slow`) of type `Mode` for `mode`, \
+             did you mean `fast`?"
+        );
+    }
+
+    #[test]
+    fn suggest_mistyped_values() {
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct ValueAttribute {
+            verbose: bool,
+            quiet: bool,
+            limit: f64,
+        }
+
+        let input = quote! {
+            verbose = ture,
+            quiet = "false",
+            limit = inff,
+        };
+
+        assert_eq!(
+            ValueAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E004): expected boolean (`true`, `false`) of type `bool` for `verbose`, did you mean `true`?",
+                "squattr(E004): expected boolean (`true`, `false`) of type `bool` for `quiet`, did you mean `false`?",
+                "squattr(E004): expected decimal of type `f64` for `limit`, did you mean `inf`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn configure_suggestions_derived() {
+        #[derive(Squattr, Debug)]
+        #[squattr(suggest(threshold = 0.5, max = 2, scorer = "levenshtein"))]
+        #[allow(dead_code)]
+        struct ServerAttribute {
+            host: Option<String>,
+            port: Option<u16>,
+            ports: Option<Vec<u16>>,
+            mode: Option<Mode>,
+        }
+
+        #[derive(Squattr, Debug)]
+        #[squattr(suggest(max = 2))]
+        enum Mode {
+            Fast,
+            Faster,
+            Slow,
+        }
+
+        let input = quote! {
+            prt = 80,
+            mode = fst,
+        };
+
+        assert_eq!(
+            ServerAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E001): unrecognized key `prt`, did you mean `port` or `ports`?",
+                "squattr(E004): expected variant (`fast`, `faster`, `slow`) of type `Mode` \
+                 for `mode`, did you mean `fast` or `faster`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_untagged_enum_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        enum IntOrString {
+            Int(u64),
+            Str(String),
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct UntaggedAttribute {
+            int: IntOrString,
+            str: IntOrString,
+            mixed: Vec<IntOrString>,
+        }
+
+        let input = quote! {
+            int = 3,
+            str = "three",
+            mixed(1, "two"),
+        };
+
+        pretty_assertions::assert_eq!(
+            UntaggedAttribute::from_tokens(input).unwrap(),
+            UntaggedAttribute {
+                int: IntOrString::Int(3),
+                str: IntOrString::Str("three".into()),
+                mixed: vec![IntOrString::Int(1), IntOrString::Str("two".into())],
+            }
+        );
+
+        let input = quote! {
+            int = true,
+            str = "three",
+            mixed(),
+        };
+
+        assert_eq!(
+            UntaggedAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E004): expected integer of type `IntOrString` for `int`",
+                "squattr(E004): expected string literal of type `IntOrString` for `int`",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_tagged_enum_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        enum Source {
+            File { path: String, watch: bool },
+            Env { var: String },
+            Stdin,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct TaggedAttribute {
+            source: Source,
+            fallback: Option<Source>,
+            sources: Vec<Source>,
+        }
+
+        let input = quote! {
+            source(file(path = "x", watch)),
+            fallback(stdin),
+            sources(env(var = "X"), stdin()),
+        };
+
+        pretty_assertions::assert_eq!(
+            TaggedAttribute::from_tokens(input).unwrap(),
+            TaggedAttribute {
+                source: Source::File {
+                    path: "x".into(),
+                    watch: true,
+                },
+                fallback: Some(Source::Stdin),
+                sources: vec![Source::Env { var: "X".into() }, Source::Stdin],
+            }
         );
+
+        let input = quote! {
+            source(fiel(path = "x")),
+            sources(env(path = "X")),
+        };
+
+        assert_eq!(
+            TaggedAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E001): in `source`: unrecognized key `fiel`, did you mean `file`?",
+                "squattr(E001): unrecognized key `path`, expected `var`",
+                "squattr(E003): required key `var` not found",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_flags_derived() {
+        #[derive(Squattr, PartialEq, Debug, Default)]
+        #[squattr(flags)]
+        struct Caps {
+            read: bool,
+            write: bool,
+            exec: bool,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct FlagsAttribute {
+            caps: Caps,
+            other_caps: Option<Caps>,
+        }
+
+        let input = quote! {
+            caps(read, write),
+            other_caps(),
+        };
+
+        pretty_assertions::assert_eq!(
+            FlagsAttribute::from_tokens(input).unwrap(),
+            FlagsAttribute {
+                caps: Caps {
+                    read: true,
+                    write: true,
+                    exec: false,
+                },
+                other_caps: Some(Caps::default()),
+            }
+        );
+
+        let input = quote! {
+            caps(raed, exec, exec, write = true),
+        };
+
+        assert_eq!(
+            FlagsAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E001): in `caps`: unrecognized key `raed`, did you mean `read`?",
+                "squattr(E002): in `caps`: duplicate key `exec`",
+                "squattr(E004): in `caps`: expected flag for `write`",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_recursive_attributes_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RecursiveAttribute {
+            name: String,
+            fallback: Option<Box<Self>>,
+            children: Vec<RecursiveAttribute>,
+        }
+
+        let input = quote! {
+            name = "a",
+            fallback(
+                name = "b",
+                fallback(name = "c", children()),
+                children(),
+            ),
+            children(children(name = "d", children())),
+        };
+
+        pretty_assertions::assert_eq!(
+            RecursiveAttribute::from_tokens(input).unwrap(),
+            RecursiveAttribute {
+                name: "a".into(),
+                fallback: Some(Box::new(RecursiveAttribute {
+                    name: "b".into(),
+                    fallback: Some(Box::new(RecursiveAttribute {
+                        name: "c".into(),
+                        fallback: None,
+                        children: vec![],
+                    })),
+                    children: vec![],
+                })),
+                children: vec![RecursiveAttribute {
+                    name: "d".into(),
+                    fallback: None,
+                    children: vec![],
+                }],
+            }
+        );
+
+        let input = quote! {
+            name = "a",
+            fallback(fallback(name = "c", children()), children()),
+            children(),
+        };
+
+        assert_eq!(
+            RecursiveAttribute::from_tokens(input)
+                .unwrap_err()
+                .to_string(),
+            "squattr(E003): in `fallback`: required key `name` not found"
+        );
+    }
+
+    #[test]
+    fn parse_syn_str_literals_derived() {
+        use squattr::types::ParseStrValue;
+        use syn::{Generics, Path, Type, WherePredicate, parse_quote};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct SynAttribute {
+            bound: WherePredicate,
+            bounds: Vec<WherePredicate>,
+            ty: Type,
+            path: Option<Path>,
+            generics: ParseStrValue<Generics>,
+        }
+
+        let input = quote! {
+            bound = "T: Clone",
+            bounds("T: Copy", "U: Default + Send"),
+            ty = "Vec<Option<u8>>",
+            path = "std::fmt::Debug",
+            generics = "<'a, T: 'a>",
+        };
+
+        pretty_assertions::assert_eq!(
+            SynAttribute::from_tokens(input).unwrap(),
+            SynAttribute {
+                bound: parse_quote!(T: Clone),
+                bounds: vec![parse_quote!(T: Copy), parse_quote!(U: Default + Send)],
+                ty: parse_quote!(Vec<Option<u8>>),
+                path: Some(parse_quote!(std::fmt::Debug)),
+                generics: ParseStrValue(parse_quote!(<'a, T: 'a>)),
+            }
+        );
+
+        let input = quote! {
+            bound = "T: Clone",
+            bounds(),
+            ty = "Vec<",
+            generics = "<T>",
+        };
+
+        let errors = SynAttribute::from_tokens(input)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("unexpected end of input"));
+    }
+
+    #[test]
+    fn parse_predicates_derived() {
+        use squattr::types::Predicate;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct PredicateAttribute {
+            enable_if: Predicate,
+            conditions: Vec<Predicate>,
+        }
+
+        let input = quote! {
+            enable_if(all(feature = "x", not(test), any(unix, !windows))),
+            conditions(test, feature = "y"),
+        };
+
+        let attribute = PredicateAttribute::from_tokens(input).unwrap();
+        let ident = |name| Ident::new(name, Span::call_site());
+
+        pretty_assertions::assert_eq!(
+            attribute,
+            PredicateAttribute {
+                enable_if: Predicate::All(vec![
+                    Predicate::KeyValue(ident("feature"), "x".into()),
+                    Predicate::Not(Box::new(Predicate::Flag(ident("test")))),
+                    Predicate::Any(vec![
+                        Predicate::Flag(ident("unix")),
+                        Predicate::Not(Box::new(Predicate::Flag(ident("windows")))),
+                    ]),
+                ]),
+                conditions: vec![
+                    Predicate::Flag(ident("test")),
+                    Predicate::KeyValue(ident("feature"), "y".into()),
+                ],
+            }
+        );
+
+        let mut is_set = |ident: &Ident, value: Option<&str>| match value {
+            Some(value) => ident == "feature" && value == "x",
+            None => ident == "unix",
+        };
+
+        assert!(attribute.enable_if.evaluate(&mut is_set));
+        assert!(!attribute.conditions[0].evaluate(&mut is_set));
+
+        let input = quote! {
+            enable_if(al(test)),
+            conditions(not(test, unix), feature = 1),
+        };
+
+        assert_eq!(
+            PredicateAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "unrecognized predicate `al`, did you mean `all`?",
+                "expected a single predicate for `not`",
+                "squattr(E004): expected string literal for `feature`",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_dotted_keys_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Limits {
+            max: u32,
+            min: Option<u32>,
+            strict: bool,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct DottedAttribute {
+            name: String,
+            limits: Limits,
+            other_limits: Option<Limits>,
+        }
+
+        let input = quote! {
+            limits.max = 10,
+            name = "x",
+            limits.min = 1,
+            limits.strict,
+            other_limits(max = 5),
+        };
+
+        pretty_assertions::assert_eq!(
+            DottedAttribute::from_tokens(input).unwrap(),
+            DottedAttribute {
+                name: "x".into(),
+                limits: Limits {
+                    max: 10,
+                    min: Some(1),
+                    strict: true,
+                },
+                other_limits: Some(Limits {
+                    max: 5,
+                    min: None,
+                    strict: false,
+                }),
+            }
+        );
+
+        let input = quote! {
+            name = "x",
+            limits(max = 10),
+            limits.min = 1,
+            other_limits.maxx = 5,
+        };
+
+        assert_eq!(
+            DottedAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E002): duplicate key `limits`",
+                "note: `limits` is first set here",
+                "squattr(E001): in `other_limits`: unrecognized key `maxx`, did you mean `max`?",
+                "squattr(E003): in `other_limits`: required key `max` not found",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_anonymous_lists_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct TupleAttribute {
+            points: Vec<(i32, i32)>,
+            matrix: Vec<Vec<u8>>,
+            corners: Option<[(i32, i32); 2]>,
+        }
+
+        let input = quote! {
+            points((1, 2), (3, -4)),
+            matrix((1, 2), (3), ()),
+            corners((0, 0), (5, 5)),
+        };
+
+        pretty_assertions::assert_eq!(
+            TupleAttribute::from_tokens(input).unwrap(),
+            TupleAttribute {
+                points: vec![(1, 2), (3, -4)],
+                matrix: vec![vec![1, 2], vec![3], vec![]],
+                corners: Some([(0, 0), (5, 5)]),
+            }
+        );
+
+        let input = quote! {
+            points((1, 2, 3)),
+            matrix(),
+            (1, 2),
+        };
+
+        assert_eq!(
+            TupleAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E004): expected list of 2 values, found 3 values",
+                "squattr(E004): expected identifier",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_parenthesized_exprs_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Limits {
+            max: u32,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct ParenthesizedAttribute {
+            deps: Vec<Ident>,
+            point: (i32, i32),
+            limits: Limits,
+        }
+
+        let input = quote! {
+            deps = (serde, tokio),
+            point = (1, 2),
+            limits = (max = 3),
+        };
+
+        pretty_assertions::assert_eq!(
+            ParenthesizedAttribute::from_tokens(input).unwrap(),
+            ParenthesizedAttribute {
+                deps: vec![
+                    Ident::new("serde", Span::call_site()),
+                    Ident::new("tokio", Span::call_site()),
+                ],
+                point: (1, 2),
+                limits: Limits { max: 3 },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_special_floats_derived() {
+        #[derive(Squattr, Debug)]
+        struct FloatAttribute {
+            upper: f64,
+            lower: f32,
+            threshold: f64,
+            bounds: Vec<f32>,
+        }
+
+        let input = quote! {
+            upper = inf,
+            lower = -inf,
+            threshold = nan,
+            bounds(-inf, 0.5, inf),
+        };
+
+        let attribute = FloatAttribute::from_tokens(input).unwrap();
+
+        assert_eq!(attribute.upper, f64::INFINITY);
+        assert_eq!(attribute.lower, f32::NEG_INFINITY);
+        assert!(attribute.threshold.is_nan());
+        assert_eq!(
+            attribute.bounds,
+            vec![f32::NEG_INFINITY, 0.5, f32::INFINITY]
+        );
+
+        let input = quote! {
+            upper = infinite,
+            lower = -nan,
+            threshold = 1,
+            bounds(),
+        };
+
+        assert_eq!(
+            FloatAttribute::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E004): expected decimal of type `f64` for `upper`, did you mean `inf`?",
+                "squattr(E004): expected decimal of type `f32` for `lower`, did you mean `nan`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_derive_input() {
+        use squattr::input::{FromDeriveInput, Input, InputData};
+        use syn::{DeriveInput, parse_quote};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct ContainerOptions {
+            rename: Option<String>,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct FieldOptions {
+            skip: bool,
+            default: Option<String>,
+        }
+
+        type MyInput = Input<ContainerOptions, ContainerOptions, FieldOptions>;
+
+        let derive_input: DeriveInput = parse_quote! {
+            #[my_attr(rename = "Bar")]
+            #[doc = "Not an option"]
+            struct Foo<T> {
+                #[my_attr(skip)]
+                a: T,
+                #[my_attr(default = "x")]
+                b: String,
+                c: bool,
+            }
+        };
+
+        let input = MyInput::from_derive_input(&derive_input, "my_attr").unwrap();
+
+        assert_eq!(input.ident, "Foo");
+        assert_eq!(input.generics.params.len(), 1);
+        assert_eq!(
+            input.options,
+            ContainerOptions {
+                rename: Some("Bar".into())
+            }
+        );
+
+        match input.data {
+            InputData::Struct(fields) => {
+                pretty_assertions::assert_eq!(
+                    fields
+                        .into_iter()
+                        .map(|field| (field.ident.unwrap().to_string(), field.options))
+                        .collect::<Vec<_>>(),
+                    vec![
+                        (
+                            "a".into(),
+                            FieldOptions {
+                                skip: true,
+                                default: None
+                            }
+                        ),
+                        (
+                            "b".into(),
+                            FieldOptions {
+                                skip: false,
+                                default: Some("x".into())
+                            }
+                        ),
+                        (
+                            "c".into(),
+                            FieldOptions {
+                                skip: false,
+                                default: None
+                            }
+                        ),
+                    ]
+                );
+            }
+            InputData::Enum(_) => panic!("input is not a struct"),
+        }
+
+        let derive_input: DeriveInput = parse_quote! {
+            #[my_attr(renam = "Bar")]
+            enum Foo {
+                #[my_attr(rename = "B")]
+                Bar(#[my_attr(skp)] u8),
+                Baz,
+            }
+        };
+
+        assert_eq!(
+            MyInput::from_derive_input(&derive_input, "my_attr")
+                .err()
+                .unwrap()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E001): unrecognized key `renam`, did you mean `rename`?",
+                "squattr(E001): unrecognized key `skp`, did you mean `skip`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_derive_input_derived() {
+        use squattr::input::{FromDeriveInput, InputData};
+        use syn::{DeriveInput, Generics, Ident, parse_quote};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct VariantOptions {
+            rename: Option<String>,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct FieldOptions {
+            skip: bool,
+        }
+
+        #[derive(Squattr)]
+        #[squattr(from_derive_input)]
+        struct MyInput {
+            ident: Ident,
+            generics: Generics,
+            data: InputData<VariantOptions, FieldOptions>,
+            rename_all: Option<String>,
+            path: Option<String>,
+        }
+
+        let derive_input: DeriveInput = parse_quote! {
+            #[my_attr(rename_all = "kebab-case", path = "crate::x")]
+            enum Foo<T> {
+                #[my_attr(rename = "b")]
+                Bar(#[my_attr(skip)] T),
+                Baz,
+            }
+        };
+
+        let input = MyInput::from_derive_input(&derive_input, "my_attr").unwrap();
+
+        assert_eq!(input.ident, "Foo");
+        assert_eq!(input.generics.params.len(), 1);
+        assert_eq!(input.rename_all.as_deref(), Some("kebab-case"));
+        assert_eq!(input.path.as_deref(), Some("crate::x"));
+
+        match input.data {
+            InputData::Enum(variants) => {
+                assert_eq!(
+                    variants
+                        .into_iter()
+                        .map(|variant| (
+                            variant.ident.to_string(),
+                            variant.options,
+                            variant
+                                .fields
+                                .into_iter()
+                                .map(|field| field.options)
+                                .collect::<Vec<_>>()
+                        ))
+                        .collect::<Vec<_>>(),
+                    vec![
+                        (
+                            "Bar".into(),
+                            VariantOptions {
+                                rename: Some("b".into())
+                            },
+                            vec![FieldOptions { skip: true }]
+                        ),
+                        ("Baz".into(), VariantOptions { rename: None }, vec![]),
+                    ]
+                );
+            }
+            InputData::Struct(_) => panic!("input is not an enum"),
+        }
+
+        let derive_input: DeriveInput = parse_quote! {
+            #[my_attr(renam_all = "kebab-case")]
+            struct Foo {
+                #[my_attr(skp)]
+                a: u8,
+            }
+        };
+
+        assert_eq!(
+            MyInput::from_derive_input(&derive_input, "my_attr")
+                .err()
+                .unwrap()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E001): unrecognized key `renam_all`, did you mean `rename_all`?",
+                "squattr(E001): unrecognized key `skp`, did you mean `skip`?",
+            ]
+        );
+
+        let derive_input: DeriveInput = parse_quote! {
+            #[my_attr(help)]
+            struct Foo;
+        };
+
+        assert!(
+            MyInput::from_derive_input(&derive_input, "my_attr")
+                .err()
+                .unwrap()
+                .to_string()
+                .starts_with("`MyInput` accepts the following keys:")
+        );
+    }
+
+    #[test]
+    fn parse_joined_attributes() {
+        use syn::{DeriveInput, parse_quote};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(path = "my_attr")]
+        struct MyAttribute {
+            a: Option<u8>,
+            b: Option<u8>,
+        }
+
+        let item: DeriveInput = parse_quote! {
+            #[my_attr(a = 1,)]
+            #[my_attr()]
+            #[my_attr(b = 2)]
+            struct Foo;
+        };
+
+        assert_eq!(
+            MyAttribute::from_attributes(&item.attrs, "my_attr").unwrap(),
+            MyAttribute {
+                a: Some(1),
+                b: Some(2)
+            }
+        );
+
+        let item: DeriveInput = parse_quote! {
+            #[my_attr()]
+            struct Foo;
+        };
+
+        assert_eq!(
+            MyAttribute::from_attributes(&item.attrs, "my_attr").unwrap(),
+            MyAttribute { a: None, b: None }
+        );
+
+        let item: DeriveInput = parse_quote! {
+            #[my_attr(a = 1,)]
+            #[my_attr(a = 2)]
+            struct Foo;
+        };
+
+        assert_eq!(
+            MyAttribute::from_attributes(&item.attrs, "my_attr")
+                .unwrap_err()
+                .to_string(),
+            "squattr(E002): duplicate key `a`"
+        );
+
+        let item: DeriveInput = parse_quote! {
+            #[my_attr(a = 1 2)]
+            struct Foo;
+        };
+
+        let error = MyAttribute::from_attributes(&item.attrs, "my_attr")
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(error, "in `#[my_attr(...)]`: expected `,`");
+        assert_eq!(
+            MyAttribute::from_attribute(&item.attrs[0])
+                .unwrap_err()
+                .to_string(),
+            error
+        );
+    }
+
+    #[test]
+    fn parse_single_attributes() {
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(path = "route")]
+        struct RouteAttribute {
+            path: String,
+        }
+
+        let attribute: syn::Attribute = syn::parse_quote!(#[route(path = "/a")]);
+
+        assert_eq!(
+            RouteAttribute::from_attribute(&attribute).unwrap(),
+            RouteAttribute { path: "/a".into() }
+        );
+
+        let attribute: syn::Attribute = syn::parse_quote!(#[route(path = 1)]);
+
+        assert_eq!(
+            RouteAttribute::from_attribute(&attribute)
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): in `#[route(...)]`: expected string literal of type `String` for `path`"
+        );
+
+        let attribute: syn::Attribute = syn::parse_quote!(#[serde(path = "/a")]);
+
+        assert_eq!(
+            RouteAttribute::from_attribute(&attribute)
+                .unwrap_err()
+                .to_string(),
+            "expected attribute `route`, found `serde`"
+        );
+    }
+
+    #[test]
+    fn parse_inline_attributes() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RouteAttribute {
+            path: String,
+            verbose: bool,
+        }
+
+        let path = "/b";
+
+        assert_eq!(
+            squattr::parse_attr!(RouteAttribute, path = "/a", verbose).unwrap(),
+            RouteAttribute {
+                path: "/a".into(),
+                verbose: true,
+            }
+        );
+        assert_eq!(
+            squattr::parse_attr!(RouteAttribute, path = #path).unwrap(),
+            RouteAttribute {
+                path: "/b".into(),
+                verbose: false,
+            }
+        );
+        assert_eq!(
+            squattr::parse_attr!(RouteAttribute)
+                .unwrap_err()
+                .to_string(),
+            "squattr(E003): required key `path` not found"
+        );
+    }
+
+    #[test]
+    fn parse_fields_and_variants() {
+        use syn::{Field, Variant, parse::Parser};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(path = "my_attr")]
+        struct MyOpts {
+            rename: Option<String>,
+            skip: bool,
+        }
+
+        let field = Field::parse_named
+            .parse2(quote! {
+                #[my_attr(rename = "id")]
+                #[doc = "Not an option"]
+                #[my_attr(skip)]
+                key: String
+            })
+            .unwrap();
+
+        assert_eq!(
+            MyOpts::from_field(&field).unwrap(),
+            MyOpts {
+                rename: Some("id".into()),
+                skip: true,
+            }
+        );
+
+        let variant: Variant = syn::parse2(quote! {
+            #[my_attr(skip, skip)]
+            Unused
+        })
+        .unwrap();
+
+        assert_eq!(
+            MyOpts::from_variant(&variant).unwrap_err().to_string(),
+            "squattr(E002): duplicate key `skip`"
+        );
+
+        let variant: Variant = syn::parse2(quote!(Plain)).unwrap();
+
+        assert_eq!(
+            MyOpts::from_variant(&variant).unwrap(),
+            MyOpts {
+                rename: None,
+                skip: false,
+            }
+        );
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct UnsetOpts {
+            skip: bool,
+        }
+
+        assert_eq!(
+            UnsetOpts::from_field(&field).unwrap_err().to_string(),
+            "no attribute path is set, set one with `#[squattr(path = \"...\")]`"
+        );
+        assert_eq!(
+            UnsetOpts::from_variant(&variant).unwrap_err().to_string(),
+            "no attribute path is set, set one with `#[squattr(path = \"...\")]`"
+        );
+    }
+
+    #[test]
+    fn extract_from_items() {
+        use quote::ToTokens;
+        use syn::{Field, parse::Parser};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RouteAttribute {
+            path: String,
+        }
+
+        let mut field = Field::parse_named
+            .parse2(quote! {
+                #[route(path = "/a")]
+                #[doc = "Not a route"]
+                #[route(path = "/b")]
+                handler: String
+            })
+            .unwrap();
+
+        pretty_assertions::assert_eq!(
+            RouteAttribute::parse_from_attributes(&field.attrs, "route").unwrap(),
+            vec![
+                RouteAttribute { path: "/a".into() },
+                RouteAttribute { path: "/b".into() },
+            ]
+        );
+        assert_eq!(field.attrs.len(), 3);
+
+        pretty_assertions::assert_eq!(
+            RouteAttribute::extract_from_attributes(&mut field, "route").unwrap(),
+            vec![
+                RouteAttribute { path: "/a".into() },
+                RouteAttribute { path: "/b".into() },
+            ]
+        );
+        assert_eq!(field.attrs.len(), 1);
+
+        let mut field = Field::parse_named
+            .parse2(quote! {
+                #[route(path = "/a")]
+                #[web::route(path = "/b")]
+                #[legacy_route(path = "/c")]
+                handler: String
+            })
+            .unwrap();
+
+        pretty_assertions::assert_eq!(
+            RouteAttribute::extract_from_attributes_with_paths(
+                &mut field,
+                &["web::route", "legacy_route"]
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(path, attr)| (quote!(#path).to_string(), attr))
+            .collect::<Vec<_>>(),
+            vec![
+                ("web :: route".into(), RouteAttribute { path: "/b".into() }),
+                ("legacy_route".into(), RouteAttribute { path: "/c".into() }),
+            ]
+        );
+        assert_eq!(field.attrs.len(), 1);
+
+        let mut field = Field::parse_named
+            .parse2(quote! {
+                #[route(path = "/a")]
+                #[doc = "Not a route"]
+                handler: String
+            })
+            .unwrap();
+
+        let extracted =
+            RouteAttribute::extract_from_attributes_with_originals(&mut field, "route").unwrap();
+
+        assert_eq!(extracted.len(), 1);
+        assert_eq!(
+            extracted[0].0.to_token_stream().to_string(),
+            quote!(#[route(path = "/a")]).to_string()
+        );
+        assert_eq!(extracted[0].1, RouteAttribute { path: "/a".into() });
+        assert_eq!(field.attrs.len(), 1);
+
+        #[cfg(feature = "full")]
+        {
+            let mut item_fn: syn::ItemFn = syn::parse_quote! {
+                #[route(path = "/c")]
+                fn handler() {}
+            };
+
+            pretty_assertions::assert_eq!(
+                RouteAttribute::extract_from_attributes(&mut item_fn, "route").unwrap(),
+                vec![RouteAttribute { path: "/c".into() }]
+            );
+            assert!(item_fn.attrs.is_empty());
+        }
+    }
+
+    #[test]
+    fn extract_with_attribute_path() {
+        use syn::{DeriveInput, parse_quote};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(path = "my_framework::route")]
+        struct RouteAttribute {
+            path: String,
+        }
+
+        assert_eq!(RouteAttribute::PATH, Some("my_framework::route"));
+
+        let mut input: DeriveInput = parse_quote! {
+            #[my_framework::route(path = "/a")]
+            #[route(path = "/b")]
+            struct Handler;
+        };
+
+        pretty_assertions::assert_eq!(
+            RouteAttribute::extract_one(&mut input).unwrap(),
+            Some(RouteAttribute { path: "/a".into() })
+        );
+        assert_eq!(RouteAttribute::extract(&mut input).unwrap(), vec![]);
+        assert_eq!(input.attrs.len(), 1);
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct UnsetAttribute {
+            path: String,
+        }
+
+        assert_eq!(
+            UnsetAttribute::extract(&mut input).unwrap_err().to_string(),
+            "no attribute path is set, set one with `#[squattr(path = \"...\")]`"
+        );
+        assert_eq!(
+            UnsetAttribute::extract_one(&mut input)
+                .unwrap_err()
+                .to_string(),
+            "no attribute path is set, set one with `#[squattr(path = \"...\")]`"
+        );
+        assert_eq!(input.attrs.len(), 1);
+
+        let mut input: DeriveInput = parse_quote! {
+            #[my_framework::route(path = "/a")]
+            #[my_framework::route(path = "/b")]
+            struct Handler;
+        };
+
+        assert_eq!(
+            RouteAttribute::extract_one(&mut input)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "attribute `my_framework::route` specified multiple times",
+                "attribute `my_framework::route` first specified here",
+            ]
+        );
+
+        let mut input: DeriveInput = parse_quote! {
+            #[route(path = "/a")]
+            struct Handler;
+        };
+
+        assert_eq!(
+            RouteAttribute::extract_exactly_one(&mut input, &["web::route", "get"])
+                .unwrap_err()
+                .to_string(),
+            "attribute `web::route` or `get` is required"
+        );
+        pretty_assertions::assert_eq!(
+            RouteAttribute::extract_exactly_one(&mut input, "route").unwrap(),
+            RouteAttribute { path: "/a".into() }
+        );
+        assert_eq!(
+            RouteAttribute::extract_at_most_one(&mut input, "route").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_attribute_macro_args() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct OptionalArgs {
+            name: Option<String>,
+            verbose: bool,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct RequiredArgs {
+            path: String,
+        }
+
+        pretty_assertions::assert_eq!(
+            OptionalArgs::from_args2(quote!()).unwrap(),
+            OptionalArgs {
+                name: None,
+                verbose: false
+            }
+        );
+        pretty_assertions::assert_eq!(
+            OptionalArgs::from_args2(quote!(name = "x", verbose)).unwrap(),
+            OptionalArgs {
+                name: Some("x".into()),
+                verbose: true
+            }
+        );
+        assert_eq!(
+            RequiredArgs::from_args2(quote!()).unwrap_err().to_string(),
+            "squattr(E003): required key `path` not found"
+        );
+    }
+
+    #[test]
+    fn parse_with_syn_parse_impl() {
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(parse)]
+        struct MacroArgs {
+            name: String,
+            verbose: bool,
+        }
+
+        pretty_assertions::assert_eq!(
+            syn::parse2::<MacroArgs>(quote!(name = "x", verbose)).unwrap(),
+            MacroArgs {
+                name: "x".into(),
+                verbose: true
+            }
+        );
+        assert_eq!(
+            syn::parse2::<MacroArgs>(quote!(verbose))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E003): required key `name` not found"
+        );
+    }
+
+    #[test]
+    fn parse_nested_meta() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Route {
+            path: String,
+        }
+
+        fn parse_api(attr: &syn::Attribute) -> syn::Result<(Vec<Route>, Vec<String>)> {
+            let mut routes = Vec::new();
+            let mut others = Vec::new();
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("route") {
+                    routes.push(Route::from_nested_meta(&meta)?);
+                } else {
+                    let value = squattr::ast::Value::from_nested_meta(&meta)?;
+                    others.extend(value.identifier());
+                }
+                Ok(())
+            })?;
+
+            Ok((routes, others))
+        }
+
+        let attr: syn::Attribute = syn::parse_quote! {
+            #[api(version = 2, route(path = "/a"), route(path = "/b"), internal)]
+        };
+
+        pretty_assertions::assert_eq!(
+            parse_api(&attr).unwrap(),
+            (
+                vec![Route { path: "/a".into() }, Route { path: "/b".into() }],
+                vec!["version".into(), "internal".into()]
+            )
+        );
+
+        let attr: syn::Attribute = syn::parse_quote!(#[api(route)]);
+        assert_eq!(
+            parse_api(&attr).unwrap_err().to_string(),
+            "squattr(E003): required key `path` not found"
+        );
+
+        let attr: syn::Attribute = syn::parse_quote!(#[api(route = "/a")]);
+        assert_eq!(
+            parse_api(&attr).unwrap_err().to_string(),
+            "expected a list of values"
+        );
+    }
+
+    #[test]
+    fn write_attributes_derived() {
+        use quote::ToTokens;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Limits {
+            min: u32,
+            max: Option<f64>,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(flags, to_tokens)]
+        struct Methods {
+            get: bool,
+            post: bool,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Route {
+            path: String,
+            methods: Methods,
+            tags: Vec<Ident>,
+            verbose: bool,
+            retries: Option<u8>,
+            limits: Vec<Limits>,
+        }
+
+        let mut route = Route::from_tokens(quote! {
+            path = "/a",
+            methods(get),
+            tags(public, v1),
+            verbose,
+            limits((min = 1), (min = 2, max = 2.5)),
+        })
+        .unwrap();
+
+        assert_eq!(
+            route.to_token_stream().to_string(),
+            quote! {
+                path = "/a",
+                methods(get),
+                tags(public, v1),
+                verbose,
+                limits((min = 1), (min = 2, max = 2.5))
+            }
+            .to_string()
+        );
+
+        route.verbose = false;
+        route.retries = Some(3);
+        route.methods.post = true;
+
+        pretty_assertions::assert_eq!(Route::from_tokens(route.to_token_stream()).unwrap(), route);
+    }
+
+    #[test]
+    fn write_enums_derived() {
+        use quote::ToTokens;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(rename_all = "kebab-case")]
+        enum Mode {
+            Fast,
+            VerySlow,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        enum IntOrString {
+            Int(u64),
+            Str(String),
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        enum Source {
+            File { path: String, watch: bool },
+            Env { var: Option<String> },
+            Stdin,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Config {
+            mode: Option<Mode>,
+            modes: Vec<Mode>,
+            limit: IntOrString,
+            source: Source,
+            sources: Vec<Source>,
+        }
+
+        let config = Config::from_tokens(quote! {
+            mode = "very-slow",
+            modes(fast),
+            limit = "none",
+            source(file(path = "x", watch)),
+            sources(env(), stdin),
+        })
+        .unwrap();
+
+        assert_eq!(
+            config.to_token_stream().to_string(),
+            quote! {
+                mode = "very-slow",
+                modes("fast"),
+                limit = "none",
+                source(file(path = "x", watch)),
+                sources(env(), stdin)
+            }
+            .to_string()
+        );
+
+        pretty_assertions::assert_eq!(
+            Config::from_tokens(config.to_token_stream()).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn describe_attributes_derived() {
+        use squattr::schema::{Kind, Schema};
+
+        #[derive(Squattr)]
+        #[squattr(rename_all = "kebab-case")]
+        enum Mode {
+            Fast,
+            VerySlow,
+        }
+
+        #[derive(Squattr)]
+        #[squattr(flags)]
+        #[allow(dead_code)]
+        struct Methods {
+            get: bool,
+            post: bool,
+        }
+
+        #[derive(Squattr)]
+        #[allow(dead_code)]
+        enum Auth {
+            Token { header: String },
+            Anonymous {},
+        }
+
+        #[derive(Squattr)]
+        #[allow(dead_code)]
+        struct Route {
+            path: String,
+            mode: Mode,
+            methods: Methods,
+            auth: Option<Auth>,
+            verbose: bool,
+            fallback: Option<Box<Route>>,
+            tags: Vec<Ident>,
+        }
+
+        let Schema::Struct { name, keys, .. } = Route::SCHEMA else {
+            panic!("expected a struct schema");
+        };
+
+        assert_eq!(name, "Route");
+        assert_eq!(
+            keys.iter()
+                .map(|key| (key.name, key.required))
+                .collect::<Vec<_>>(),
+            [
+                ("path", true),
+                ("mode", true),
+                ("methods", true),
+                ("auth", false),
+                ("verbose", false),
+                ("fallback", false),
+                ("tags", true),
+            ]
+        );
+
+        assert!(matches!(keys[0].kind, Kind::String));
+        assert!(matches!(keys[1].kind, Kind::OneOf(["fast", "very-slow"])));
+        assert!(matches!(keys[4].kind, Kind::Bool));
+        assert!(matches!(keys[6].kind, Kind::List(Kind::Ident)));
+
+        let Kind::Nested(methods) = keys[2].kind else {
+            panic!("expected a nested attribute");
+        };
+        let Schema::Flags { flags, .. } = methods() else {
+            panic!("expected a flags schema");
+        };
+        assert_eq!(
+            flags.iter().map(|flag| flag.name).collect::<Vec<_>>(),
+            ["get", "post"]
+        );
+
+        let Kind::Nested(auth) = keys[3].kind else {
+            panic!("expected a nested attribute");
+        };
+        let Schema::Tagged { variants, .. } = auth() else {
+            panic!("expected a tagged schema");
+        };
+        assert_eq!(variants[0].name, "token");
+        assert_eq!(variants[0].keys[0].name, "header");
+        assert!(variants[1].keys.is_empty());
+
+        let Kind::Nested(fallback) = keys[5].kind else {
+            panic!("expected a nested attribute");
+        };
+        assert_eq!(fallback().name(), Some("Route"));
+    }
+
+    #[test]
+    fn omit_optional_types_derived() {
+        use squattr::schema::{Kind, Schema};
+
+        type MaybeString = Option<String>;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Route {
+            path: String,
+            retries: core::option::Option<u8>,
+            #[squattr(optional)]
+            method: MaybeString,
+            verbose: core::primitive::bool,
+        }
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/a")).unwrap(),
+            Route {
+                path: "/a".into(),
+                retries: None,
+                method: None,
+                verbose: false,
+            }
+        );
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/a", method = "GET", retries = 3))
+                .unwrap()
+                .method,
+            Some("GET".into())
+        );
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/a", method = 1))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): expected string literal of type `MaybeString` for `method`"
+        );
+
+        let Schema::Struct { keys, .. } = Route::SCHEMA else {
+            panic!("expected a struct schema");
+        };
+
+        assert_eq!(
+            keys.iter()
+                .map(|key| (key.name, key.required))
+                .collect::<Vec<_>>(),
+            [
+                ("path", true),
+                ("retries", false),
+                ("method", false),
+                ("verbose", false)
+            ]
+        );
+        assert!(matches!(keys[2].kind, Kind::String));
+    }
+
+    #[test]
+    fn parse_flags_states_derived() {
+        use quote::ToTokens;
+        use squattr::types::Flag;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Options {
+            cache: Flag,
+            debug: Flag,
+            verbose: Flag,
+            strict: Flag,
+        }
+
+        let span = Span::call_site();
+        let options = Options::from_tokens(quote!(cache, debug = false, !verbose)).unwrap();
+
+        assert_eq!(
+            options,
+            Options {
+                cache: Flag::Set(span),
+                debug: Flag::Explicit(false, span),
+                verbose: Flag::Negated(span),
+                strict: Flag::Absent,
+            }
+        );
+
+        assert_eq!(options.cache.get(), Some(true));
+        assert_eq!(options.debug.get(), Some(false));
+        assert_eq!(options.strict.get(), None);
+        assert!(!options.strict.value());
+        assert!(options.debug.is_present());
+        assert!(options.strict.span().is_none());
+
+        assert_eq!(
+            options.to_token_stream().to_string(),
+            quote!(cache, debug = false, !verbose).to_string()
+        );
+
+        assert_eq!(
+            Options::from_tokens(quote!(cache = 1))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): expected boolean (`true`, `false`) of type `Flag` for `cache`"
+        );
+    }
+
+    #[test]
+    fn to_tokens_raw_identifiers_derived() {
+        use quote::ToTokens;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Field {
+            r#type: String,
+            r#ref: bool,
+        }
+
+        let field = Field::from_tokens(quote!(r#type = "u8", r#ref)).unwrap();
+
+        assert_eq!(
+            field,
+            Field {
+                r#type: "u8".into(),
+                r#ref: true,
+            }
+        );
+        assert_eq!(
+            field.to_token_stream().to_string(),
+            quote!(r#type = "u8", r#ref).to_string()
+        );
+        assert_eq!(Field::from_tokens(field.to_token_stream()).unwrap(), field);
+    }
+
+    #[test]
+    fn tell_bare_keys_from_absent_keys_derived() {
+        use quote::ToTokens;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Field {
+            default: Option<Option<String>>,
+            rename: Option<Option<String>>,
+            skip: Option<Option<bool>>,
+        }
+
+        let field = Field::from_tokens(quote!(default, rename = "id")).unwrap();
+
+        assert_eq!(
+            field,
+            Field {
+                default: Some(None),
+                rename: Some(Some("id".into())),
+                skip: None,
+            }
+        );
+
+        assert_eq!(
+            field.to_token_stream().to_string(),
+            quote!(default, rename = "id").to_string()
+        );
+
+        assert_eq!(
+            Field::from_tokens(quote!(default, default = "x"))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E002): duplicate key `default`"
+        );
+
+        assert_eq!(Field::from_tokens(quote!(skip)).unwrap().skip, Some(None));
+        assert_eq!(
+            Field::from_tokens(quote!(skip = true)).unwrap().skip,
+            Some(Some(true))
+        );
+        assert_eq!(
+            Field::from_tokens(quote!(skip = false)).unwrap().skip,
+            Some(Some(false))
+        );
+
+        // Only a key is told apart from its value, an entry is always given.
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Tags {
+            tags: Vec<Option<Ident>>,
+        }
+
+        assert_eq!(
+            Tags::from_tokens(quote!(tags(foo, bar))).unwrap(),
+            Tags {
+                tags: vec![
+                    Some(Ident::new("foo", Span::call_site())),
+                    Some(Ident::new("bar", Span::call_site())),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn require_optional_types_derived() {
+        use quote::ToTokens;
+        use squattr::schema::Schema;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Column {
+            #[squattr(required)]
+            default: Option<String>,
+            #[squattr(required)]
+            unique: bool,
+        }
+
+        assert_eq!(
+            Column::from_tokens(quote!(default = "x", unique)).unwrap(),
+            Column {
+                default: Some("x".into()),
+                unique: true,
+            }
+        );
+
+        let column = Column::from_tokens(quote!(default, unique = false)).unwrap();
+
+        assert_eq!(
+            column,
+            Column {
+                default: None,
+                unique: false,
+            }
+        );
+
+        assert_eq!(
+            column.to_token_stream().to_string(),
+            quote!(default).to_string()
+        );
+
+        assert_eq!(
+            Column::from_tokens(quote!())
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "squattr(E003): required key `default` not found",
+                "squattr(E003): required key `unique` not found"
+            ]
+        );
+
+        let Schema::Struct { keys, .. } = Column::SCHEMA else {
+            panic!("expected a struct schema");
+        };
+
+        assert!(keys.iter().all(|key| key.required));
+    }
+
+    #[test]
+    fn convert_into_field_types_derived() {
+        use std::sync::Arc;
+
+        #[derive(PartialEq, Debug)]
+        struct Id(String);
+
+        impl From<String> for Id {
+            fn from(id: String) -> Self {
+                Self(id)
+            }
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct User {
+            #[squattr(into = "String")]
+            name: Arc<str>,
+            #[squattr(into = "String")]
+            id: Id,
+            #[squattr(into = "String")]
+            group: Option<Arc<str>>,
+        }
+
+        assert_eq!(
+            User::from_tokens(quote!(name = "root", id = "0")).unwrap(),
+            User {
+                name: "root".into(),
+                id: Id("0".into()),
+                group: None,
+            }
+        );
+
+        assert_eq!(
+            User::from_tokens(quote!(name = "root", id = "0", group = "wheel"))
+                .unwrap()
+                .group,
+            Some("wheel".into())
+        );
+
+        assert_eq!(
+            User::from_tokens(quote!(name = 0, id = "0"))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): expected string literal of type `Arc<str>` for `name`"
+        );
+    }
+
+    #[test]
+    fn convert_try_from_field_types_derived() {
+        #[derive(PartialEq, Debug)]
+        struct Port(u16);
+
+        impl TryFrom<u16> for Port {
+            type Error = &'static str;
+
+            fn try_from(port: u16) -> Result<Self, Self::Error> {
+                match port {
+                    0 => Err("port 0 is reserved"),
+                    port => Ok(Self(port)),
+                }
+            }
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Listen {
+            #[squattr(try_from = "u16")]
+            port: Port,
+            #[squattr(try_from = "u16")]
+            admin_port: Option<Port>,
+        }
+
+        assert_eq!(
+            Listen::from_tokens(quote!(port = 80, admin_port = 8080)).unwrap(),
+            Listen {
+                port: Port(80),
+                admin_port: Some(Port(8080)),
+            }
+        );
+
+        assert_eq!(
+            Listen::from_tokens(quote!(port = 0, admin_port = 0))
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            ["port 0 is reserved", "port 0 is reserved",]
+        );
+    }
+
+    #[test]
+    fn check_numeric_ranges_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Server {
+            #[squattr(min = 1, max = 65535)]
+            port: u32,
+            #[squattr(min = 0.0)]
+            timeout: Option<f64>,
+            #[squattr(max = -1)]
+            offset: Option<i8>,
+        }
+
+        assert_eq!(
+            Server::from_tokens(quote!(port = 65535, timeout = 0.5, offset = -2)).unwrap(),
+            Server {
+                port: 65535,
+                timeout: Some(0.5),
+                offset: Some(-2),
+            }
+        );
+
+        assert_eq!(
+            Server::from_tokens(quote!(port = 0, timeout = -0.5, offset = 1))
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "expected a value between 1 and 65535, found 0",
+                "expected a value of at least 0, found -0.5",
+                "expected a value of at most -1, found 1",
+            ]
+        );
+
+        assert_eq!(
+            Server::from_tokens(quote!(port = 80, port = 0))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E002): duplicate key `port`"
+        );
+    }
+
+    #[test]
+    fn check_list_items_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Route {
+            #[squattr(min_items = 1, max_items = 2, unique)]
+            methods: Vec<String>,
+            #[squattr(unique)]
+            tags: Option<Vec<u8>>,
+        }
+
+        assert_eq!(
+            Route::from_tokens(quote!(methods("get", "post"), tags(1, 2))).unwrap(),
+            Route {
+                methods: vec!["get".into(), "post".into()],
+                tags: Some(vec![1, 2]),
+            }
+        );
+
+        let errors = |input| {
+            Route::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors(quote!(methods(), tags = (1, 2, 1))),
+            [
+                "expected between 1 and 2 values, found 0",
+                "duplicate value"
+            ]
+        );
+        assert_eq!(
+            errors(quote!(methods("get", "get", "post"))),
+            ["expected between 1 and 2 values, found 3"]
+        );
+        assert_eq!(errors(quote!(methods("get", "get"))), ["duplicate value"]);
+
+        // The values of a repeated key are checked together.
+        assert_eq!(
+            Route::from_tokens(quote!(methods("get"), methods())).unwrap(),
+            Route {
+                methods: vec!["get".into()],
+                tags: None,
+            }
+        );
+        assert_eq!(
+            errors(quote!(methods("get", "post"), methods("put"))),
+            ["expected between 1 and 2 values, found 3"]
+        );
+        assert_eq!(
+            errors(quote!(methods("get"), methods("get"))),
+            ["duplicate value"]
+        );
+        assert_eq!(
+            errors(quote!(methods("get"), tags(1), tags(2, 1))),
+            ["duplicate value"]
+        );
+    }
+
+    #[test]
+    fn check_non_empty_strings_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Module {
+            #[squattr(non_empty)]
+            name: String,
+            #[squattr(non_empty)]
+            rename: Option<String>,
+        }
+
+        assert_eq!(
+            Module::from_tokens(quote!(name = "a")).unwrap(),
+            Module {
+                name: "a".into(),
+                rename: None,
+            }
+        );
+
+        assert_eq!(
+            Module::from_tokens(quote!(name = "", rename = ""))
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            ["expected a non-empty string", "expected a non-empty string"]
+        );
+    }
+
+    #[test]
+    fn check_related_keys_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Route {
+            path: Option<String>,
+            #[squattr(conflicts_with("path"))]
+            url: Option<String>,
+            #[squattr(requires("path"))]
+            strict: bool,
+        }
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/", strict)).unwrap(),
+            Route {
+                path: Some("/".into()),
+                url: None,
+                strict: true,
+            }
+        );
+
+        let errors = |input| {
+            Route::from_tokens(input)
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors(quote!(path = "/", url = "/")),
+            [
+                "key `url` conflicts with `path`",
+                "note: `path` is set here"
+            ]
+        );
+        assert_eq!(
+            errors(quote!(url = "/", strict)),
+            ["key `strict` requires `path` to be set"]
+        );
+    }
+
+    #[test]
+    fn merge_over_base_derived() {
+        use squattr::types::{Flag, Spans};
+
+        #[derive(Squattr, PartialEq, Debug, Clone)]
+        #[squattr(merge)]
+        struct Column {
+            rename: Option<String>,
+            skip: Flag,
+            width: u8,
+        }
+
+        let base = Column::from_tokens(quote!(rename = "id", skip, width = 8)).unwrap();
+        let column = Column::from_tokens(quote!(width = 4)).unwrap();
+
+        assert_eq!(
+            column.merge_over(&base),
+            Column {
+                rename: Some("id".into()),
+                skip: Flag::Set(Span::call_site()),
+                width: 4,
+            }
+        );
+
+        #[derive(Squattr, Debug, Clone)]
+        #[squattr(merge)]
+        struct Options {
+            strict: bool,
+            depth: u8,
+            #[squattr(spans)]
+            spans: Spans,
+        }
+
+        let base = Options::from_tokens(quote!(strict, depth = 2)).unwrap();
+        let options = Options::from_tokens(quote!(depth = 3))
+            .unwrap()
+            .merge_over(&base);
+
+        assert!(options.strict);
+        assert_eq!(options.depth, 3);
+    }
+
+    #[test]
+    fn fold_arithmetic_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Buffer {
+            len: u32,
+            retries: Option<u8>,
+        }
+
+        assert_eq!(
+            Buffer::from_tokens(quote!(len = 4 * 1024, retries = (1 + 2) * 3)).unwrap(),
+            Buffer {
+                len: 4096,
+                retries: Some(9),
+            }
+        );
+        assert_eq!(
+            Buffer::from_tokens(quote!(len = 1, retries = 60 * 5))
+                .unwrap_err()
+                .to_string(),
+            "number too large to fit in target type"
+        );
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Offset {
+            delta: i32,
+        }
+
+        assert_eq!(
+            Offset::from_tokens(quote!(delta = -(5))).unwrap(),
+            Offset { delta: -5 }
+        );
+        assert_eq!(
+            Offset::from_tokens(quote!(delta = -(2 + 3) * 2)).unwrap(),
+            Offset { delta: -10 }
+        );
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn parse_closures_derived() {
+        use squattr::tokens::ToValueTokens;
+        use syn::ExprClosure;
+
+        #[derive(Squattr)]
+        struct Builder {
+            map: ExprClosure,
+            validate: Option<ExprClosure>,
+        }
+
+        let builder = Builder::from_tokens(quote!(
+            map = |x: u32| x + 1,
+            validate = move |s| !s.is_empty()
+        ))
+        .unwrap();
+
+        assert_eq!(builder.map.inputs.len(), 1);
+        assert_eq!(
+            builder.map.to_value_tokens().to_string(),
+            quote!(|x: u32| x + 1).to_string()
+        );
+        assert!(builder.validate.unwrap().capture.is_some());
+        assert_eq!(
+            Builder::from_tokens(quote!(map = "x + 1"))
+                .err()
+                .unwrap()
+                .to_string(),
+            "squattr(E004): expected closure of type `ExprClosure` for `map`"
+        );
+    }
+
+    #[test]
+    fn parse_ranges_derived() {
+        use std::ops::{Range, RangeInclusive};
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Field {
+            bits: Range<u8>,
+            levels: Option<RangeInclusive<u32>>,
+        }
+
+        assert_eq!(
+            Field::from_tokens(quote!(bits = 0..8, levels = 1..=3)).unwrap(),
+            Field {
+                bits: 0..8,
+                levels: Some(1..=3),
+            }
+        );
+        assert_eq!(
+            Field::from_tokens(quote!(bits = 0..=7))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): expected range (`a..b`) of type `Range<u8>` for `bits`"
+        );
+        assert_eq!(
+            Field::from_tokens(quote!(bits = 0..256))
+                .unwrap_err()
+                .to_string(),
+            "number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn merge_with_strategies_derived() {
+        use squattr::types::Spans;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(merge)]
+        struct Route {
+            path: Option<String>,
+            #[squattr(merge = "first")]
+            name: Option<String>,
+            #[squattr(merge = "append")]
+            middleware: Vec<String>,
+            #[squattr(merge = "append")]
+            tags: Option<Vec<String>>,
+        }
+
+        let first =
+            Route::from_tokens(quote!(path = "/a", name = "a", middleware("auth"))).unwrap();
+        let last = Route::from_tokens(quote!(
+            path = "/b",
+            name = "b",
+            middleware("log"),
+            tags("x")
+        ))
+        .unwrap();
+
+        assert_eq!(
+            first.merge(last).unwrap(),
+            Route {
+                path: Some("/b".into()),
+                name: Some("a".into()),
+                middleware: vec!["auth".into(), "log".into()],
+                tags: Some(vec!["x".into()]),
+            }
+        );
+
+        #[derive(Squattr, Debug)]
+        #[squattr(merge)]
+        struct Options {
+            #[squattr(merge = "error")]
+            depth: Option<u8>,
+            strict: bool,
+            #[squattr(spans)]
+            spans: Spans,
+        }
+
+        let first = Options::from_tokens(quote!(depth = 1)).unwrap();
+        let last = Options::from_tokens(quote!(strict)).unwrap();
+        let merged = first.merge(last).unwrap();
+
+        assert_eq!((merged.depth, merged.strict), (Some(1), true));
+        assert!(merged.spans.contains("depth") && merged.spans.contains("strict"));
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(merge)]
+        struct Limits {
+            strict: bool,
+            width: Option<u8>,
+        }
+
+        let first = Limits::from_tokens(quote!(strict)).unwrap();
+        let last = Limits::from_tokens(quote!(width = 8)).unwrap();
+
+        assert_eq!(
+            first.merge(last).unwrap(),
+            Limits {
+                strict: false,
+                width: Some(8),
+            }
+        );
+
+        let first = Options::from_tokens(quote!(depth = 1)).unwrap();
+        let last = Options::from_tokens(quote!(depth = 2)).unwrap();
+
+        assert_eq!(
+            first
+                .merge(last)
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "squattr(E002): duplicate key `depth`",
+                "note: `depth` is first set here"
+            ]
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn fill_defaults_from_toml_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Retry {
+            count: u8,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(path = "route", defaults_file = "tests/defaults/squattr.toml")]
+        struct Route {
+            path: String,
+            method: String,
+            middleware: Vec<String>,
+            retry: Option<Retry>,
+        }
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/", middleware("cors"))).unwrap(),
+            Route {
+                path: "/".into(),
+                method: "GET".into(),
+                middleware: vec!["cors".into()],
+                retry: Some(Retry { count: 3 }),
+            }
+        );
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        #[squattr(defaults_file = "tests/defaults/missing.toml")]
+        struct Missing {
+            method: String,
+        }
+
+        assert_eq!(
+            Missing::from_tokens(quote!()).unwrap_err().to_string(),
+            "squattr(E003): required key `method` not found"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn fill_defaults_from_json_derived() {
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(defaults_file = "tests/defaults/squattr.json")]
+        struct Request {
+            method: String,
+            timeout: Option<u32>,
+        }
+
+        assert_eq!(
+            Request::from_tokens(quote!(method = "PUT")).unwrap(),
+            Request {
+                method: "PUT".into(),
+                timeout: Some(30),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_with_options_derived() {
+        use squattr::{
+            ast::Values,
+            diagnostic,
+            runtime::{OnUnknown, ParseOptions},
+        };
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Retry {
+            count: u8,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        struct Route {
+            path: String,
+            retry: Option<Retry>,
+        }
+
+        let values = || -> Values {
+            syn::parse2(quote!(path = "/", methd = get, retry(count = 2, delay = 1))).unwrap()
+        };
+
+        let expected = Route {
+            path: "/".into(),
+            retry: Some(Retry { count: 2 }),
+        };
+
+        assert_eq!(
+            Route::from_values(values())
+                .unwrap_err()
+                .into_iter()
+                .count(),
+            2
+        );
+
+        let ignore = ParseOptions {
+            on_unknown: OnUnknown::Ignore,
+        };
+
+        let (route, warnings) = diagnostic::collect(|| Route::from_values_with(values(), ignore));
+        assert_eq!(route.unwrap(), expected);
+        assert!(warnings.is_empty());
+
+        let warn = ParseOptions {
+            on_unknown: OnUnknown::Warn,
+        };
+
+        let (route, warnings) = diagnostic::collect(|| Route::from_values_with(values(), warn));
+        assert_eq!(route.unwrap(), expected);
+
+        let warnings = warnings
+            .iter()
+            .map(|warning| warning.message())
+            .collect::<Vec<_>>();
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("unrecognized key `methd`"))
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("unrecognized key `delay`"))
+        );
+
+        // The options only last for the parse they were given to.
+        assert_eq!(ParseOptions::current(), ParseOptions::STRICT);
+        assert!(Route::from_values(values()).is_err());
+    }
+
+    #[test]
+    fn count_repeated_flags_derived() {
+        use quote::ToTokens;
+        use squattr::types::Count;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Options {
+            verbose: Count,
+            quiet: Count,
+            path: Option<String>,
+        }
+
+        let options = Options::from_tokens(quote!(verbose, path = "/", verbose, verbose)).unwrap();
+
+        assert_eq!(
+            options,
+            Options {
+                verbose: Count(3),
+                quiet: Count(0),
+                path: Some("/".into()),
+            }
+        );
+
+        assert_eq!(
+            options.to_token_stream().to_string(),
+            quote!(verbose, verbose, verbose, path = "/").to_string()
+        );
+
+        assert_eq!(
+            Options::from_tokens(quote!(verbose = 2))
+                .unwrap_err()
+                .to_string(),
+            "squattr(E004): expected flag of type `Count` for `verbose`"
+        );
+    }
+
+    #[test]
+    fn document_attributes_derived() {
+        /// The methods a route accepts.
+        #[derive(Squattr)]
+        #[squattr(flags)]
+        #[allow(dead_code)]
+        struct Methods {
+            /// Accept `GET` requests.
+            get: bool,
+            post: bool,
+        }
+
+        #[derive(Squattr)]
+        #[allow(dead_code)]
+        enum Auth {
+            /// Authenticate with a token.
+            Token {
+                /// The header holding the token.
+                header: String,
+            },
+            Anonymous {},
+        }
+
+        #[derive(Squattr)]
+        #[allow(dead_code)]
+        enum IntOrString {
+            Int(u64),
+            Str(String),
+        }
+
+        /// A route of the server.
+        ///
+        /// Routes are matched in order.
+        #[derive(Squattr)]
+        #[allow(dead_code)]
+        struct Route {
+            /// The path of the route, e.g. `"/a"`.
+            path: String,
+            /// The accepted
+            /// methods.
+            methods: Methods,
+            auth: Option<Auth>,
+            /// Log every request.
+            verbose: bool,
+            tags: Vec<Ident>,
+            /// Either `a` | `b`.
+            mode: Option<IntOrString>,
+        }
+
+        pretty_assertions::assert_eq!(
+            Route::SCHEMA.to_markdown(),
+            r#"## `Route`
+
+A route of the server.
+
+Routes are matched in order.
+
+| Key | Type | Default | Description |
+| --- | --- | --- | --- |
+| `path` | string | *required* | The path of the route, e.g. `"/a"`. |
+| `methods` | `Methods` | *required* | The accepted methods. |
+| `auth` | `Auth` | - |  |
+| `verbose` | bool | `false` | Log every request. |
+| `tags` | list of identifier | *required* |  |
+| `mode` | integer or string | - | Either `a` \| `b`. |
+
+## `Methods`
+
+The methods a route accepts.
+
+| Flag | Description |
+| --- | --- |
+| `get` | Accept `GET` requests. |
+| `post` |  |
+
+## `Auth`
+
+Given as exactly one of the following variants.
+
+### `token`
+
+Authenticate with a token.
+
+| Key | Type | Default | Description |
+| --- | --- | --- | --- |
+| `header` | string | *required* | The header holding the token. |
+
+### `anonymous`
+
+Takes no keys.
+"#
+        );
+    }
+
+    #[test]
+    fn help_attributes_derived() {
+        /// The methods a route accepts.
+        #[derive(Squattr, Debug)]
+        #[squattr(flags)]
+        #[allow(dead_code)]
+        struct Methods {
+            /// Accept `GET` requests.
+            get: bool,
+            post: bool,
+        }
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        enum Auth {
+            Token {
+                /// The header holding the token.
+                header: String,
+            },
+            Anonymous {},
+        }
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Route {
+            /// The path of the route.
+            ///
+            /// Must start with a slash.
+            path: String,
+            methods: Methods,
+            auth: Option<Auth>,
+            verbose: bool,
+        }
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Manual {
+            help: Option<String>,
+        }
+
+        assert_eq!(
+            Route::from_tokens(quote!(verbose, help))
+                .unwrap_err()
+                .to_string(),
+            "`Route` accepts the following keys:\n  \
+             `path`: string (required) - The path of the route.\n  \
+             `methods`: `Methods` (required)\n  \
+             `auth`: `Auth`\n  \
+             `verbose`: bool (default `false`)"
+        );
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/", methods(help)))
+                .unwrap_err()
+                .to_string(),
+            "in `methods`: `Methods` accepts the following flags:\n  \
+             `get` - Accept `GET` requests.\n  \
+             `post`"
+        );
+
+        assert_eq!(
+            Route::from_tokens(quote!(path = "/", methods(get), auth(help)))
+                .unwrap_err()
+                .to_string(),
+            "in `auth`: `Auth` accepts one of the following variants:\n  \
+             `token`, with the keys:\n    \
+             `header`: string (required) - The header holding the token.\n  \
+             `anonymous`"
+        );
+
+        assert_eq!(
+            Manual::from_tokens(quote!(help = "me")).unwrap().help,
+            Some("me".to_string())
+        );
+    }
+
+    #[test]
+    fn locate_errors_derived() {
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Limits {
+            count: u32,
+            name: Option<String>,
+            sizes: Option<(u8, u8)>,
+        }
+
+        let columns = |input: &str| {
+            let error = Limits::from_tokens(input.parse::<proc_macro2::TokenStream>().unwrap())
+                .unwrap_err();
+
+            error
+                .into_iter()
+                .map(|error| (error.to_string(), error.span().start().column))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            columns(r#"count = "three", name = "a", name = "b", sizes = 1"#),
+            [
+                (
+                    "squattr(E004): expected integer of type `u32` for `count`".to_string(),
+                    8
+                ),
+                ("squattr(E002): duplicate key `name`".to_string(), 29),
+                ("note: `name` is first set here".to_string(), 17),
+                (
+                    "squattr(E004): expected list of 2 values of type `(u8, u8)` for `sizes`"
+                        .to_string(),
+                    49
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn locate_nested_errors_derived() {
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Retry {
+            attempts: u32,
+        }
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Client {
+            retry: Retry,
+        }
+
+        let input = "retry(backoff = 2)".parse::<proc_macro2::TokenStream>();
+        let error = Client::from_tokens(input.unwrap()).unwrap_err();
+
+        let locations = error
+            .into_iter()
+            .map(|error| {
+                let span = error.span();
+                (error.to_string(), span.start().column, span.end().column)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations,
+            [
+                (
+                    "squattr(E001): in `retry`: unrecognized key `backoff`, expected `attempts`"
+                        .to_string(),
+                    6,
+                    13
+                ),
+                (
+                    "squattr(E003): in `retry`: required key `attempts` not found".to_string(),
+                    0,
+                    18
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_with_error_context() {
+        use syn::{DeriveInput, parse_quote};
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Retry {
+            count: u32,
+        }
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct Options {
+            retry: Option<Retry>,
+        }
+
+        #[derive(Squattr, Debug)]
+        #[allow(dead_code)]
+        struct RouteAttribute {
+            path: String,
+            opts: Options,
+        }
+
+        let mut input: DeriveInput = parse_quote! {
+            #[my_framework::route(pathh = "/a", opts(retry(cout = 1)))]
+            struct Handler;
+        };
+
+        pretty_assertions::assert_eq!(
+            RouteAttribute::extract_from_attributes(&mut input, "my_framework::route")
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "squattr(E001): in `#[my_framework::route(...)]`: \
+                 unrecognized key `pathh`, did you mean `path`?",
+                "squattr(E001): in `opts.retry` of `#[my_framework::route(...)]`: \
+                 unrecognized key `cout`, did you mean `count`?",
+                "squattr(E003): in `opts.retry` of `#[my_framework::route(...)]`: \
+                 required key `count` not found",
+                "squattr(E003): in `#[my_framework::route(...)]`: required key `path` not found",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "proptest")]
+    fn write_generated_attributes_derived() {
+        use proptest::{prop_assert_eq, test_runner::TestRunner};
+        use quote::ToTokens;
+        use squattr::strategy;
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Limits {
+            min: u32,
+            max: Option<f64>,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(flags, to_tokens)]
+        struct Methods {
+            get: bool,
+            post: bool,
+        }
+
+        #[derive(Squattr, PartialEq, Debug)]
+        #[squattr(to_tokens)]
+        struct Route {
+            path: String,
+            methods: Methods,
+            tags: Vec<Ident>,
+            verbose: bool,
+            retries: Option<u8>,
+            limits: Vec<Limits>,
+        }
+
+        TestRunner::default()
+            .run(&strategy::values(&Route::SCHEMA), |tokens| {
+                let route = Route::from_tokens(tokens).unwrap();
+                prop_assert_eq!(Route::from_tokens(route.to_token_stream()).unwrap(), route);
+                Ok(())
+            })
+            .unwrap();
     }
 }
@@ -0,0 +1,86 @@
+// The derive must expand in crates without an implicit prelude, and must not
+// pick up local items shadowing prelude names.
+#![no_implicit_prelude]
+
+mod hygiene {
+    #![allow(dead_code)]
+
+    use ::squattr::derive::Squattr;
+
+    struct Ok;
+    struct Some;
+    struct None;
+    struct Vec;
+    struct String;
+    struct Default;
+
+    #[derive(Squattr)]
+    #[squattr(to_tokens, partial)]
+    pub struct Route {
+        path: ::std::string::String,
+        retries: ::std::option::Option<u8>,
+        tags: ::std::vec::Vec<::std::string::String>,
+        verbose: bool,
+        #[squattr(deprecated = "use `path`")]
+        url: ::std::option::Option<::std::string::String>,
+        methods: Methods,
+    }
+
+    #[derive(::std::clone::Clone, Squattr)]
+    #[squattr(merge)]
+    pub struct Limits {
+        #[squattr(min = 1, max = 8)]
+        depth: ::std::option::Option<u8>,
+        #[squattr(min_items = 1, unique, conflicts_with("depth"))]
+        names: ::std::option::Option<::std::vec::Vec<::std::string::String>>,
+        #[squattr(try_from = "u8")]
+        port: ::std::option::Option<u16>,
+    }
+
+    #[derive(Squattr)]
+    pub struct Service {
+        route: Route,
+        mode: ::std::option::Option<Mode>,
+        auth: ::std::option::Option<Auth>,
+        size: ::std::option::Option<IntOrString>,
+    }
+
+    #[derive(Squattr)]
+    #[squattr(flags, to_tokens)]
+    pub struct Methods {
+        get: bool,
+        post: bool,
+    }
+
+    #[derive(Squattr)]
+    #[squattr(suggest(max = 2))]
+    pub enum Mode {
+        Fast,
+        Slow,
+    }
+
+    #[derive(Squattr)]
+    pub enum Auth {
+        Token { header: ::std::string::String },
+        Basic,
+    }
+
+    #[derive(Squattr)]
+    pub enum IntOrString {
+        Int(u64),
+        Str(::std::string::String),
+    }
+}
+
+#[test]
+fn expand_without_prelude() {
+    use ::squattr::attribute::Attribute;
+
+    hygiene::Service::from_tokens(::quote::quote! {
+        route(path = "/a", tags("a"), methods(get)),
+        mode = fast,
+        auth(token(header = "x")),
+        size = 1,
+    })
+    .unwrap();
+}
@@ -0,0 +1,53 @@
+use proc_macro::TokenStream;
+use squattr::{
+    attribute::Attribute,
+    derive::Squattr,
+    diagnostic::{self, Warning},
+};
+
+/// The arguments of the `#[route(...)]` attribute used by the UI tests, so that
+/// errors raised while parsing them are rendered by the compiler.
+///
+#[derive(Squattr)]
+#[allow(dead_code)]
+struct Route {
+    path: String,
+    method: Option<String>,
+    timeout: Option<u64>,
+    #[squattr(deprecated = "use `path` instead")]
+    url: Option<String>,
+}
+
+/// Check the arguments of `#[route(...)]` written as the input of a
+/// function-like macro (e.g. `route_args!(path = "/users")`), expanding to
+/// nothing unless they are invalid.
+///
+#[proc_macro]
+pub fn route_args(tokens: TokenStream) -> TokenStream {
+    match Route::from_tokens1(tokens) {
+        Ok(_) => TokenStream::new(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+/// Check the arguments of `#[route(...)]`, keeping the item it is placed on.
+/// Warnings (e.g. about a deprecated key) are written next to it, for the
+/// compiler to report them.
+///
+#[proc_macro_attribute]
+pub fn route(args: TokenStream, item: TokenStream) -> TokenStream {
+    let (route, warnings) = diagnostic::collect(|| Route::from_args(args));
+
+    let mut output: TokenStream = match route {
+        Ok(_) => warnings
+            .iter()
+            .map(|warning| TokenStream::from(Warning::to_tokens(warning)))
+            .collect(),
+        Err(error) => diagnostic::with_notes(error, warnings)
+            .into_compile_error()
+            .into(),
+    };
+
+    output.extend(item);
+    output
+}
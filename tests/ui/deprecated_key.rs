@@ -0,0 +1,8 @@
+#![deny(deprecated)]
+
+use squattr_test_macros::route;
+
+#[route(path = "/users", url = "/users")]
+fn users() {}
+
+fn main() {}
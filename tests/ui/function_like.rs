@@ -0,0 +1,5 @@
+use squattr_test_macros::route_args;
+
+route_args!(path = "/users", timeout = "5s");
+
+fn main() {}
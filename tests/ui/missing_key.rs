@@ -0,0 +1,6 @@
+use squattr_test_macros::route;
+
+#[route(method = "GET")]
+fn users() {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use squattr_test_macros::route;
+
+#[route(path = "/users", methd = "GET")]
+fn users() {}
+
+fn main() {}
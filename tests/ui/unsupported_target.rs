@@ -0,0 +1,15 @@
+use squattr::derive::Squattr;
+
+#[derive(Squattr)]
+struct Unnamed(String);
+
+#[derive(Squattr)]
+struct Unit;
+
+#[derive(Squattr)]
+#[squattr(flags)]
+struct Flags {
+    verbose: u8,
+}
+
+fn main() {}
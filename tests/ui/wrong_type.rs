@@ -0,0 +1,6 @@
+use squattr_test_macros::route;
+
+#[route(path = "/users", timeout = "30s")]
+fn users() {}
+
+fn main() {}